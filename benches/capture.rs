@@ -0,0 +1,84 @@
+// cargo bench --bench capture
+//
+// requests per second against a local keep-alive server with no capture hook, a
+// status-only hook and one that takes the whole body
+
+use peakrequests::{CaptureRequirements, PeakRequests};
+use std::io::{BufRead, BufReader, Write};
+use std::net::TcpListener;
+use std::thread;
+use std::time::Instant;
+
+const ROUNDS: u32 = 2_000;
+const BODY: usize = 16 * 1024;
+
+// answers every request on a connection with the same response until the client hangs up
+fn serve() -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    let mut response = format!("HTTP/1.1 200 OK\r\nContent-Length: {}\r\n\r\n", BODY).into_bytes();
+    response.extend(std::iter::repeat_n(b'x', BODY));
+    thread::spawn(move || {
+        for stream in listener.incoming() {
+            let Ok(mut stream) = stream else { continue };
+            let response = response.clone();
+            thread::spawn(move || {
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                loop {
+                    let mut line = String::new();
+                    loop {
+                        line.clear();
+                        if reader.read_line(&mut line).unwrap_or(0) == 0 {
+                            return;
+                        }
+                        if line == "\r\n" {
+                            break;
+                        }
+                    }
+                    if stream.write_all(&response).is_err() {
+                        return;
+                    }
+                }
+            });
+        }
+    });
+    format!("http://{}/", addr)
+}
+
+fn time(label: &str, mut client: PeakRequests, url: &str) {
+    for _ in 0..ROUNDS / 10 {
+        client.get(url).unwrap();
+    }
+    let started = Instant::now();
+    for _ in 0..ROUNDS {
+        client.get(url).unwrap();
+    }
+    let elapsed = started.elapsed();
+    println!(
+        "{:<10} {:>8.0} requests/s",
+        label,
+        f64::from(ROUNDS) / elapsed.as_secs_f64()
+    );
+}
+
+fn main() {
+    let url = serve();
+    time("no hook", PeakRequests::new(), &url);
+    time(
+        "status",
+        PeakRequests::new().on_capture(CaptureRequirements::STATUS, |capture| {
+            std::hint::black_box(capture.status());
+        }),
+        &url,
+    );
+    time(
+        "full body",
+        PeakRequests::new().on_capture(
+            CaptureRequirements::HEADERS | CaptureRequirements::body(usize::MAX),
+            |capture| {
+                std::hint::black_box(capture.body());
+            },
+        ),
+        &url,
+    );
+}
@@ -0,0 +1,64 @@
+// cargo bench --bench template
+//
+// renders one template over and over against building the same request from
+// scratch, the case templates are meant to make cheaper
+
+use peakrequests::PeakRequests;
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+const ROUNDS: u32 = 200_000;
+
+fn time(label: &str, mut f: impl FnMut(u32)) -> Duration {
+    for i in 0..ROUNDS / 10 {
+        f(i);
+    }
+    let started = Instant::now();
+    for i in 0..ROUNDS {
+        f(i);
+    }
+    let elapsed = started.elapsed();
+    println!(
+        "{:<10} {:>8.0} ns/request",
+        label,
+        elapsed.as_nanos() as f64 / f64::from(ROUNDS)
+    );
+    elapsed
+}
+
+fn main() {
+    let mut client = PeakRequests::new();
+    let template = client
+        .request("GET", "https://sync.example/v1/{tenant}/objects/{id}")
+        .header("Authorization", "Bearer abcdef0123456789")
+        .header("Accept", "application/json")
+        .header("If-None-Match", "{etag}")
+        .query("fields", "id,name,updated")
+        .template()
+        .unwrap();
+
+    let rendered = time("template", |i| {
+        let id = i.to_string();
+        black_box(
+            template
+                .render(&[("tenant", "acme corp"), ("id", &id), ("etag", "\"v1\"")])
+                .unwrap(),
+        );
+    });
+    let built = time("builder", |i| {
+        let url = format!("https://sync.example/v1/{}/objects/{}", "acme%20corp", i);
+        black_box(
+            client
+                .request("GET", &url)
+                .header("Authorization", "Bearer abcdef0123456789")
+                .header("Accept", "application/json")
+                .header("If-None-Match", "\"v1\"")
+                .query("fields", "id,name,updated")
+                .prepare(),
+        );
+    });
+    println!(
+        "template renders in {:.2}x the builder's time",
+        rendered.as_secs_f64() / built.as_secs_f64()
+    );
+}
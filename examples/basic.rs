@@ -30,7 +30,7 @@ fn main() {
     match get("https://httpbin.org/get") {
         Ok(response) => {
             println!("status: {}", response.status_code);
-            println!("body: {}", response.text);
+            println!("body: {}", response.text());
         }
         Err(e) => eprintln!("error: {}", e),
     }
@@ -40,7 +40,7 @@ fn main() {
     form_data.insert("key2", "value2");
 
     match post("https://httpbin.org/post", form_data) {
-        Ok(response) => println!("post response: {}", response.text),
+        Ok(response) => println!("post response: {}", response.text()),
         Err(e) => eprintln!("error: {}", e),
     }
 
@@ -51,7 +51,7 @@ fn main() {
     });
 
     match post_json("https://httpbin.org/post", json_data) {
-        Ok(response) => println!("json post response: {}", response.text),
+        Ok(response) => println!("json post response: {}", response.text()),
         Err(e) => eprintln!("error: {}", e),
     }
 
@@ -67,7 +67,7 @@ fn main() {
         .max_redirects(5);
 
     match client.get("https://httpbin.org/headers") {
-        Ok(response) => println!("client response: {}", response.text),
+        Ok(response) => println!("client response: {}", response.text()),
         Err(e) => eprintln!("error: {}", e),
     }
 }
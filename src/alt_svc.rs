@@ -0,0 +1,202 @@
+/*
+ * MIT License
+ *
+ * Copyright (c) 2025 Squirrel
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in
+ * all copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+use crate::prefer::{split_pair, split_unquoted};
+use crate::{contain, Callback, PeakError, PeakRequests, Response};
+use percent_encoding::percent_decode_str;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+// rfc 7838 says 24 hours when there's no ma
+const DEFAULT_MAX_AGE: Duration = Duration::from_secs(24 * 60 * 60);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AltService {
+    pub protocol_id: String,
+    // None means the same host the response came from
+    pub host: Option<String>,
+    pub port: u16,
+    pub max_age: Duration,
+    pub persist: bool,
+}
+
+pub(crate) type AltSvcHook = Callback<dyn Fn(&str, &[AltService]) + Send + Sync>;
+
+#[derive(Debug, Clone)]
+pub(crate) struct Learned {
+    service: AltService,
+    expires: Instant,
+}
+
+impl Response {
+    // an empty list for no header and for "clear" alike
+    pub fn alt_svc(&self) -> Vec<AltService> {
+        self.headers
+            .get("alt-svc")
+            .map(|header| parse_alt_svc(header))
+            .unwrap_or_default()
+    }
+}
+
+impl PeakRequests {
+    // called with the origin and what it advertised, every time a response has Alt-Svc
+    pub fn on_alt_svc(mut self, f: impl Fn(&str, &[AltService]) + Send + Sync + 'static) -> Self {
+        self.on_alt_svc = Some(Callback(Arc::new(f)));
+        self
+    }
+
+    // origin -> alternatives that haven't passed their ma yet. only recorded for
+    // now, requests still go to the origin itself.
+    pub fn known_alt_services(&self) -> HashMap<String, Vec<AltService>> {
+        let now = self.clock().now();
+        self.alt_services
+            .iter()
+            .map(|(origin, learned)| {
+                let live: Vec<AltService> = learned
+                    .iter()
+                    .filter(|learned| learned.expires > now)
+                    .map(|learned| learned.service.clone())
+                    .collect();
+                (origin.clone(), live)
+            })
+            .filter(|(_, live)| !live.is_empty())
+            .collect()
+    }
+
+    pub(crate) fn learn_alt_svc(&mut self, response: &Response) -> Result<(), PeakError> {
+        let Some(header) = response.headers.get("alt-svc") else {
+            return Ok(());
+        };
+        let Some(url) = self.parse_normalized(&response.url) else {
+            return Ok(());
+        };
+        let origin = url.origin().ascii_serialization();
+        let services = parse_alt_svc(header);
+
+        // a new header replaces whatever the origin said before, "clear" included
+        let now = self.clock().now();
+        if services.is_empty() {
+            self.alt_services.remove(&origin);
+        } else {
+            let learned = services
+                .iter()
+                .map(|service| Learned {
+                    service: service.clone(),
+                    expires: now + service.max_age,
+                })
+                .collect();
+            self.alt_services.insert(origin.clone(), learned);
+        }
+
+        if let Some(hook) = &self.on_alt_svc {
+            contain("on_alt_svc", || hook(&origin, &services))?;
+        }
+        Ok(())
+    }
+}
+
+fn parse_alt_svc(header: &str) -> Vec<AltService> {
+    if header.trim().eq_ignore_ascii_case("clear") {
+        return Vec::new();
+    }
+
+    split_unquoted(header, ',')
+        .iter()
+        .filter_map(|element| {
+            let params = split_unquoted(element, ';');
+            let (protocol_id, authority) = split_pair(params.first()?)?;
+            let authority = authority?;
+            let (host, port) = authority.rsplit_once(':')?;
+            let port = port.parse().ok()?;
+
+            let mut service = AltService {
+                protocol_id: percent_decode_str(&protocol_id)
+                    .decode_utf8_lossy()
+                    .into_owned(),
+                host: (!host.is_empty()).then(|| host.to_string()),
+                port,
+                max_age: DEFAULT_MAX_AGE,
+                persist: false,
+            };
+            for param in &params[1..] {
+                match split_pair(param) {
+                    Some((name, Some(value))) if name.eq_ignore_ascii_case("ma") => {
+                        if let Ok(seconds) = value.parse() {
+                            service.max_age = Duration::from_secs(seconds);
+                        }
+                    }
+                    Some((name, Some(value))) if name.eq_ignore_ascii_case("persist") => {
+                        service.persist = value == "1";
+                    }
+                    _ => {}
+                }
+            }
+            Some(service)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_alternatives() {
+        assert_eq!(
+            parse_alt_svc(
+                r#"h3=":443"; ma=3600, h2="alt.example.com:8443"; persist=1, h3%2D29=":8443""#
+            ),
+            [
+                AltService {
+                    protocol_id: "h3".to_string(),
+                    host: None,
+                    port: 443,
+                    max_age: Duration::from_secs(3600),
+                    persist: false,
+                },
+                AltService {
+                    protocol_id: "h2".to_string(),
+                    host: Some("alt.example.com".to_string()),
+                    port: 8443,
+                    max_age: DEFAULT_MAX_AGE,
+                    persist: true,
+                },
+                AltService {
+                    protocol_id: "h3-29".to_string(),
+                    host: None,
+                    port: 8443,
+                    max_age: DEFAULT_MAX_AGE,
+                    persist: false,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn clear_and_broken_entries_yield_nothing() {
+        assert!(parse_alt_svc(" Clear ").is_empty());
+        assert!(parse_alt_svc(r#"h3, h2=":notaport", h3="""#).is_empty());
+    }
+}
@@ -0,0 +1,300 @@
+/*
+ * MIT License
+ *
+ * Copyright (c) 2025 Squirrel
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in
+ * all copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+use crate::background::InFlight;
+use crate::{spawn_named, unique_token, PeakError, PeakRequests, PreparedRequest, Response};
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc::{self, SyncSender};
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+#[derive(Debug, Clone)]
+pub struct ArchiveConfig {
+    pub dir: PathBuf,
+    pub filter: fn(&PreparedRequest) -> bool,
+    // bodies past this are cut, the record says so
+    pub max_body: u64,
+    // name records by their sha256 and include the body's, otherwise a random name
+    pub hash: bool,
+    // exchanges waiting for the writer. when it's full new ones are dropped and counted.
+    pub queue: usize,
+}
+
+impl Default for ArchiveConfig {
+    fn default() -> Self {
+        ArchiveConfig {
+            dir: PathBuf::from("archive"),
+            filter: |_| true,
+            max_body: 1024 * 1024,
+            hash: true,
+            queue: 64,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ArchiveStats {
+    pub written: u64,
+    pub dropped: u64,
+    // the disk write itself failed
+    pub failed: u64,
+}
+
+#[derive(Debug, Default)]
+struct Counters {
+    written: AtomicU64,
+    dropped: AtomicU64,
+    failed: AtomicU64,
+}
+
+#[derive(Debug)]
+pub(crate) struct Archive {
+    filter: fn(&PreparedRequest) -> bool,
+    max_body: u64,
+    hash: bool,
+    sender: SyncSender<Record>,
+    counters: Arc<Counters>,
+    in_flight: Arc<InFlight>,
+}
+
+impl Archive {
+    pub(crate) fn in_flight(&self) -> &InFlight {
+        &self.in_flight
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct Record {
+    sent_at_ms: u128,
+    received_at_ms: u128,
+    request: RequestRecord,
+    response: ResponseRecord,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    body_sha256: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct RequestRecord {
+    method: String,
+    url: String,
+    headers: Vec<(String, String)>,
+}
+
+#[derive(Debug, Serialize)]
+struct ResponseRecord {
+    status: u16,
+    url: String,
+    headers: BTreeMap<String, String>,
+    body: String,
+    body_encoding: BodyEncoding,
+    body_truncated: bool,
+}
+
+// text bodies are stored as they are, anything else as base64 of the exact bytes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+enum BodyEncoding {
+    Utf8,
+    Base64,
+}
+
+impl PeakRequests {
+    pub fn archive(mut self, config: ArchiveConfig) -> Self {
+        let (sender, receiver) = mpsc::sync_channel::<Record>(config.queue);
+        let counters = Arc::new(Counters::default());
+        let in_flight = Arc::new(InFlight::default());
+        let (writer_counters, writer_in_flight) = (Arc::clone(&counters), Arc::clone(&in_flight));
+        let dir = config.dir.clone();
+        let hash = config.hash;
+        // exits once the client (and with it the sender) is dropped. without the
+        // thread the receiver is gone too, so every record counts as dropped
+        let writer = spawn_named("archive", move || {
+            for record in receiver {
+                let counter = match write_record(&dir, &record, hash) {
+                    Ok(()) => &writer_counters.written,
+                    Err(e) => {
+                        log::warn!("could not write archive record: {}", e);
+                        &writer_counters.failed
+                    }
+                };
+                counter.fetch_add(1, Ordering::SeqCst);
+                writer_in_flight.done();
+            }
+        });
+        if let Err(e) = writer {
+            log::error!("could not start the archive writer: {}", e);
+        }
+
+        self.archive = Some(Archive {
+            filter: config.filter,
+            max_body: config.max_body,
+            hash,
+            sender,
+            counters,
+            in_flight,
+        });
+        self
+    }
+
+    pub fn archive_stats(&self) -> ArchiveStats {
+        let Some(archive) = &self.archive else {
+            return ArchiveStats::default();
+        };
+        ArchiveStats {
+            written: archive.counters.written.load(Ordering::SeqCst),
+            dropped: archive.counters.dropped.load(Ordering::SeqCst),
+            failed: archive.counters.failed.load(Ordering::SeqCst),
+        }
+    }
+
+    fn redact(&self, headers: impl IntoIterator<Item = (String, String)>) -> Vec<(String, String)> {
+        headers
+            .into_iter()
+            .map(|(key, value)| {
+                let value = self.redaction.header(&key, &value).to_string();
+                (key, value)
+            })
+            .collect()
+    }
+
+    // only builds the record here, the disk write happens on the archive thread
+    pub(crate) fn archive_exchange(
+        &self,
+        request: &PreparedRequest,
+        response: &Response,
+        sent_at: SystemTime,
+    ) {
+        let Some(archive) = &self.archive else {
+            return;
+        };
+        if !(archive.filter)(request) {
+            return;
+        }
+
+        let mut headers: Vec<(String, String)> = self
+            .headers
+            .iter()
+            .map(|(key, value)| (key.clone(), value.clone()))
+            .collect();
+        headers.sort();
+        headers.extend(request.headers.iter().cloned());
+
+        // a spilled body is only read as far as the record needs, and hashed in chunks
+        let body_truncated = response.body.len() > archive.max_body;
+        let read = || -> io::Result<_> {
+            let kept = response.body.prefix(archive.max_body)?;
+            let sha256 = if archive.hash {
+                Some(body_sha256(response)?)
+            } else {
+                None
+            };
+            Ok((kept, sha256))
+        };
+        let (kept, body_sha256) = match read() {
+            Ok(read) => read,
+            Err(e) => {
+                log::warn!("could not read the spilled body for the archive: {}", e);
+                archive.counters.failed.fetch_add(1, Ordering::SeqCst);
+                return;
+            }
+        };
+        let (body, body_encoding) = encode_body(&kept, body_truncated);
+
+        let record = Record {
+            sent_at_ms: unix_millis(sent_at),
+            received_at_ms: unix_millis(SystemTime::now()),
+            request: RequestRecord {
+                method: request.method.clone(),
+                url: self.redaction.url(&request.url),
+                headers: self.redact(headers),
+            },
+            response: ResponseRecord {
+                status: response.status_code,
+                url: self.redaction.url(&response.url),
+                headers: self.redact(response.headers.clone()).into_iter().collect(),
+                body,
+                body_encoding,
+                body_truncated,
+            },
+            body_sha256,
+        };
+
+        // full queue, or the writer is gone: drop it rather than hold up the caller
+        archive.in_flight.start();
+        if archive.sender.try_send(record).is_err() {
+            archive.in_flight.done();
+            archive.counters.dropped.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+}
+
+fn body_sha256(response: &Response) -> io::Result<String> {
+    if let Some(sha256) = response.sha256() {
+        return Ok(sha256.to_string());
+    }
+    let mut hasher = Sha256::new();
+    io::copy(&mut response.body.reader()?, &mut hasher)?;
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+fn encode_body(body: &[u8], truncated: bool) -> (String, BodyEncoding) {
+    match std::str::from_utf8(body) {
+        Ok(text) => (text.to_string(), BodyEncoding::Utf8),
+        // the cut landed inside a character, keep the text up to it
+        Err(e) if truncated && e.error_len().is_none() => (
+            String::from_utf8_lossy(&body[..e.valid_up_to()]).into_owned(),
+            BodyEncoding::Utf8,
+        ),
+        Err(_) => (STANDARD.encode(body), BodyEncoding::Base64),
+    }
+}
+
+fn write_record(dir: &Path, record: &Record, hash: bool) -> Result<(), PeakError> {
+    let json = serde_json::to_vec_pretty(record)?;
+    let name = if hash {
+        format!("{:x}", Sha256::digest(&json))
+    } else {
+        unique_token()
+    };
+    fs::create_dir_all(dir)?;
+    // written aside and renamed so a reader never sees half a record
+    let temp = dir.join(format!(".{}.tmp", name));
+    fs::write(&temp, &json)?;
+    fs::rename(&temp, dir.join(format!("{}.json", name)))?;
+    Ok(())
+}
+
+fn unix_millis(time: SystemTime) -> u128 {
+    time.duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis())
+        .unwrap_or_default()
+}
@@ -0,0 +1,520 @@
+/*
+ * MIT License
+ *
+ * Copyright (c) 2025 Squirrel
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in
+ * all copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+use crate::{unique_token, PeakError, PeakRequests, PreparedRequest, Response};
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use md5::Md5;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+
+#[derive(Debug, Clone)]
+pub enum AuthScheme {
+    Basic {
+        username: String,
+        password: String,
+        preemptive: bool,
+    },
+    Digest {
+        username: String,
+        password: String,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AuthChallenge {
+    pub scheme: String,
+    pub params: HashMap<String, String>,
+    pub token68: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct DigestState {
+    realm: String,
+    nonce: String,
+    opaque: Option<String>,
+    algorithm: String,
+    qop: Option<String>,
+    nc: u32,
+}
+
+impl AuthScheme {
+    pub fn basic(username: &str, password: &str) -> Self {
+        AuthScheme::Basic {
+            username: username.to_string(),
+            password: password.to_string(),
+            preemptive: true,
+        }
+    }
+
+    pub fn digest(username: &str, password: &str) -> Self {
+        AuthScheme::Digest {
+            username: username.to_string(),
+            password: password.to_string(),
+        }
+    }
+}
+
+impl PeakRequests {
+    pub fn auth(mut self, scheme: AuthScheme) -> Self {
+        self.auth = Some(scheme);
+        self.digest = None;
+        self
+    }
+
+    pub(crate) fn execute_with_auth(
+        &mut self,
+        request: &PreparedRequest,
+    ) -> Result<Response, PeakError> {
+        if let Some(provider) = self.token_provider.clone() {
+            return self.execute_with_token(&provider, request);
+        }
+        let Some(scheme) = self.auth.clone() else {
+            return self.fetch(request);
+        };
+
+        let mut answered_challenge = false;
+        let mut retries = 0;
+        loop {
+            let mut attempt = request.clone();
+            if let Some(value) = self.authorization_header(&scheme, &attempt, answered_challenge) {
+                attempt.set_header("Authorization", value);
+            }
+
+            let response = self.fetch(&attempt)?;
+            if response.status_code != 401 {
+                return Ok(response);
+            }
+
+            let challenges = response.www_authenticate();
+
+            let retry = match &scheme {
+                AuthScheme::Basic { .. } => {
+                    !answered_challenge
+                        && challenges
+                            .iter()
+                            .any(|c| c.scheme.eq_ignore_ascii_case("basic"))
+                }
+                AuthScheme::Digest { .. } => {
+                    match challenges
+                        .iter()
+                        .find(|c| c.scheme.eq_ignore_ascii_case("digest"))
+                    {
+                        Some(challenge) => {
+                            let stale = challenge
+                                .params
+                                .get("stale")
+                                .is_some_and(|s| s.eq_ignore_ascii_case("true"));
+                            let retry = !answered_challenge || stale;
+                            if retry {
+                                self.digest = DigestState::from_challenge(challenge);
+                            }
+                            retry && self.digest.is_some()
+                        }
+                        None => false,
+                    }
+                }
+            };
+
+            // one retry for the first challenge, plus one more if the server says our nonce went stale
+            if !retry || retries == 2 {
+                return Ok(response);
+            }
+            retries += 1;
+            answered_challenge = true;
+        }
+    }
+
+    fn authorization_header(
+        &mut self,
+        scheme: &AuthScheme,
+        request: &PreparedRequest,
+        challenged: bool,
+    ) -> Option<String> {
+        match scheme {
+            AuthScheme::Basic {
+                username,
+                password,
+                preemptive,
+            } => {
+                if *preemptive || challenged {
+                    let credentials = STANDARD.encode(format!("{}:{}", username, password));
+                    Some(format!("Basic {}", credentials))
+                } else {
+                    None
+                }
+            }
+            AuthScheme::Digest { username, password } => {
+                let state = self.digest.as_mut()?;
+                Some(state.authorization(username, password, request))
+            }
+        }
+    }
+}
+
+impl Response {
+    pub fn www_authenticate(&self) -> Vec<AuthChallenge> {
+        self.headers
+            .get("www-authenticate")
+            .map(|header| parse_challenges(header))
+            .unwrap_or_default()
+    }
+}
+
+impl DigestState {
+    fn from_challenge(challenge: &AuthChallenge) -> Option<Self> {
+        let algorithm = challenge
+            .params
+            .get("algorithm")
+            .cloned()
+            .unwrap_or_else(|| "MD5".to_string());
+        hash_algorithm(&algorithm)?;
+
+        let qop = challenge.params.get("qop").and_then(|qop| {
+            qop.split(',')
+                .map(str::trim)
+                .find(|option| option.eq_ignore_ascii_case("auth"))
+                .map(|option| option.to_string())
+        });
+
+        Some(DigestState {
+            realm: challenge.params.get("realm").cloned().unwrap_or_default(),
+            nonce: challenge.params.get("nonce")?.clone(),
+            opaque: challenge.params.get("opaque").cloned(),
+            algorithm,
+            qop,
+            nc: 0,
+        })
+    }
+
+    fn authorization(
+        &mut self,
+        username: &str,
+        password: &str,
+        request: &PreparedRequest,
+    ) -> String {
+        let hash = hash_algorithm(&self.algorithm).unwrap_or(HashAlgorithm::Md5);
+        let uri = request_uri(&request.url);
+        self.nc += 1;
+        let nc = format!("{:08x}", self.nc);
+        let cnonce = unique_token();
+
+        let mut ha1 = hash.hex(&format!("{}:{}:{}", username, self.realm, password));
+        if self.algorithm.to_ascii_lowercase().ends_with("-sess") {
+            ha1 = hash.hex(&format!("{}:{}:{}", ha1, self.nonce, cnonce));
+        }
+        let ha2 = hash.hex(&format!("{}:{}", request.method, uri));
+
+        let response = match &self.qop {
+            Some(qop) => hash.hex(&format!(
+                "{}:{}:{}:{}:{}:{}",
+                ha1, self.nonce, nc, cnonce, qop, ha2
+            )),
+            None => hash.hex(&format!("{}:{}:{}", ha1, self.nonce, ha2)),
+        };
+
+        let mut header = format!(
+            "Digest username=\"{}\", realm=\"{}\", nonce=\"{}\", uri=\"{}\", algorithm={}, response=\"{}\"",
+            username, self.realm, self.nonce, uri, self.algorithm, response
+        );
+        if let Some(qop) = &self.qop {
+            header.push_str(&format!(", qop={}, nc={}, cnonce=\"{}\"", qop, nc, cnonce));
+        }
+        if let Some(opaque) = &self.opaque {
+            header.push_str(&format!(", opaque=\"{}\"", opaque));
+        }
+        header
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum HashAlgorithm {
+    Md5,
+    Sha256,
+}
+
+impl HashAlgorithm {
+    fn hex(self, input: &str) -> String {
+        match self {
+            HashAlgorithm::Md5 => format!("{:x}", Md5::digest(input.as_bytes())),
+            HashAlgorithm::Sha256 => format!("{:x}", Sha256::digest(input.as_bytes())),
+        }
+    }
+}
+
+fn hash_algorithm(name: &str) -> Option<HashAlgorithm> {
+    match name.to_ascii_uppercase().trim_end_matches("-SESS") {
+        "MD5" => Some(HashAlgorithm::Md5),
+        "SHA-256" => Some(HashAlgorithm::Sha256),
+        _ => None,
+    }
+}
+
+fn request_uri(url: &str) -> String {
+    match reqwest::Url::parse(url) {
+        Ok(parsed) => match parsed.query() {
+            Some(query) => format!("{}?{}", parsed.path(), query),
+            None => parsed.path().to_string(),
+        },
+        Err(_) => url.to_string(),
+    }
+}
+
+fn parse_challenges(header: &str) -> Vec<AuthChallenge> {
+    let mut cursor = Cursor::new(header);
+    let mut challenges = Vec::new();
+
+    loop {
+        cursor.skip(|c| c == ',' || c == ' ' || c == '\t');
+        if cursor.done() {
+            break;
+        }
+
+        let scheme = cursor.token();
+        if scheme.is_empty() {
+            cursor.bump();
+            continue;
+        }
+
+        cursor.skip(|c| c == ' ' || c == '\t');
+        let mut challenge = AuthChallenge {
+            scheme,
+            params: HashMap::new(),
+            token68: cursor.token68(),
+        };
+        if challenge.token68.is_some() {
+            challenges.push(challenge);
+            continue;
+        }
+
+        loop {
+            cursor.skip(|c| c == ',' || c == ' ' || c == '\t');
+            if cursor.done() {
+                break;
+            }
+
+            let start = cursor.pos;
+            let name = cursor.token();
+            if name.is_empty() {
+                break;
+            }
+            cursor.skip(|c| c == ' ' || c == '\t');
+            if cursor.peek() != Some('=') {
+                // a bare token after a comma starts the next challenge
+                cursor.pos = start;
+                break;
+            }
+            cursor.bump();
+            cursor.skip(|c| c == ' ' || c == '\t');
+            let value = if cursor.peek() == Some('"') {
+                cursor.quoted()
+            } else {
+                cursor.token()
+            };
+            challenge.params.insert(name.to_ascii_lowercase(), value);
+        }
+
+        challenges.push(challenge);
+    }
+
+    challenges
+}
+
+struct Cursor {
+    chars: Vec<char>,
+    pos: usize,
+}
+
+impl Cursor {
+    fn new(input: &str) -> Self {
+        Cursor {
+            chars: input.chars().collect(),
+            pos: 0,
+        }
+    }
+
+    fn done(&self) -> bool {
+        self.pos >= self.chars.len()
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn bump(&mut self) {
+        self.pos += 1;
+    }
+
+    fn skip(&mut self, mut skip: impl FnMut(char) -> bool) {
+        while self.peek().is_some_and(&mut skip) {
+            self.bump();
+        }
+    }
+
+    fn token(&mut self) -> String {
+        let start = self.pos;
+        self.skip(is_tchar);
+        self.chars[start..self.pos].iter().collect()
+    }
+
+    // token68 has to be the only thing in the challenge, so anything but a
+    // comma or the end of the header after it means these are auth-params
+    fn token68(&mut self) -> Option<String> {
+        let start = self.pos;
+        self.skip(|c| c.is_ascii_alphanumeric() || "-._~+/".contains(c));
+        if self.pos == start {
+            return None;
+        }
+        self.skip(|c| c == '=');
+        let end = self.pos;
+        self.skip(|c| c == ' ' || c == '\t');
+        if self.done() || self.peek() == Some(',') {
+            Some(self.chars[start..end].iter().collect())
+        } else {
+            self.pos = start;
+            None
+        }
+    }
+
+    fn quoted(&mut self) -> String {
+        let mut value = String::new();
+        self.bump();
+        while let Some(c) = self.peek() {
+            self.bump();
+            match c {
+                '"' => break,
+                '\\' => {
+                    if let Some(escaped) = self.peek() {
+                        value.push(escaped);
+                        self.bump();
+                    }
+                }
+                _ => value.push(c),
+            }
+        }
+        value
+    }
+}
+
+fn is_tchar(c: char) -> bool {
+    c.is_ascii_alphanumeric() || "!#$%&'*+-.^_`|~".contains(c)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn challenge(header: &str) -> AuthChallenge {
+        let mut challenges = parse_challenges(header);
+        assert_eq!(challenges.len(), 1, "{:?}", challenges);
+        challenges.remove(0)
+    }
+
+    #[test]
+    fn splits_challenges_at_the_next_scheme() {
+        let challenges = parse_challenges(
+            r#"Basic realm="simple", Bearer realm="api", error="invalid_token", Newauth abc123=="#,
+        );
+        let schemes: Vec<&str> = challenges.iter().map(|c| c.scheme.as_str()).collect();
+        assert_eq!(schemes, ["Basic", "Bearer", "Newauth"]);
+        assert_eq!(challenges[0].params["realm"], "simple");
+        assert_eq!(challenges[1].params["error"], "invalid_token");
+        assert_eq!(challenges[2].token68.as_deref(), Some("abc123=="));
+        assert!(challenges[2].params.is_empty());
+    }
+
+    #[test]
+    fn reads_quoted_params() {
+        let challenge =
+            challenge(r#"Digest REALM="a, \"quoted\" realm", qop="auth,auth-int", stale=TRUE"#);
+        assert_eq!(challenge.params["realm"], r#"a, "quoted" realm"#);
+        assert_eq!(challenge.params["qop"], "auth,auth-int");
+        assert_eq!(challenge.params["stale"], "TRUE");
+        assert_eq!(challenge.token68, None);
+    }
+
+    #[test]
+    fn skips_empty_elements() {
+        let challenges = parse_challenges(" , Basic realm=x,, ,Bearer");
+        assert_eq!(challenges.len(), 2);
+        assert_eq!(challenges[0].params["realm"], "x");
+        assert_eq!(challenges[1].scheme, "Bearer");
+    }
+
+    #[test]
+    fn digest_without_qop_matches_rfc_2617() {
+        let challenge = challenge(
+            r#"Digest realm="testrealm@host.com", nonce="dcd98b7102dd2f0e8b11d0f600bfb0c093""#,
+        );
+        let mut state = DigestState::from_challenge(&challenge).unwrap();
+        let request = PreparedRequest::new("GET", "http://host.com/dir/index.html");
+
+        let header = state.authorization("Mufasa", "Circle Of Life", &request);
+        let answer = self::challenge(&header);
+        assert_eq!(answer.params["uri"], "/dir/index.html");
+        assert_eq!(answer.params["algorithm"], "MD5");
+        assert_eq!(
+            answer.params["response"],
+            "670fd8c2df070c60b045671b8b24ff02"
+        );
+        assert!(!answer.params.contains_key("qop"));
+    }
+
+    #[test]
+    fn digest_with_qop_counts_and_hashes_the_cnonce() {
+        let challenge = challenge(
+            r#"Digest realm="testrealm@host.com", qop="auth,auth-int", nonce="dcd98b7102dd2f0e8b11d0f600bfb0c093", opaque="5ccc069c403ebaf9f0171e9517f40e41""#,
+        );
+        let mut state = DigestState::from_challenge(&challenge).unwrap();
+        let request = PreparedRequest::new("GET", "http://host.com/dir/index.html");
+
+        state.authorization("Mufasa", "Circle Of Life", &request);
+        let answer = self::challenge(&state.authorization("Mufasa", "Circle Of Life", &request));
+        assert_eq!(answer.params["qop"], "auth");
+        assert_eq!(answer.params["nc"], "00000002");
+        assert_eq!(answer.params["opaque"], "5ccc069c403ebaf9f0171e9517f40e41");
+
+        let md5 = HashAlgorithm::Md5;
+        let ha1 = md5.hex("Mufasa:testrealm@host.com:Circle Of Life");
+        let ha2 = md5.hex("GET:/dir/index.html");
+        let expected = md5.hex(&format!(
+            "{}:dcd98b7102dd2f0e8b11d0f600bfb0c093:00000002:{}:auth:{}",
+            ha1, answer.params["cnonce"], ha2
+        ));
+        assert_eq!(answer.params["response"], expected);
+    }
+
+    #[test]
+    fn digest_needs_a_nonce_and_a_known_algorithm() {
+        assert!(DigestState::from_challenge(&challenge(r#"Digest realm="r""#)).is_none());
+        assert!(
+            DigestState::from_challenge(&challenge(r#"Digest nonce="n", algorithm=SHA-512"#))
+                .is_none()
+        );
+        assert!(DigestState::from_challenge(&challenge(
+            r#"Digest nonce="n", algorithm=sha-256-sess"#
+        ))
+        .is_some());
+    }
+}
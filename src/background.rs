@@ -0,0 +1,326 @@
+/*
+ * MIT License
+ *
+ * Copyright (c) 2025 Squirrel
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in
+ * all copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+use crate::{
+    send_detached, spawn_named, PeakError, PeakRequests, PreparedRequest, Response,
+    ResponseSettings,
+};
+use reqwest::blocking::RequestBuilder;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Condvar, Mutex};
+use std::time::{Duration, Instant};
+
+// what happens to background work still queued or running when the client is dropped
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BackgroundDrop {
+    // block the drop until everything finished or the grace period ran out
+    Wait(Duration),
+    // return right away. queued requests are never sent (they end in ClientClosed),
+    // running ones finish on their own.
+    Abandon,
+}
+
+impl Default for BackgroundDrop {
+    fn default() -> Self {
+        BackgroundDrop::Wait(Duration::from_secs(5))
+    }
+}
+
+#[derive(Debug)]
+enum Slot {
+    Queued,
+    Running,
+    Done(Result<Response, PeakError>),
+    Taken,
+}
+
+#[derive(Debug)]
+struct Shared {
+    slot: Mutex<Slot>,
+    done: Condvar,
+}
+
+impl Shared {
+    fn finish(&self, result: Result<Response, PeakError>) {
+        *self.slot.lock().unwrap_or_else(|e| e.into_inner()) = Slot::Done(result);
+        self.done.notify_all();
+    }
+}
+
+// dropping the handle detaches, the request still goes out
+#[derive(Debug)]
+pub struct BackgroundHandle {
+    shared: Arc<Shared>,
+}
+
+impl BackgroundHandle {
+    // the result can only be taken once, after that this keeps returning None
+    pub fn try_result(&self) -> Option<Result<Response, PeakError>> {
+        take(&mut self.shared.slot.lock().unwrap_or_else(|e| e.into_inner()))
+    }
+
+    pub fn wait(&self, timeout: Duration) -> Option<Result<Response, PeakError>> {
+        let slot = self.shared.slot.lock().unwrap_or_else(|e| e.into_inner());
+        let (mut slot, _) = self
+            .shared
+            .done
+            .wait_timeout_while(slot, timeout, |slot| {
+                matches!(slot, Slot::Queued | Slot::Running)
+            })
+            .unwrap_or_else(|e| e.into_inner());
+        take(&mut slot)
+    }
+
+    // only works while the request is still queued, returns whether it was stopped in time
+    pub fn cancel(&self) -> bool {
+        let mut slot = self.shared.slot.lock().unwrap_or_else(|e| e.into_inner());
+        if !matches!(*slot, Slot::Queued) {
+            return false;
+        }
+        *slot = Slot::Done(Err(PeakError::Cancelled));
+        self.shared.done.notify_all();
+        true
+    }
+}
+
+fn take(slot: &mut Slot) -> Option<Result<Response, PeakError>> {
+    match std::mem::replace(slot, Slot::Taken) {
+        Slot::Done(result) => Some(result),
+        other => {
+            *slot = other;
+            None
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Job {
+    request_builder: RequestBuilder,
+    settings: ResponseSettings,
+    shared: Arc<Shared>,
+}
+
+// counts requests running off the caller's thread, so shutdown can wait for them
+#[derive(Debug, Default)]
+pub(crate) struct InFlight {
+    count: Mutex<usize>,
+    idle: Condvar,
+}
+
+#[derive(Debug)]
+pub(crate) struct BackgroundPool {
+    sender: Option<Sender<Job>>,
+    in_flight: Arc<InFlight>,
+    closed: Arc<AtomicBool>,
+    on_drop: BackgroundDrop,
+}
+
+impl BackgroundPool {
+    // runs with however many workers could be started, and only fails if none could
+    fn new(workers: usize, on_drop: BackgroundDrop) -> Result<Self, PeakError> {
+        let (sender, receiver) = mpsc::channel::<Job>();
+        let receiver = Arc::new(Mutex::new(receiver));
+        let in_flight = Arc::new(InFlight::default());
+        let closed = Arc::new(AtomicBool::new(false));
+        for started in 0..workers.max(1) {
+            let receiver = Arc::clone(&receiver);
+            let in_flight = Arc::clone(&in_flight);
+            let closed = Arc::clone(&closed);
+            let worker = spawn_named("background", move || work(&receiver, &in_flight, &closed));
+            match worker {
+                Ok(_) => {}
+                Err(error) if started == 0 => return Err(error),
+                Err(error) => {
+                    log::warn!("background pool runs with {} workers: {}", started, error);
+                    break;
+                }
+            }
+        }
+        Ok(BackgroundPool {
+            sender: Some(sender),
+            in_flight,
+            closed,
+            on_drop,
+        })
+    }
+
+    fn submit(&self, job: Job) {
+        self.in_flight.start();
+        if let Some(sender) = &self.sender {
+            if let Err(mpsc::SendError(job)) = sender.send(job) {
+                self.in_flight.done();
+                job.shared.finish(Err(PeakError::Unsupported(
+                    "background pool has shut down".into(),
+                )));
+            }
+        }
+    }
+}
+
+impl InFlight {
+    pub(crate) fn start(&self) {
+        *self.count.lock().unwrap_or_else(|e| e.into_inner()) += 1;
+    }
+
+    pub(crate) fn count(&self) -> usize {
+        *self.count.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    // returns how many were still going when the deadline passed
+    pub(crate) fn wait_until(&self, deadline: Instant) -> usize {
+        let mut count = self.count.lock().unwrap_or_else(|e| e.into_inner());
+        while *count > 0 {
+            let left = deadline.saturating_duration_since(Instant::now());
+            if left.is_zero() {
+                break;
+            }
+            count = self
+                .idle
+                .wait_timeout(count, left)
+                .unwrap_or_else(|e| e.into_inner())
+                .0;
+        }
+        *count
+    }
+
+    pub(crate) fn done(&self) {
+        let mut count = self.count.lock().unwrap_or_else(|e| e.into_inner());
+        *count -= 1;
+        if *count == 0 {
+            self.idle.notify_all();
+        }
+    }
+}
+
+fn work(receiver: &Mutex<Receiver<Job>>, in_flight: &InFlight, closed: &AtomicBool) {
+    loop {
+        // the lock is only held while waiting for the next job, not while sending it
+        let Ok(job) = receiver.lock().unwrap_or_else(|e| e.into_inner()).recv() else {
+            return;
+        };
+
+        let start = {
+            let mut slot = job.shared.slot.lock().unwrap_or_else(|e| e.into_inner());
+            match *slot {
+                Slot::Queued if closed.load(Ordering::SeqCst) => {
+                    *slot = Slot::Done(Err(PeakError::ClientClosed));
+                    false
+                }
+                Slot::Queued => {
+                    *slot = Slot::Running;
+                    true
+                }
+                // cancelled while it sat in the queue
+                _ => false,
+            }
+        };
+        if start {
+            job.shared
+                .finish(send_detached(job.request_builder, &job.settings));
+        } else {
+            job.shared.done.notify_all();
+        }
+        in_flight.done();
+    }
+}
+
+impl BackgroundPool {
+    pub(crate) fn in_flight(&self) -> &InFlight {
+        &self.in_flight
+    }
+
+    // for shutdown, which has already done its own waiting
+    pub(crate) fn abandon_on_drop(&mut self) {
+        self.on_drop = BackgroundDrop::Abandon;
+    }
+}
+
+impl Drop for BackgroundPool {
+    fn drop(&mut self) {
+        // closing the channel lets the workers exit once the queue is empty
+        self.sender = None;
+        if let BackgroundDrop::Wait(grace) = self.on_drop {
+            self.in_flight.wait_until(Instant::now() + grace);
+        }
+        self.closed.store(true, Ordering::SeqCst);
+    }
+}
+
+impl PeakRequests {
+    pub fn background_workers(mut self, workers: usize) -> Self {
+        self.background_workers = workers.max(1);
+        self
+    }
+
+    pub fn background_drop(mut self, on_drop: BackgroundDrop) -> Self {
+        self.background_drop = on_drop;
+        self
+    }
+
+    // for requests nobody waits on. goes through the client's headers, timeout and
+    // redirect limits, but not auth retries, robots or the journal, like mirroring.
+    pub fn send_background(&self, request: PreparedRequest) -> BackgroundHandle {
+        let shared = Arc::new(Shared {
+            slot: Mutex::new(Slot::Queued),
+            done: Condvar::new(),
+        });
+        let handle = BackgroundHandle {
+            shared: Arc::clone(&shared),
+        };
+
+        let request = self.outgoing(&request);
+        let client = match self.background_client.get() {
+            Some(client) => Ok(client.clone()),
+            None => self
+                .detached_client()
+                .map(|client| self.background_client.get_or_init(|| client).clone()),
+        };
+        let request_builder =
+            match client.and_then(|client| self.request_builder(&client, &request)) {
+                Ok(request_builder) => request_builder,
+                Err(error) => {
+                    shared.finish(Err(error));
+                    return handle;
+                }
+            };
+
+        let pool = match self.background.get() {
+            Some(pool) => pool,
+            None => match BackgroundPool::new(self.background_workers, self.background_drop) {
+                Ok(pool) => self.background.get_or_init(|| pool),
+                Err(error) => {
+                    shared.finish(Err(error));
+                    return handle;
+                }
+            },
+        };
+        pool.submit(Job {
+            request_builder,
+            settings: self.response_settings(),
+            shared,
+        });
+        handle
+    }
+}
@@ -0,0 +1,116 @@
+/*
+ * MIT License
+ *
+ * Copyright (c) 2025 Squirrel
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in
+ * all copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+use crate::{expect, Overrides, PeakError, PeakRequests, PreparedRequest, Response};
+#[cfg(feature = "json")]
+use serde_json::Value;
+use std::collections::HashMap;
+use std::time::Duration;
+
+pub struct PeakRequestBuilder<'a> {
+    pub(crate) client: &'a mut PeakRequests,
+    pub(crate) request: PreparedRequest,
+    pub(crate) overrides: Option<Overrides>,
+    pub(crate) expectation_retry: Option<expect::ExpectationRetry>,
+}
+
+impl PeakRequests {
+    pub fn request(&mut self, method: &str, url: &str) -> PeakRequestBuilder<'_> {
+        let request =
+            PreparedRequest::new(&method.to_ascii_uppercase(), url).with_redaction(&self.redaction);
+        PeakRequestBuilder {
+            client: self,
+            request,
+            overrides: None,
+            expectation_retry: None,
+        }
+    }
+}
+
+impl PeakRequestBuilder<'_> {
+    pub fn header(mut self, key: &str, value: &str) -> Self {
+        self.request
+            .headers
+            .push((key.to_string(), value.to_string()));
+        self
+    }
+
+    pub fn form(mut self, data: HashMap<&str, &str>) -> Self {
+        self.request.form = Some(
+            data.into_iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect(),
+        );
+        self
+    }
+
+    #[cfg(feature = "json")]
+    pub fn json(mut self, json: Value) -> Self {
+        self.request.json = Some(json);
+        self
+    }
+
+    pub fn query(mut self, key: &str, value: &str) -> Self {
+        self.request.append_query(key, value);
+        self
+    }
+
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.request.timeout = Some(timeout);
+        self
+    }
+
+    // written to the client's journal before it goes out, see journal()
+    #[cfg(feature = "json")]
+    pub fn durable(mut self) -> Self {
+        self.request.durable = true;
+        self
+    }
+
+    // durable and accept_fallback travel with the request, retry_on_expectation
+    // only means something to send_expecting and is left behind
+    pub fn prepare(mut self) -> PreparedRequest {
+        if self.expectation_retry.is_some() {
+            log::warn!("retry_on_expectation only applies to send_expecting, prepare() drops it");
+        }
+        self.apply_overrides();
+        self.request
+    }
+
+    pub fn send(mut self) -> Result<Response, PeakError> {
+        self.apply_overrides();
+        self.dispatch()
+    }
+
+    pub(crate) fn dispatch(&mut self) -> Result<Response, PeakError> {
+        self.client.dispatch(&self.request)
+    }
+
+    // scoped overrides only fill in what this request didn't set itself
+    pub(crate) fn apply_overrides(&mut self) {
+        if let Some(overrides) = self.overrides.take() {
+            overrides.apply(&mut self.request);
+        }
+    }
+}
@@ -0,0 +1,74 @@
+/*
+ * MIT License
+ *
+ * Copyright (c) 2025 Squirrel
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in
+ * all copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+use crate::Response;
+
+impl Response {
+    pub fn accepts_ranges(&self) -> bool {
+        self.header_tokens("accept-ranges")
+            .iter()
+            .any(|unit| unit.eq_ignore_ascii_case("bytes"))
+    }
+
+    // lowercased header names. a lone "*" (response varies on everything) comes
+    // back as vec!["*"] and swallows whatever else was listed
+    pub fn vary(&self) -> Vec<String> {
+        let mut names: Vec<String> = Vec::new();
+        for name in self.header_tokens("vary") {
+            if name == "*" {
+                return vec!["*".to_string()];
+            }
+            let name = name.to_ascii_lowercase();
+            if !names.contains(&name) {
+                names.push(name);
+            }
+        }
+        names
+    }
+
+    pub fn allow(&self) -> Vec<String> {
+        let mut methods: Vec<String> = Vec::new();
+        for method in self.header_tokens("allow") {
+            let method = method.to_ascii_uppercase();
+            if !methods.contains(&method) {
+                methods.push(method);
+            }
+        }
+        methods
+    }
+
+    // repeated headers are already folded with ", " by header_map
+    fn header_tokens(&self, name: &str) -> Vec<&str> {
+        self.headers
+            .get(name)
+            .map(|value| {
+                value
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|token| !token.is_empty())
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+}
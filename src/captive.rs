@@ -0,0 +1,163 @@
+/*
+ * MIT License
+ *
+ * Copyright (c) 2025 Squirrel
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in
+ * all copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+#[cfg(feature = "json")]
+use crate::content_type::media_type;
+use crate::PeakRequests;
+#[cfg(feature = "json")]
+use crate::{PeakError, PreparedRequest, Response};
+
+impl PeakRequests {
+    pub fn detect_captive_portal(mut self, detect: bool) -> Self {
+        self.detect_captive_portal = detect;
+        self
+    }
+
+    #[cfg(feature = "json")]
+    // only armed when we asked for json, so someone fetching html on purpose is never flagged
+    pub(crate) fn expects_json(&self, request: &PreparedRequest) -> bool {
+        let accept = request
+            .headers
+            .iter()
+            .rev()
+            .find(|(name, _)| name.eq_ignore_ascii_case("accept"))
+            .map(|(_, value)| value)
+            .or_else(|| {
+                self.headers
+                    .iter()
+                    .find(|(name, _)| name.eq_ignore_ascii_case("accept"))
+                    .map(|(_, value)| value)
+            });
+        accept.is_some_and(|accept| accept.to_ascii_lowercase().contains("json"))
+    }
+}
+
+#[cfg(feature = "json")]
+impl Response {
+    pub(crate) fn check_captive_portal(&self) -> Result<(), PeakError> {
+        if !self.captive_portal_check || !looks_like_html(self) {
+            return Ok(());
+        }
+        Err(PeakError::LikelyCaptivePortal {
+            portal_url_hint: portal_url_hint(&self.text()).map(|hint| self.resolve(&hint)),
+        })
+    }
+
+    fn resolve(&self, target: &str) -> String {
+        reqwest::Url::parse(&self.url)
+            .and_then(|base| base.join(target))
+            .map(|url| url.to_string())
+            .unwrap_or_else(|_| target.to_string())
+    }
+}
+
+#[cfg(feature = "json")]
+fn looks_like_html(response: &Response) -> bool {
+    if let Some(content_type) = response.headers.get("content-type") {
+        let media_type = media_type(content_type);
+        if media_type == "text/html" || media_type == "application/xhtml+xml" {
+            return true;
+        }
+    }
+    // plenty of portals send text/plain or nothing at all, so check the body too
+    let start: String = response
+        .text()
+        .trim_start_matches('\u{feff}')
+        .trim_start()
+        .chars()
+        .take(15)
+        .collect::<String>()
+        .to_ascii_lowercase();
+    start.starts_with("<!doctype html") || start.starts_with("<html") || start.starts_with("<head")
+}
+
+// meta refresh wins over a form action, it's where the portal actually sends you
+#[cfg(feature = "json")]
+fn portal_url_hint(html: &str) -> Option<String> {
+    let refresh = tags(html, "meta").find_map(|tag| {
+        let http_equiv = attribute(tag, "http-equiv")?;
+        if !http_equiv.eq_ignore_ascii_case("refresh") {
+            return None;
+        }
+        let content = attribute(tag, "content")?;
+        let at = content.to_ascii_lowercase().find("url=")?;
+        let url = content[at + 4..]
+            .trim()
+            .trim_matches(|c| c == '\'' || c == '"');
+        (!url.is_empty()).then(|| url.to_string())
+    });
+
+    refresh.or_else(|| {
+        tags(html, "form")
+            .find_map(|tag| attribute(tag, "action"))
+            .filter(|action| !action.is_empty())
+    })
+}
+
+#[cfg(feature = "json")]
+fn tags<'a>(html: &'a str, name: &'a str) -> impl Iterator<Item = &'a str> + 'a {
+    let lower = html.to_ascii_lowercase();
+    let open = format!("<{}", name);
+    let starts: Vec<usize> = lower
+        .match_indices(&open)
+        .map(|(at, _)| at)
+        .filter(|at| {
+            lower[at + open.len()..]
+                .starts_with(|c: char| c.is_ascii_whitespace() || c == '>' || c == '/')
+        })
+        .collect();
+    starts.into_iter().map(move |start| {
+        let end = html[start..]
+            .find('>')
+            .map_or(html.len(), |end| start + end);
+        &html[start..end]
+    })
+}
+
+#[cfg(feature = "json")]
+fn attribute(tag: &str, name: &str) -> Option<String> {
+    let lower = tag.to_ascii_lowercase();
+    let mut from = 0;
+    while let Some(found) = lower[from..].find(name) {
+        let at = from + found;
+        from = at + name.len();
+        let preceded_by_space = lower[..at].ends_with(|c: char| c.is_ascii_whitespace());
+        let rest = lower[from..].trim_start();
+        if !preceded_by_space || !rest.starts_with('=') {
+            continue;
+        }
+
+        let value_start = tag.len() - rest.len() + 1;
+        let value = tag[value_start..].trim_start();
+        return Some(match value.chars().next() {
+            Some(quote @ ('"' | '\'')) => value[1..].split(quote).next().unwrap_or("").to_string(),
+            _ => value
+                .split(|c: char| c.is_ascii_whitespace() || c == '>')
+                .next()
+                .unwrap_or("")
+                .to_string(),
+        });
+    }
+    None
+}
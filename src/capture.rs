@@ -0,0 +1,213 @@
+/*
+ * MIT License
+ *
+ * Copyright (c) 2025 Squirrel
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in
+ * all copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+use crate::{contain, Callback, PeakError, PeakRequests, PreparedRequest, Response};
+use bytes::Bytes;
+use std::collections::HashMap;
+use std::ops::BitOr;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+// what a capture hook needs from each exchange, declared when it's registered.
+// combine with |, e.g. CaptureRequirements::HEADERS | CaptureRequirements::body(512)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CaptureRequirements {
+    headers: bool,
+    body: Option<usize>,
+}
+
+impl CaptureRequirements {
+    // method, url, status and timing, which every capture has anyway
+    pub const STATUS: CaptureRequirements = CaptureRequirements {
+        headers: false,
+        body: None,
+    };
+    pub const HEADERS: CaptureRequirements = CaptureRequirements {
+        headers: true,
+        body: None,
+    };
+
+    // the first limit bytes of the body
+    pub const fn body(limit: usize) -> Self {
+        CaptureRequirements {
+            headers: false,
+            body: Some(limit),
+        }
+    }
+}
+
+impl BitOr for CaptureRequirements {
+    type Output = CaptureRequirements;
+
+    fn bitor(self, other: CaptureRequirements) -> CaptureRequirements {
+        CaptureRequirements {
+            headers: self.headers || other.headers,
+            body: self.body.max(other.body),
+        }
+    }
+}
+
+// one exchange as a hook sees it. headers are borrowed from the response and the
+// body is a slice of it, so handing them out copies nothing
+#[derive(Debug)]
+pub struct Capture<'a> {
+    request: &'a PreparedRequest,
+    response: &'a Response,
+    elapsed: Duration,
+    headers: bool,
+    body: Option<Bytes>,
+}
+
+impl Capture<'_> {
+    pub fn method(&self) -> &str {
+        &self.request.method
+    }
+
+    // with the client's redaction profile applied
+    pub fn display_url(&self) -> String {
+        self.response.redaction.url(&self.request.url)
+    }
+
+    pub fn status(&self) -> u16 {
+        self.response.status_code
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        self.elapsed
+    }
+
+    // None unless the hook asked for HEADERS
+    pub fn headers(&self) -> Option<&HashMap<String, String>> {
+        self.headers.then_some(&self.response.headers)
+    }
+
+    // None unless the hook asked for a body, cut to its limit
+    pub fn body(&self) -> Option<&Bytes> {
+        self.body.as_ref()
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CaptureStats {
+    pub captures: u64,
+    pub header_bytes: u64,
+    pub body_bytes: u64,
+    // since the first capture
+    pub elapsed: Duration,
+}
+
+impl CaptureStats {
+    pub fn bytes_per_second(&self) -> f64 {
+        let seconds = self.elapsed.as_secs_f64();
+        if seconds == 0.0 {
+            return 0.0;
+        }
+        (self.header_bytes + self.body_bytes) as f64 / seconds
+    }
+}
+
+type CaptureHook = Callback<dyn Fn(&Capture) + Send + Sync>;
+
+#[derive(Debug, Default)]
+pub(crate) struct Captures {
+    hooks: Vec<(CaptureRequirements, CaptureHook)>,
+    // the union of what the hooks asked for, the most any exchange has to provide
+    wanted: CaptureRequirements,
+    captures: u64,
+    header_bytes: u64,
+    body_bytes: u64,
+    first: Option<Instant>,
+}
+
+impl PeakRequests {
+    // called after every exchange made on the calling thread, like the archive.
+    // with no hooks nothing is captured at all.
+    pub fn on_capture(
+        mut self,
+        requirements: CaptureRequirements,
+        f: impl Fn(&Capture) + Send + Sync + 'static,
+    ) -> Self {
+        let captures = &mut self.captures;
+        captures.wanted = captures.wanted | requirements;
+        captures.hooks.push((requirements, Callback(Arc::new(f))));
+        self
+    }
+
+    pub fn capture_stats(&self) -> CaptureStats {
+        let captures = &self.captures;
+        CaptureStats {
+            captures: captures.captures,
+            header_bytes: captures.header_bytes,
+            body_bytes: captures.body_bytes,
+            elapsed: captures.first.map_or(Duration::ZERO, |first| {
+                self.clock().now().saturating_duration_since(first)
+            }),
+        }
+    }
+
+    pub(crate) fn capture_exchange(
+        &mut self,
+        request: &PreparedRequest,
+        response: &Response,
+        elapsed: Duration,
+    ) -> Result<(), PeakError> {
+        if self.captures.hooks.is_empty() {
+            return Ok(());
+        }
+        let now = self.clock().now();
+        let captures = &mut self.captures;
+        captures.first.get_or_insert(now);
+        captures.captures += 1;
+
+        let wanted = captures.wanted;
+        if wanted.headers {
+            captures.header_bytes += response
+                .headers
+                .iter()
+                .map(|(key, value)| (key.len() + value.len()) as u64)
+                .sum::<u64>();
+        }
+        // read once for the hook that wants the most, the others get slices of it
+        let body = match wanted.body {
+            Some(limit) => Some(response.body.prefix(limit as u64)?),
+            None => None,
+        };
+        captures.body_bytes += body.as_ref().map_or(0, |body| body.len() as u64);
+
+        for (requirements, hook) in &captures.hooks {
+            let capture = Capture {
+                request,
+                response,
+                elapsed,
+                headers: requirements.headers,
+                body: requirements
+                    .body
+                    .zip(body.as_ref())
+                    .map(|(limit, body)| body.slice(..body.len().min(limit))),
+            };
+            contain("on_capture", || hook(&capture))?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,110 @@
+/*
+ * MIT License
+ *
+ * Copyright (c) 2025 Squirrel
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in
+ * all copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+use crate::{PeakError, PeakRequests, PreparedRequest, Response};
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Validators {
+    pub etag: Option<String>,
+    pub last_modified: Option<String>,
+    pub content_length: Option<u64>,
+}
+
+#[derive(Debug)]
+pub enum FetchResult {
+    NotModified,
+    Fetched(Response),
+}
+
+impl Validators {
+    pub fn from_response(response: &Response) -> Self {
+        Validators {
+            etag: response.headers.get("etag").cloned(),
+            last_modified: response.headers.get("last-modified").cloned(),
+            content_length: response
+                .headers
+                .get("content-length")
+                .and_then(|length| length.trim().parse().ok()),
+        }
+    }
+
+    fn matches(&self, response: &Response) -> bool {
+        let seen = Validators::from_response(response);
+        match (&self.etag, &seen.etag) {
+            (Some(known), Some(seen)) => return known == seen,
+            (Some(_), None) => return false,
+            _ => {}
+        }
+        match (&self.last_modified, &seen.last_modified) {
+            (Some(known), Some(seen)) => return known == seen,
+            (Some(_), None) => return false,
+            _ => {}
+        }
+        match (self.content_length, seen.content_length) {
+            (Some(known), Some(seen)) => known == seen,
+            _ => false,
+        }
+    }
+}
+
+impl PeakRequests {
+    pub fn fetch_if_changed(
+        &mut self,
+        url: &str,
+        known: Validators,
+    ) -> Result<FetchResult, PeakError> {
+        if known.etag.is_some() || known.last_modified.is_some() {
+            let mut request = PreparedRequest::new("GET", url);
+            if let Some(etag) = &known.etag {
+                request
+                    .headers
+                    .push(("If-None-Match".to_string(), etag.clone()));
+            }
+            if let Some(last_modified) = &known.last_modified {
+                request
+                    .headers
+                    .push(("If-Modified-Since".to_string(), last_modified.clone()));
+            }
+
+            let response = self.execute_prepared(&request)?;
+            // some servers ignore conditionals and send the same thing back with a 200
+            if response.status_code == 304
+                || (response.status_code == 200 && known.matches(&response))
+            {
+                return Ok(FetchResult::NotModified);
+            }
+            return Ok(FetchResult::Fetched(response));
+        }
+
+        if known.content_length.is_some() {
+            // anything but a 2xx (405 when HEAD is forbidden, etc) just falls through to GET
+            let probe = self.head(url)?;
+            if (200..300).contains(&probe.status_code) && known.matches(&probe) {
+                return Ok(FetchResult::NotModified);
+            }
+        }
+
+        Ok(FetchResult::Fetched(self.get(url)?))
+    }
+}
@@ -0,0 +1,56 @@
+/*
+ * MIT License
+ *
+ * Copyright (c) 2025 Squirrel
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in
+ * all copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+use crate::{PeakError, PeakRequestBuilder, PeakRequests};
+use reqwest::blocking::Client;
+
+impl PeakRequests {
+    // every request gets Connection: close and nothing is kept in the pool
+    pub fn disable_keep_alive(mut self, disable: bool) -> Self {
+        self.disable_keep_alive = disable;
+        self
+    }
+
+    // a client of its own for one request, so the connection can't be reused on
+    // http2 either, where hyper drops the Connection header
+    pub(crate) fn one_off_client(
+        &self,
+        proxy: Option<&Option<String>>,
+    ) -> Result<Client, PeakError> {
+        // no override leaves it to the client, whose own default is reqwest's
+        // environment proxies
+        Ok(self
+            .client_builder_via(proxy.map(Option::as_deref))?
+            .pool_max_idle_per_host(0)
+            .build()?)
+    }
+}
+
+impl PeakRequestBuilder<'_> {
+    // one fresh connection for this request only, the client's pool is left alone
+    pub fn connection_close(mut self) -> Self {
+        self.request.connection_close = true;
+        self
+    }
+}
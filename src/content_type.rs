@@ -0,0 +1,110 @@
+/*
+ * MIT License
+ *
+ * Copyright (c) 2025 Squirrel
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in
+ * all copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+#[cfg(feature = "json")]
+use crate::PeakError;
+use crate::{PeakRequests, Response};
+
+const SNIPPET_LEN: usize = 200;
+
+impl PeakRequests {
+    pub fn strict_content_type(mut self, strict: bool) -> Self {
+        self.strict_content_type = strict;
+        self
+    }
+}
+
+impl Response {
+    pub fn content_type(&self) -> Option<String> {
+        self.headers
+            .get("content-type")
+            .map(|value| media_type(value))
+            .filter(|value| !value.is_empty())
+    }
+
+    #[cfg(feature = "json")]
+    pub fn json_strict(&self) -> Result<serde_json::Value, PeakError> {
+        self.check_captive_portal()?;
+        self.expect_content_type("application/json", is_json_media_type)?;
+        Ok(serde_json::from_str(&self.text())?)
+    }
+
+    #[cfg(feature = "json")]
+    pub(crate) fn expect_content_type(
+        &self,
+        expected: &str,
+        compatible: fn(&str) -> bool,
+    ) -> Result<(), PeakError> {
+        let got = self.content_type();
+        if got.as_deref().is_some_and(compatible) {
+            return Ok(());
+        }
+        Err(PeakError::UnexpectedContentType {
+            expected: expected.to_string(),
+            got,
+            body_snippet: snippet(&self.text()),
+        })
+    }
+}
+
+pub(crate) fn media_type(value: &str) -> String {
+    value
+        .split(';')
+        .next()
+        .unwrap_or("")
+        .trim()
+        .to_ascii_lowercase()
+}
+
+// the charset parameter of a Content-Type value, quotes stripped
+pub(crate) fn charset(value: &str) -> Option<&str> {
+    value.split(';').skip(1).find_map(|param| {
+        let (name, value) = param.split_once('=')?;
+        name.trim()
+            .eq_ignore_ascii_case("charset")
+            .then(|| value.trim().trim_matches('"'))
+    })
+}
+
+#[cfg(feature = "json")]
+pub(crate) fn is_json_media_type(media_type: &str) -> bool {
+    media_type == "application/json" || media_type.ends_with("+json")
+}
+
+// checks a response media type against one entry of an Accept list, wildcards included
+pub(crate) fn media_type_matches(accept: &str, got: &str) -> bool {
+    let accept = media_type(accept);
+    match accept.split_once('/') {
+        Some(("*", "*")) => true,
+        Some((kind, "*")) => got.split('/').next() == Some(kind),
+        _ => accept == got,
+    }
+}
+
+pub(crate) fn snippet(text: &str) -> String {
+    match text.char_indices().nth(SNIPPET_LEN) {
+        Some((end, _)) => format!("{}...", &text[..end]),
+        None => text.to_string(),
+    }
+}
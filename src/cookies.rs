@@ -0,0 +1,423 @@
+/*
+ * MIT License
+ *
+ * Copyright (c) 2025 Squirrel
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in
+ * all copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+use crate::{PeakError, PeakRequests};
+use reqwest::cookie::CookieStore;
+use reqwest::header::{HeaderValue, SET_COOKIE};
+use reqwest::Url;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+#[cfg(feature = "json")]
+use std::fs;
+#[cfg(feature = "json")]
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StoredCookie {
+    pub name: String,
+    pub value: String,
+    pub domain: String,
+    pub path: String,
+    // unix seconds, None for a session cookie
+    pub expiry: Option<u64>,
+    pub secure: bool,
+    pub http_only: bool,
+    #[serde(default)]
+    pub host_only: bool,
+}
+
+#[derive(Debug, Default)]
+pub(crate) struct CookieJar {
+    cookies: Mutex<Vec<StoredCookie>>,
+}
+
+impl PeakRequests {
+    // a client that keeps cookies between calls, same as new().cookies(true)
+    pub fn session() -> Self {
+        PeakRequests::new().cookies(true)
+    }
+
+    pub fn cookies(mut self, enabled: bool) -> Self {
+        self.cookie_jar = if enabled {
+            self.cookie_jar
+                .or_else(|| Some(Arc::new(CookieJar::default())))
+        } else {
+            None
+        };
+        self
+    }
+
+    // name -> value for what would be sent to url right now
+    pub fn get_cookies(&self, url: &str) -> HashMap<String, String> {
+        let (Some(jar), Ok(url)) = (&self.cookie_jar, Url::parse(url)) else {
+            return HashMap::new();
+        };
+        // longest path first, so that one wins when a name shows up twice
+        let mut cookies = HashMap::new();
+        for cookie in jar.matching(&url) {
+            cookies.entry(cookie.name).or_insert(cookie.value);
+        }
+        cookies
+    }
+
+    // a host-only session cookie for the url's host, as if the server had set it.
+    // turns the jar on if it wasn't already.
+    pub fn set_cookie(&mut self, url: &str, name: &str, value: &str) -> Result<(), PeakError> {
+        let shown = self.redaction.url(url);
+        let parsed =
+            Url::parse(url).map_err(|e| PeakError::InvalidUrl(format!("{}: {}", shown, e)))?;
+        let Some(host) = parsed.host_str() else {
+            return Err(PeakError::InvalidUrl(format!("{}: no host", shown)));
+        };
+        let cookie = StoredCookie {
+            name: name.to_string(),
+            value: value.to_string(),
+            domain: host.to_ascii_lowercase(),
+            path: "/".to_string(),
+            expiry: None,
+            secure: false,
+            http_only: false,
+            host_only: true,
+        };
+
+        if self.cookie_jar.is_none() {
+            self.cookie_jar = Some(Arc::new(CookieJar::default()));
+            // clients built so far don't know about the new jar
+            self.client = None;
+            self.proxy_clients.clear();
+        }
+        self.cookie_jar.as_ref().unwrap().store(cookie);
+        Ok(())
+    }
+
+    pub fn persist_session_cookies(mut self, persist: bool) -> Self {
+        self.persist_session_cookies = persist;
+        self
+    }
+
+    #[cfg(feature = "json")]
+    pub fn save_cookies(&self, path: impl AsRef<Path>) -> Result<(), PeakError> {
+        let cookies: Vec<StoredCookie> = match &self.cookie_jar {
+            Some(jar) => jar
+                .snapshot()
+                .into_iter()
+                .filter(|cookie| self.persist_session_cookies || cookie.expiry.is_some())
+                .collect(),
+            None => Vec::new(),
+        };
+        let json = serde_json::to_string_pretty(&cookies)?;
+        fs::write(path, json)?;
+        Ok(())
+    }
+
+    #[cfg(feature = "json")]
+    pub fn load_cookies(self, path: impl AsRef<Path>) -> Result<Self, PeakError> {
+        let text = fs::read_to_string(&path)?;
+        let cookies: Vec<StoredCookie> =
+            serde_json::from_str(&text).map_err(PeakError::CookieFile)?;
+
+        let mut client = self.cookies(true);
+        // shutdown() writes the jar back here
+        client.cookie_file = Some(path.as_ref().to_path_buf());
+        let jar = client.cookie_jar.as_ref().unwrap();
+        let now = now();
+        for cookie in cookies {
+            if cookie.expiry.is_none_or(|expiry| expiry > now) {
+                jar.store(cookie);
+            }
+        }
+        Ok(client)
+    }
+}
+
+impl CookieJar {
+    #[cfg(feature = "json")]
+    pub(crate) fn snapshot(&self) -> Vec<StoredCookie> {
+        let now = now();
+        self.lock()
+            .iter()
+            .filter(|cookie| cookie.expiry.is_none_or(|expiry| expiry > now))
+            .cloned()
+            .collect()
+    }
+
+    pub(crate) fn store(&self, cookie: StoredCookie) {
+        let mut cookies = self.lock();
+        cookies.retain(|existing| {
+            !(existing.name == cookie.name
+                && existing.domain == cookie.domain
+                && existing.path == cookie.path)
+        });
+        if cookie.expiry.is_none_or(|expiry| expiry > now()) {
+            cookies.push(cookie);
+        }
+    }
+
+    pub(crate) fn matching(&self, url: &Url) -> Vec<StoredCookie> {
+        let Some(host) = url.host_str().map(|h| h.to_ascii_lowercase()) else {
+            return Vec::new();
+        };
+        let secure = url.scheme() == "https";
+        let now = now();
+
+        let mut matching: Vec<StoredCookie> = self
+            .lock()
+            .iter()
+            .filter(|cookie| cookie.expiry.is_none_or(|expiry| expiry > now))
+            .filter(|cookie| secure || !cookie.secure)
+            .filter(|cookie| {
+                if cookie.host_only {
+                    host == cookie.domain
+                } else {
+                    domain_matches(&host, &cookie.domain)
+                }
+            })
+            .filter(|cookie| path_matches(url.path(), &cookie.path))
+            .cloned()
+            .collect();
+        // longer paths go first, per rfc 6265
+        matching.sort_by_key(|cookie| std::cmp::Reverse(cookie.path.len()));
+        matching
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Vec<StoredCookie>> {
+        self.cookies.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl CookieStore for CookieJar {
+    fn set_cookies(&self, cookie_headers: &mut dyn Iterator<Item = &HeaderValue>, url: &Url) {
+        for header in cookie_headers {
+            if let Some(cookie) = header.to_str().ok().and_then(|h| parse_set_cookie(h, url)) {
+                self.store(cookie);
+            }
+        }
+    }
+
+    fn cookies(&self, url: &Url) -> Option<HeaderValue> {
+        let header = self
+            .matching(url)
+            .iter()
+            .map(|cookie| format!("{}={}", cookie.name, cookie.value))
+            .collect::<Vec<_>>()
+            .join("; ");
+        if header.is_empty() {
+            return None;
+        }
+        HeaderValue::from_str(&header).ok()
+    }
+}
+
+// header_map only keeps the last Set-Cookie, this reads all of them
+pub(crate) fn response_cookies(response: &reqwest::blocking::Response) -> Vec<StoredCookie> {
+    response
+        .headers()
+        .get_all(SET_COOKIE)
+        .iter()
+        .filter_map(|header| header.to_str().ok())
+        .filter_map(|header| parse_set_cookie(header, response.url()))
+        .collect()
+}
+
+pub(crate) fn parse_set_cookie(header: &str, url: &Url) -> Option<StoredCookie> {
+    let host = url.host_str()?.to_ascii_lowercase();
+    let mut parts = header.split(';');
+    let (name, value) = parts.next()?.split_once('=')?;
+    let name = name.trim();
+    if name.is_empty() {
+        return None;
+    }
+
+    let mut cookie = StoredCookie {
+        name: name.to_string(),
+        value: value.trim().trim_matches('"').to_string(),
+        domain: host.clone(),
+        path: default_path(url.path()),
+        expiry: None,
+        secure: false,
+        http_only: false,
+        host_only: true,
+    };
+
+    let mut max_age = None;
+    for attribute in parts {
+        let (key, value) = match attribute.split_once('=') {
+            Some((key, value)) => (key.trim(), value.trim()),
+            None => (attribute.trim(), ""),
+        };
+        match key.to_ascii_lowercase().as_str() {
+            "domain" if !value.is_empty() => {
+                let domain = value.trim_start_matches('.').to_ascii_lowercase();
+                if !domain_matches(&host, &domain) {
+                    return None;
+                }
+                // rfc 6265 5.3 step 5: a public suffix can only name the host itself,
+                // and then the cookie stays host-only
+                if looks_like_public_suffix(&domain) {
+                    if domain != host {
+                        return None;
+                    }
+                } else {
+                    cookie.domain = domain;
+                    cookie.host_only = false;
+                }
+            }
+            "path" if value.starts_with('/') => cookie.path = value.to_string(),
+            "expires" => {
+                if let Ok(at) = httpdate::parse_http_date(value) {
+                    cookie.expiry = Some(unix_seconds(at));
+                }
+            }
+            "max-age" => max_age = value.parse::<i64>().ok(),
+            "secure" => cookie.secure = true,
+            "httponly" => cookie.http_only = true,
+            _ => {}
+        }
+    }
+
+    // max-age wins over expires, and anything <= 0 means delete it now
+    if let Some(max_age) = max_age {
+        cookie.expiry = Some(if max_age <= 0 {
+            0
+        } else {
+            now().saturating_add(max_age as u64)
+        });
+    }
+
+    Some(cookie)
+}
+
+fn domain_matches(host: &str, domain: &str) -> bool {
+    host == domain
+        || (host.ends_with(domain)
+            && host.as_bytes().get(host.len() - domain.len() - 1) == Some(&b'.'))
+}
+
+// stands in for the public suffix list, which isn't consulted: a single label
+// (Domain=com) or a ccTLD under a generic second level (co.uk, com.au) is refused.
+// private suffixes like github.io still get through.
+fn looks_like_public_suffix(domain: &str) -> bool {
+    const GENERIC_SECOND_LEVEL: &[&str] = &[
+        "ac", "co", "com", "edu", "go", "gov", "mil", "ne", "net", "or", "org",
+    ];
+    match domain.split('.').collect::<Vec<_>>().as_slice() {
+        [_] => true,
+        [second, tld] => tld.len() == 2 && GENERIC_SECOND_LEVEL.contains(second),
+        _ => false,
+    }
+}
+
+fn path_matches(request_path: &str, cookie_path: &str) -> bool {
+    request_path == cookie_path
+        || (request_path.starts_with(cookie_path)
+            && (cookie_path.ends_with('/')
+                || request_path.as_bytes().get(cookie_path.len()) == Some(&b'/')))
+}
+
+fn default_path(request_path: &str) -> String {
+    match request_path.rfind('/') {
+        Some(0) | None => "/".to_string(),
+        Some(at) => request_path[..at].to_string(),
+    }
+}
+
+fn unix_seconds(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+fn now() -> u64 {
+    unix_seconds(SystemTime::now())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(header: &str, url: &str) -> Option<StoredCookie> {
+        parse_set_cookie(header, &Url::parse(url).unwrap())
+    }
+
+    #[test]
+    fn reads_the_attributes() {
+        let cookie = parse(
+            "sid=\"abc\"; Path=/app; Domain=.Example.com; Secure; HttpOnly; Expires=Wed, 21 Oct 2015 07:28:00 GMT",
+            "https://www.example.com/login",
+        )
+        .unwrap();
+        assert_eq!(
+            cookie,
+            StoredCookie {
+                name: "sid".to_string(),
+                value: "abc".to_string(),
+                domain: "example.com".to_string(),
+                path: "/app".to_string(),
+                expiry: Some(1445412480),
+                secure: true,
+                http_only: true,
+                host_only: false,
+            }
+        );
+    }
+
+    #[test]
+    fn defaults_to_host_only_and_the_request_directory() {
+        let cookie = parse("a=1; Path=relative", "http://Example.com/docs/page").unwrap();
+        assert_eq!(cookie.domain, "example.com");
+        assert!(cookie.host_only);
+        assert_eq!(cookie.path, "/docs");
+        assert_eq!(cookie.expiry, None);
+
+        assert_eq!(parse("a=1", "http://example.com/").unwrap().path, "/");
+    }
+
+    #[test]
+    fn max_age_wins_over_expires() {
+        let cookie = parse(
+            "a=1; Max-Age=0; Expires=Wed, 21 Oct 2099 07:28:00 GMT",
+            "http://example.com/",
+        )
+        .unwrap();
+        assert_eq!(cookie.expiry, Some(0));
+
+        let cookie = parse("a=1; Max-Age=60", "http://example.com/").unwrap();
+        assert!(cookie.expiry.unwrap() >= now() + 59);
+    }
+
+    #[test]
+    fn refuses_foreign_domains_and_public_suffixes() {
+        assert_eq!(parse("=1", "http://example.com/"), None);
+        assert_eq!(parse("novalue", "http://example.com/"), None);
+        assert_eq!(parse("a=1; Domain=other.com", "http://example.com/"), None);
+        assert_eq!(parse("a=1; Domain=ample.com", "http://example.com/"), None);
+        assert_eq!(parse("a=1; Domain=co.uk", "http://shop.co.uk/"), None);
+        assert_eq!(parse("a=1; Domain=com", "http://example.com/"), None);
+
+        let cookie = parse("a=1; Domain=co.uk", "http://co.uk/").unwrap();
+        assert!(cookie.host_only);
+    }
+}
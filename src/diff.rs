@@ -0,0 +1,221 @@
+/*
+ * MIT License
+ *
+ * Copyright (c) 2025 Squirrel
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in
+ * all copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+use crate::Response;
+use serde::Serialize;
+#[cfg(feature = "json")]
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashMap};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum BodyComparison {
+    Exact,
+    #[cfg(feature = "json")]
+    NormalizedJson,
+    HashOnly,
+}
+
+#[derive(Debug, Clone)]
+pub struct DiffOptions {
+    ignore_headers: Vec<String>,
+    body: BodyComparison,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Change<T> {
+    pub from: T,
+    pub to: T,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub enum BodyDiff {
+    Same,
+    Changed {
+        from_len: usize,
+        to_len: usize,
+    },
+    HashChanged {
+        from_sha256: String,
+        to_sha256: String,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ResponseDiff {
+    pub status: Option<Change<u16>>,
+    pub headers_added: BTreeMap<String, String>,
+    pub headers_removed: BTreeMap<String, String>,
+    pub headers_changed: BTreeMap<String, Change<String>>,
+    pub body: BodyDiff,
+}
+
+impl Default for DiffOptions {
+    fn default() -> Self {
+        DiffOptions {
+            ignore_headers: vec!["date".to_string(), "set-cookie".to_string()],
+            body: BodyComparison::Exact,
+        }
+    }
+}
+
+impl DiffOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn ignore_header(mut self, name: &str) -> Self {
+        self.ignore_headers.push(name.to_ascii_lowercase());
+        self
+    }
+
+    pub fn ignore_headers(mut self, names: Vec<String>) -> Self {
+        self.ignore_headers = names.iter().map(|n| n.to_ascii_lowercase()).collect();
+        self
+    }
+
+    pub fn body(mut self, comparison: BodyComparison) -> Self {
+        self.body = comparison;
+        self
+    }
+
+    fn ignores(&self, name: &str) -> bool {
+        self.ignore_headers
+            .iter()
+            .any(|ignored| ignored.eq_ignore_ascii_case(name))
+    }
+}
+
+impl ResponseDiff {
+    pub fn is_same(&self) -> bool {
+        self.status.is_none()
+            && self.headers_added.is_empty()
+            && self.headers_removed.is_empty()
+            && self.headers_changed.is_empty()
+            && self.body == BodyDiff::Same
+    }
+}
+
+impl Response {
+    pub fn diff(&self, other: &Response, options: DiffOptions) -> ResponseDiff {
+        let status = if self.status_code != other.status_code {
+            Some(Change {
+                from: self.status_code,
+                to: other.status_code,
+            })
+        } else {
+            None
+        };
+
+        let old_headers = comparable_headers(&self.headers, &options);
+        let new_headers = comparable_headers(&other.headers, &options);
+
+        let mut headers_added = BTreeMap::new();
+        let mut headers_removed = BTreeMap::new();
+        let mut headers_changed = BTreeMap::new();
+
+        for (name, old_value) in &old_headers {
+            match new_headers.get(name) {
+                None => {
+                    headers_removed.insert(name.clone(), old_value.clone());
+                }
+                Some(new_value) if new_value != old_value => {
+                    headers_changed.insert(
+                        name.clone(),
+                        Change {
+                            from: old_value.clone(),
+                            to: new_value.clone(),
+                        },
+                    );
+                }
+                Some(_) => {}
+            }
+        }
+        for (name, new_value) in &new_headers {
+            if !old_headers.contains_key(name) {
+                headers_added.insert(name.clone(), new_value.clone());
+            }
+        }
+
+        ResponseDiff {
+            status,
+            headers_added,
+            headers_removed,
+            headers_changed,
+            body: diff_bodies(self.bytes_ref(), other.bytes_ref(), options.body),
+        }
+    }
+}
+
+fn comparable_headers(
+    headers: &HashMap<String, String>,
+    options: &DiffOptions,
+) -> BTreeMap<String, String> {
+    headers
+        .iter()
+        .filter(|(name, _)| !options.ignores(name))
+        .map(|(name, value)| (name.to_ascii_lowercase(), value.clone()))
+        .collect()
+}
+
+// on the raw bytes: two bodies that aren't utf-8 would decode to the same text
+fn diff_bodies(old: &[u8], new: &[u8], comparison: BodyComparison) -> BodyDiff {
+    let changed = BodyDiff::Changed {
+        from_len: old.len(),
+        to_len: new.len(),
+    };
+
+    match comparison {
+        BodyComparison::Exact => {
+            if old == new {
+                BodyDiff::Same
+            } else {
+                changed
+            }
+        }
+        #[cfg(feature = "json")]
+        BodyComparison::NormalizedJson => {
+            match (
+                serde_json::from_slice::<Value>(old),
+                serde_json::from_slice::<Value>(new),
+            ) {
+                (Ok(old_json), Ok(new_json)) if old_json == new_json => BodyDiff::Same,
+                (Ok(_), Ok(_)) => changed,
+                _ => diff_bodies(old, new, BodyComparison::Exact),
+            }
+        }
+        BodyComparison::HashOnly => {
+            let from_sha256 = format!("{:x}", Sha256::digest(old));
+            let to_sha256 = format!("{:x}", Sha256::digest(new));
+            if from_sha256 == to_sha256 {
+                BodyDiff::Same
+            } else {
+                BodyDiff::HashChanged {
+                    from_sha256,
+                    to_sha256,
+                }
+            }
+        }
+    }
+}
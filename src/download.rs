@@ -0,0 +1,395 @@
+/*
+ * MIT License
+ *
+ * Copyright (c) 2025 Squirrel
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in
+ * all copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+use crate::content_type::snippet;
+use crate::retry::{retry_reason, RetryHeaders, RetryInfo};
+use crate::{
+    classify_send_error, contain, spawn_scoped_named, Callback, FileSink, PeakError, PeakRequests,
+    Response, ResponseSettings, StreamingResponse,
+};
+use reqwest::blocking::{Client, RequestBuilder};
+use std::collections::HashSet;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::SystemTime;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DownloadJob {
+    pub url: String,
+    pub destination: PathBuf,
+}
+
+impl DownloadJob {
+    pub fn new(url: &str, destination: impl Into<PathBuf>) -> Self {
+        DownloadJob {
+            url: url.to_string(),
+            destination: destination.into(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SkipExisting {
+    Never,
+    Always,
+    // both of these HEAD the url first and only skip when the server agrees
+    IfSizeMatches,
+    IfEtagMatches,
+}
+
+#[derive(Debug)]
+pub enum DownloadOutcome {
+    Downloaded { destination: PathBuf, bytes: u64 },
+    Skipped { destination: PathBuf },
+    Failed { url: String, error: PeakError },
+}
+
+impl DownloadOutcome {
+    pub fn is_failure(&self) -> bool {
+        matches!(self, DownloadOutcome::Failed { .. })
+    }
+}
+
+#[derive(Debug)]
+pub struct DownloadProgress<'a> {
+    pub index: usize,
+    pub completed: usize,
+    pub total: usize,
+    pub outcome: &'a DownloadOutcome,
+}
+
+type ProgressHook = Callback<dyn Fn(&DownloadProgress) + Send + Sync>;
+
+#[derive(Debug, Clone)]
+pub struct BulkOptions {
+    concurrency: usize,
+    retries: usize,
+    skip_existing: SkipExisting,
+    on_progress: Option<ProgressHook>,
+}
+
+impl Default for BulkOptions {
+    fn default() -> Self {
+        BulkOptions {
+            concurrency: 4,
+            retries: 0,
+            skip_existing: SkipExisting::Never,
+            on_progress: None,
+        }
+    }
+}
+
+impl BulkOptions {
+    pub fn new() -> Self {
+        BulkOptions::default()
+    }
+
+    pub fn concurrency(mut self, concurrency: usize) -> Self {
+        self.concurrency = concurrency.max(1);
+        self
+    }
+
+    pub fn retries(mut self, retries: usize) -> Self {
+        self.retries = retries;
+        self
+    }
+
+    pub fn skip_existing(mut self, skip_existing: SkipExisting) -> Self {
+        self.skip_existing = skip_existing;
+        self
+    }
+
+    pub fn on_progress(mut self, f: impl Fn(&DownloadProgress) + Send + Sync + 'static) -> Self {
+        self.on_progress = Some(Callback(Arc::new(f)));
+        self
+    }
+}
+
+impl PeakRequests {
+    // outcomes come back in job order. a failed item never stops the batch.
+    pub fn download_all(&self, jobs: Vec<DownloadJob>, opts: BulkOptions) -> Vec<DownloadOutcome> {
+        let client = match self.detached_client() {
+            Ok(client) => client,
+            // the same settings fail the same way again, so every job gets its own
+            // typed error
+            Err(error) => {
+                let mut error = Some(error);
+                return jobs
+                    .into_iter()
+                    .map(|job| DownloadOutcome::Failed {
+                        url: job.url,
+                        error: error
+                            .take()
+                            .unwrap_or_else(|| match self.detached_client() {
+                                Err(error) => error,
+                                Ok(_) => PeakError::Io(io::Error::other("client is unavailable")),
+                            }),
+                    })
+                    .collect();
+            }
+        };
+
+        // two jobs writing the same file would race on the rename, the later one loses
+        let mut seen = HashSet::new();
+        let duplicate: Vec<bool> = jobs
+            .iter()
+            .map(|job| !seen.insert(job.destination.clone()))
+            .collect();
+
+        let settings = self.response_settings();
+        let total = jobs.len();
+        let next = AtomicUsize::new(0);
+        let completed = AtomicUsize::new(0);
+        let outcomes: Mutex<Vec<Option<DownloadOutcome>>> =
+            Mutex::new((0..total).map(|_| None).collect());
+
+        let mut spawn_error = None;
+        thread::scope(|scope| {
+            for _ in 0..opts.concurrency.min(total) {
+                let worker = spawn_scoped_named(scope, "download", || loop {
+                    let index = next.fetch_add(1, Ordering::SeqCst);
+                    let Some(job) = jobs.get(index) else {
+                        break;
+                    };
+
+                    let outcome = if duplicate[index] {
+                        DownloadOutcome::Failed {
+                            url: job.url.clone(),
+                            error: PeakError::Io(io::Error::new(
+                                io::ErrorKind::AlreadyExists,
+                                format!(
+                                    "{} is already the destination of an earlier job",
+                                    job.destination.display()
+                                ),
+                            )),
+                        }
+                    } else {
+                        download_one(&client, job, &opts, self.retry_headers.as_ref(), &settings)
+                    };
+
+                    // the download itself is done either way, so a panicking hook
+                    // only gets logged
+                    if let Some(on_progress) = &opts.on_progress {
+                        let progress = DownloadProgress {
+                            index,
+                            completed: completed.fetch_add(1, Ordering::SeqCst) + 1,
+                            total,
+                            outcome: &outcome,
+                        };
+                        if let Err(e) = contain("on_progress", || on_progress(&progress)) {
+                            log::warn!("{}", e);
+                        }
+                    }
+                    outcomes.lock().unwrap_or_else(|e| e.into_inner())[index] = Some(outcome);
+                });
+                // the workers that did start still get through every job
+                if let Err(error) = worker {
+                    spawn_error = Some(error);
+                    break;
+                }
+            }
+        });
+
+        // only left unvisited if no worker started at all
+        outcomes
+            .into_inner()
+            .unwrap_or_else(|e| e.into_inner())
+            .into_iter()
+            .zip(jobs)
+            .map(|(outcome, job)| {
+                outcome.unwrap_or_else(|| DownloadOutcome::Failed {
+                    url: job.url,
+                    error: spawn_error.take().unwrap_or_else(|| {
+                        PeakError::Io(io::Error::other("no download worker could be started"))
+                    }),
+                })
+            })
+            .collect()
+    }
+}
+
+fn download_one(
+    client: &Client,
+    job: &DownloadJob,
+    opts: &BulkOptions,
+    retry_headers: Option<&RetryHeaders>,
+    settings: &ResponseSettings,
+) -> DownloadOutcome {
+    let first_attempt_at = SystemTime::now();
+    let mut retry: Option<RetryInfo> = None;
+    let mut attempt = 0;
+    loop {
+        let tag = |request_builder: RequestBuilder| match retry_headers {
+            Some(names) => names.apply(request_builder, retry.as_ref()),
+            None => request_builder,
+        };
+        let result =
+            should_skip(client, job, opts.skip_existing, &tag, settings).and_then(|skip| {
+                if skip {
+                    return Ok(None);
+                }
+                fetch_to_file(client, job, opts.skip_existing, &tag, settings).map(Some)
+            });
+
+        match result {
+            Ok(Some(bytes)) => {
+                return DownloadOutcome::Downloaded {
+                    destination: job.destination.clone(),
+                    bytes,
+                }
+            }
+            Ok(None) => {
+                return DownloadOutcome::Skipped {
+                    destination: job.destination.clone(),
+                }
+            }
+            Err(error) if attempt < opts.retries && retryable(&error) => {
+                attempt += 1;
+                retry = Some(RetryInfo {
+                    // counting the first try, so the first retry is attempt 2
+                    attempt: attempt + 1,
+                    reason: retry_reason(&error),
+                    first_attempt_at,
+                });
+            }
+            Err(error) => {
+                return DownloadOutcome::Failed {
+                    url: job.url.clone(),
+                    error,
+                }
+            }
+        }
+    }
+}
+
+// a 404 isn't going to fix itself, everything else gets another go
+fn retryable(error: &PeakError) -> bool {
+    match error {
+        PeakError::Status { status, .. } => *status >= 500 || *status == 429,
+        PeakError::Http(_) => true,
+        _ => false,
+    }
+}
+
+fn should_skip(
+    client: &Client,
+    job: &DownloadJob,
+    mode: SkipExisting,
+    tag: &dyn Fn(RequestBuilder) -> RequestBuilder,
+    settings: &ResponseSettings,
+) -> Result<bool, PeakError> {
+    let Ok(metadata) = fs::metadata(&job.destination) else {
+        return Ok(false);
+    };
+
+    match mode {
+        SkipExisting::Never => Ok(false),
+        SkipExisting::Always => Ok(true),
+        SkipExisting::IfSizeMatches => {
+            // content_length() describes the (empty) HEAD body, not the resource
+            let response = tag(client.head(&job.url))
+                .send()
+                .map_err(|e| classify_send_error(e, &settings.redaction))?;
+            let length = response
+                .headers()
+                .get("content-length")
+                .and_then(|value| value.to_str().ok())
+                .and_then(|value| value.trim().parse::<u64>().ok());
+            Ok(response.status().is_success() && length == Some(metadata.len()))
+        }
+        SkipExisting::IfEtagMatches => {
+            let Ok(known) = fs::read_to_string(etag_path(&job.destination)) else {
+                return Ok(false);
+            };
+            let response = tag(client.head(&job.url))
+                .send()
+                .map_err(|e| classify_send_error(e, &settings.redaction))?;
+            let etag = response
+                .headers()
+                .get("etag")
+                .and_then(|value| value.to_str().ok());
+            Ok(response.status().is_success() && etag == Some(known.trim()))
+        }
+    }
+}
+
+fn fetch_to_file(
+    client: &Client,
+    job: &DownloadJob,
+    mode: SkipExisting,
+    tag: &dyn Fn(RequestBuilder) -> RequestBuilder,
+    settings: &ResponseSettings,
+) -> Result<u64, PeakError> {
+    let started = settings.now();
+    let response = tag(client.get(&job.url))
+        .send()
+        .map_err(|e| classify_send_error(e, &settings.redaction))?;
+    settings.check_head(response.headers())?;
+    if !response.status().is_success() {
+        // the body is only there for the error, a failed read just leaves it empty
+        let response = Response::from_reqwest_with(response, settings, started, |response| {
+            Ok(response.bytes().unwrap_or_default())
+        })?;
+        return Err(PeakError::Status {
+            status: response.status_code,
+            url: response.display_url(),
+            body_snippet: snippet(&response.text()),
+            #[cfg(feature = "json")]
+            problem: None,
+            response: Box::new(response),
+        });
+    }
+
+    if let Some(parent) = job.destination.parent() {
+        if !parent.as_os_str().is_empty() {
+            fs::create_dir_all(parent)?;
+        }
+    }
+
+    let etag = response
+        .headers()
+        .get("etag")
+        .and_then(|value| value.to_str().ok())
+        .map(str::to_string);
+    let bytes =
+        StreamingResponse::new(response).pipe(vec![Box::new(FileSink::new(&job.destination)?)])?;
+
+    // remembered next to the file so the next run has something to compare against
+    if mode == SkipExisting::IfEtagMatches {
+        if let Some(etag) = etag {
+            fs::write(etag_path(&job.destination), etag)?;
+        }
+    }
+    Ok(bytes)
+}
+
+fn etag_path(destination: &Path) -> PathBuf {
+    let mut name = destination.file_name().unwrap_or_default().to_os_string();
+    name.push(".etag");
+    destination.with_file_name(name)
+}
@@ -0,0 +1,230 @@
+/*
+ * MIT License
+ *
+ * Copyright (c) 2025 Squirrel
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in
+ * all copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+use crate::{AuthScheme, PeakRequests};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::env;
+
+// what a client will actually do once every default, override and environment
+// variable is folded in. for bug reports, so anything secret is already masked.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct EffectiveConfig {
+    pub client_built: bool,
+    pub timeout_secs: Option<u64>,
+    pub redirects: EffectiveRedirects,
+    pub proxy: EffectiveProxy,
+    pub tls: EffectiveTls,
+    pub http2: EffectiveHttp2,
+    pub pool: EffectivePool,
+    pub limits: EffectiveLimits,
+    // default headers with the redaction profile applied
+    pub headers: BTreeMap<String, String>,
+    // basic, digest, token_provider or none
+    pub auth: &'static str,
+    // the names sent on retries, None when emit_retry_headers is off
+    pub retry_headers: Option<[String; 3]>,
+    // every opt-in behaviour that's switched on, sorted
+    pub features: Vec<&'static str>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct EffectiveRedirects {
+    pub follow: bool,
+    pub max: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct EffectiveProxy {
+    // builder, environment or none
+    pub source: &'static str,
+    pub http: Option<String>,
+    pub https: Option<String>,
+    pub no_proxy: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct EffectiveTls {
+    pub backend: &'static str,
+    pub require_san: bool,
+    pub check_revocation: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct EffectiveHttp2 {
+    pub http1_only: bool,
+    pub stream_window: Option<u32>,
+    pub connection_window: Option<u32>,
+    pub adaptive_window: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct EffectivePool {
+    pub keep_alive: bool,
+    pub keepalive_ping_secs: Option<u64>,
+    pub background_workers: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct EffectiveLimits {
+    pub max_response_header_size: usize,
+    pub max_header_count: usize,
+    pub max_url_length: Option<usize>,
+}
+
+// the first of the names that's set and not empty, the same precedence reqwest uses
+fn env_var(names: &[&str]) -> Option<String> {
+    names
+        .iter()
+        .filter_map(|name| env::var(name).ok())
+        .find(|value| !value.is_empty())
+}
+
+impl PeakRequests {
+    // works before the first request builds the client and after. the proxy comes
+    // from the environment as it is now, reqwest reads it when the client is built.
+    pub fn effective_config(&self) -> EffectiveConfig {
+        let proxy = match &self.proxy {
+            Some(url) => EffectiveProxy {
+                source: "builder",
+                http: Some(self.redaction.url(url)),
+                https: Some(self.redaction.url(url)),
+                no_proxy: None,
+            },
+            None => {
+                let all = env_var(&["ALL_PROXY", "all_proxy"]);
+                // HTTP_PROXY can be set by a client in a cgi request, so reqwest ignores it there
+                let http = if env::var_os("REQUEST_METHOD").is_some() {
+                    env_var(&["http_proxy"])
+                } else {
+                    env_var(&["HTTP_PROXY", "http_proxy"])
+                }
+                .or_else(|| all.clone());
+                let https = env_var(&["HTTPS_PROXY", "https_proxy"]).or(all);
+                let source = if http.is_some() || https.is_some() {
+                    "environment"
+                } else {
+                    "none"
+                };
+                EffectiveProxy {
+                    source,
+                    http: http.map(|url| self.redaction.url(&url)),
+                    https: https.map(|url| self.redaction.url(&url)),
+                    no_proxy: env_var(&["NO_PROXY", "no_proxy"]),
+                }
+            }
+        };
+
+        let auth = match (&self.auth, &self.token_provider) {
+            (_, Some(_)) => "token_provider",
+            (Some(AuthScheme::Basic { .. }), None) => "basic",
+            (Some(AuthScheme::Digest { .. }), None) => "digest",
+            (None, None) => "none",
+        };
+
+        let flags = [
+            ("cookies", self.cookie_jar.is_some()),
+            ("persist_session_cookies", self.persist_session_cookies),
+            ("strict_content_type", self.strict_content_type),
+            ("lenient_framing", self.lenient_framing),
+            ("normalize_outgoing", self.normalize_outgoing),
+            ("robots", self.robots.is_some()),
+            ("mirror", self.mirror.is_some()),
+            ("compute_digest", self.body_digest.is_some()),
+            ("convert_long_get_to_post", self.long_get.is_some()),
+            ("proxy_loop_detection", self.proxy_loop_token.is_some()),
+            ("detect_captive_portal", self.detect_captive_portal),
+            ("spill_to_disk", self.spill.is_some()),
+        ];
+        #[cfg(feature = "json")]
+        let flags = flags.into_iter().chain([
+            ("journal", self.journal.is_some()),
+            ("archive", self.archive.is_some()),
+        ]);
+        let mut features: Vec<&'static str> = flags
+            .into_iter()
+            .filter(|(_, on)| *on)
+            .map(|(name, _)| name)
+            .collect();
+        features.sort_unstable();
+
+        EffectiveConfig {
+            client_built: self.client.is_some(),
+            timeout_secs: self.timeout,
+            redirects: EffectiveRedirects {
+                follow: self.allow_redirects,
+                max: self.max_redirects,
+            },
+            proxy,
+            tls: EffectiveTls {
+                // reqwest's default features keep native-tls as the default backend
+                backend: if self.require_san {
+                    "rustls"
+                } else {
+                    "native-tls"
+                },
+                require_san: self.require_san,
+                check_revocation: self.check_revocation,
+            },
+            http2: EffectiveHttp2 {
+                http1_only: self.http2.http1_only,
+                stream_window: self.http2.stream_window,
+                connection_window: self.http2.connection_window,
+                adaptive_window: self.http2.adaptive_window,
+            },
+            pool: EffectivePool {
+                keep_alive: !self.disable_keep_alive,
+                keepalive_ping_secs: self
+                    .keepalive
+                    .as_ref()
+                    .map(|keepalive| keepalive.interval.as_secs()),
+                background_workers: self.background_workers,
+            },
+            limits: EffectiveLimits {
+                max_response_header_size: self.max_response_header_size,
+                max_header_count: self.max_header_count,
+                max_url_length: self.max_url_length,
+            },
+            headers: self
+                .headers
+                .iter()
+                .map(|(name, value)| {
+                    (
+                        name.to_ascii_lowercase(),
+                        self.redaction.header(name, value).to_string(),
+                    )
+                })
+                .collect(),
+            auth,
+            retry_headers: self.retry_headers.as_ref().map(|names| {
+                [
+                    names.attempt.clone(),
+                    names.reason.clone(),
+                    names.first_attempt_at.clone(),
+                ]
+            }),
+            features,
+        }
+    }
+}
@@ -0,0 +1,183 @@
+/*
+ * MIT License
+ *
+ * Copyright (c) 2025 Squirrel
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in
+ * all copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+#[cfg(feature = "json")]
+use crate::problem::{describe_status, ProblemDetails};
+use crate::{AttemptRecord, Response};
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum PeakError {
+    #[error("{0}")]
+    Http(#[from] reqwest::Error),
+    #[error("invalid header: {0}")]
+    InvalidHeader(String),
+    #[error("invalid url: {0}")]
+    InvalidUrl(String),
+    #[error("url is {length} bytes, over the {limit} byte limit")]
+    UrlTooLong { length: usize, limit: usize },
+    #[error("invalid pattern: {0}")]
+    InvalidPattern(#[from] regex::Error),
+    #[error("unsupported HTTP method: {0}")]
+    UnsupportedMethod(String),
+    #[cfg(feature = "json")]
+    #[error("{0}")]
+    Json(#[from] serde_json::Error),
+    #[error("{0}")]
+    Io(#[from] std::io::Error),
+    #[cfg(feature = "json")]
+    #[error("invalid cookie file: {0}")]
+    CookieFile(serde_json::Error),
+    #[error("no acceptable content type, tried: {}", attempted.join(", "))]
+    NotAcceptable { attempted: Vec<String> },
+    #[error("disallowed by robots.txt ({rule})")]
+    DisallowedByRobots { rule: String },
+    #[error(
+        "expected {expected} but got {}: {body_snippet}",
+        got.as_deref().unwrap_or("no content type")
+    )]
+    UnexpectedContentType {
+        expected: String,
+        got: Option<String>,
+        body_snippet: String,
+    },
+    #[error(
+        "request template: missing values for [{}], unknown placeholders [{}]",
+        missing.join(", "),
+        unused.join(", ")
+    )]
+    Template {
+        missing: Vec<String>,
+        unused: Vec<String>,
+    },
+    #[error("response headers too large ({count} headers, {size} bytes)")]
+    HeadersTooLarge { size: usize, count: usize },
+    #[error("response body is over the {limit} byte limit")]
+    BodyTooLarge { limit: u64 },
+    #[error("too many redirects (max {max})")]
+    TooManyRedirects { max: usize },
+    #[error("proxy loop detected, our own via token came back: {via}")]
+    ProxyLoop { via: String },
+    #[error("pagination loop: server repeated cursor {cursor}")]
+    PaginationLoop { cursor: String },
+    #[error("upgrade required: {}", protocols.join(", "))]
+    UpgradeRequired {
+        protocols: Vec<String>,
+        response: Box<Response>,
+    },
+    #[error(
+        "got an html page where json was expected, probably a captive portal{}",
+        portal_url_hint.as_ref().map(|hint| format!(" ({})", hint)).unwrap_or_default()
+    )]
+    LikelyCaptivePortal { portal_url_hint: Option<String> },
+    #[error("malformed response: {reason}")]
+    MalformedResponse { reason: String },
+    #[error("token provider failed: {0}")]
+    TokenProvider(String),
+    #[error("tls {check} check failed: {message}")]
+    Tls { check: String, message: String },
+    #[error("unsupported: {0}")]
+    Unsupported(String),
+    #[error("{option} needs {needed_feature}, which this build doesn't have")]
+    UnsupportedFeature {
+        option: &'static str,
+        needed_feature: &'static str,
+    },
+    #[error("relay failed reading the source {url}: {source}")]
+    RelaySource { url: String, source: std::io::Error },
+    #[error("unix socket {}: {reason}", path.display())]
+    UnixSocket {
+        path: std::path::PathBuf,
+        reason: String,
+        source: std::io::Error,
+    },
+    #[error("the client was shut down before this request was sent")]
+    ClientClosed,
+    #[error("{hook} panicked: {message}")]
+    HookPanicked { hook: &'static str, message: String },
+    #[error("background request was cancelled before it was sent")]
+    Cancelled,
+    #[error("{last} (gave up after {} attempts)", attempts.len())]
+    RetriesExhausted {
+        attempts: Vec<AttemptRecord>,
+        last: Box<PeakError>,
+    },
+    #[error("body of {url} doesn't match the digest the server sent")]
+    DigestMismatch { url: String },
+    #[cfg(feature = "json")]
+    #[error("request journal is full (max {max_bytes} bytes)")]
+    JournalFull { max_bytes: u64 },
+    #[error("expected {wanted}, got status {got_status}: {body_snippet}")]
+    Expectation {
+        wanted: String,
+        got_status: u16,
+        body_snippet: String,
+    },
+    #[error("body sink {index} ({name}) failed: {source}")]
+    Sink {
+        index: usize,
+        name: String,
+        source: Box<PeakError>,
+    },
+    #[cfg_attr(
+        feature = "json",
+        error("{}", describe_status(*status, url, problem.as_deref()))
+    )]
+    #[cfg_attr(not(feature = "json"), error("HTTP status {status} for {url}"))]
+    Status {
+        status: u16,
+        url: String,
+        body_snippet: String,
+        #[cfg(feature = "json")]
+        problem: Option<Box<ProblemDetails>>,
+        // kept whole so callers can look at headers or the full body after the fact,
+        // Display never prints it
+        response: Box<Response>,
+    },
+}
+
+impl PeakError {
+    pub fn response(&self) -> Option<&Response> {
+        match self {
+            PeakError::Status { response, .. } => Some(response),
+            PeakError::UpgradeRequired { response, .. } => Some(response),
+            PeakError::RetriesExhausted { last, .. } => last.response(),
+            _ => None,
+        }
+    }
+
+    // empty unless the error came out of a retry loop that tried more than once
+    pub fn attempts(&self) -> &[AttemptRecord] {
+        match self {
+            PeakError::RetriesExhausted { attempts, .. } => attempts,
+            _ => &[],
+        }
+    }
+}
+
+impl From<PeakError> for String {
+    fn from(error: PeakError) -> String {
+        error.to_string()
+    }
+}
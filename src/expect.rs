@@ -0,0 +1,124 @@
+/*
+ * MIT License
+ *
+ * Copyright (c) 2025 Squirrel
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in
+ * all copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+use crate::content_type::snippet;
+use crate::{AttemptOutcome, AttemptRecord, PeakError, PeakRequestBuilder, Response};
+use std::time::Duration;
+
+const DEFAULT_RETRIES: usize = 3;
+const DEFAULT_RETRY_DELAY: Duration = Duration::from_secs(1);
+
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct ExpectationRetry {
+    retries: usize,
+    delay: Duration,
+}
+
+impl PeakRequestBuilder<'_> {
+    // with send_expecting*, a mismatch or a transport error sends again instead of
+    // failing straight away. for waiting on a deploy to come up.
+    pub fn retry_on_expectation(mut self, retry: bool) -> Self {
+        self.expectation_retry = retry.then_some(ExpectationRetry {
+            retries: DEFAULT_RETRIES,
+            delay: DEFAULT_RETRY_DELAY,
+        });
+        self
+    }
+
+    // turns retry_on_expectation on as well
+    pub fn expectation_retries(mut self, retries: usize, delay: Duration) -> Self {
+        self.expectation_retry = Some(ExpectationRetry { retries, delay });
+        self
+    }
+
+    pub fn send_expecting(self, status: u16) -> Result<Response, PeakError> {
+        self.send_checked(status, None)
+    }
+
+    pub fn send_expecting_body(self, status: u16, needle: &str) -> Result<Response, PeakError> {
+        self.send_checked(status, Some(needle))
+    }
+
+    fn send_checked(mut self, status: u16, needle: Option<&str>) -> Result<Response, PeakError> {
+        self.apply_overrides();
+        let retry = self.expectation_retry.unwrap_or(ExpectationRetry {
+            retries: 0,
+            delay: Duration::ZERO,
+        });
+
+        let mut attempts: Vec<AttemptRecord> = Vec::new();
+        loop {
+            let started = self.client.clock().now();
+            let result = self
+                .dispatch()
+                .and_then(|response| check(response, status, needle));
+            let retrying = attempts.len() < retry.retries
+                && matches!(
+                    result,
+                    Err(PeakError::Expectation { .. } | PeakError::Http(_))
+                );
+            attempts.push(AttemptRecord {
+                attempt: attempts.len() + 1,
+                outcome: AttemptOutcome::of(&result),
+                elapsed: self.client.clock().now().saturating_duration_since(started),
+                backoff: if retrying {
+                    retry.delay
+                } else {
+                    Duration::ZERO
+                },
+            });
+            if retrying {
+                self.client.clock().sleep(retry.delay);
+                continue;
+            }
+            return match result {
+                Ok(mut response) => {
+                    response.attempts = attempts.into();
+                    Ok(response)
+                }
+                Err(last) if attempts.len() > 1 => Err(PeakError::RetriesExhausted {
+                    attempts,
+                    last: Box::new(last),
+                }),
+                Err(last) => Err(last),
+            };
+        }
+    }
+}
+
+fn check(response: Response, status: u16, needle: Option<&str>) -> Result<Response, PeakError> {
+    let body_matches = needle.is_none_or(|needle| response.text().contains(needle));
+    if response.status_code == status && body_matches {
+        return Ok(response);
+    }
+    let wanted = match needle {
+        Some(needle) => format!("status {} with a body containing {:?}", status, needle),
+        None => format!("status {}", status),
+    };
+    Err(PeakError::Expectation {
+        wanted,
+        got_status: response.status_code,
+        body_snippet: snippet(&response.text()),
+    })
+}
@@ -0,0 +1,146 @@
+/*
+ * MIT License
+ *
+ * Copyright (c) 2025 Squirrel
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in
+ * all copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+use crate::prefer::{pair, split_pair, split_unquoted};
+use crate::PeakRequestBuilder;
+use std::net::Ipv6Addr;
+
+// rfc 7239. values are an ip (ipv6 with a port must already be bracketed,
+// "[2001:db8::1]:8080"), "unknown", or an obfuscated "_name". quoting is handled here.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ForwardedElement {
+    pub for_: Option<String>,
+    pub by: Option<String>,
+    pub host: Option<String>,
+    pub proto: Option<String>,
+}
+
+impl ForwardedElement {
+    pub fn to_header_value(&self) -> String {
+        [
+            ("for", &self.for_),
+            ("by", &self.by),
+            ("host", &self.host),
+            ("proto", &self.proto),
+        ]
+        .iter()
+        .filter_map(|(name, value)| {
+            let value = value.as_deref()?;
+            // a bare ipv6 address has to be bracketed to tell it apart from a port
+            if value.parse::<Ipv6Addr>().is_ok() {
+                return Some(pair(name, Some(&format!("[{}]", value))));
+            }
+            Some(pair(name, Some(value)))
+        })
+        .collect::<Vec<_>>()
+        .join(";")
+    }
+
+    // unknown parameters are skipped, a header with only those yields no element for it
+    pub fn parse_header(header: &str) -> Vec<ForwardedElement> {
+        split_unquoted(header, ',')
+            .iter()
+            .filter_map(|element| {
+                let mut parsed = ForwardedElement::default();
+                for part in split_unquoted(element, ';') {
+                    let Some((name, Some(value))) = split_pair(&part) else {
+                        continue;
+                    };
+                    match name.to_ascii_lowercase().as_str() {
+                        "for" => parsed.for_ = Some(value),
+                        "by" => parsed.by = Some(value),
+                        "host" => parsed.host = Some(value),
+                        "proto" => parsed.proto = Some(value),
+                        _ => {}
+                    }
+                }
+                (parsed != ForwardedElement::default()).then_some(parsed)
+            })
+            .collect()
+    }
+}
+
+impl PeakRequestBuilder<'_> {
+    // each call adds one more hop to the same header
+    pub fn forwarded(mut self, element: ForwardedElement) -> Self {
+        let element = element.to_header_value();
+        let value = match self
+            .request
+            .headers
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case("forwarded"))
+        {
+            Some((_, existing)) => format!("{}, {}", existing, element),
+            None => element,
+        };
+        self.request.set_header("Forwarded", value);
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_each_hop() {
+        let elements = ForwardedElement::parse_header(
+            r#"for=192.0.2.60;proto=http;by=203.0.113.43, For="[2001:db8:cafe::17]:4711", for=_hidden;secret=x, foo=bar"#,
+        );
+        assert_eq!(
+            elements,
+            [
+                ForwardedElement {
+                    for_: Some("192.0.2.60".to_string()),
+                    by: Some("203.0.113.43".to_string()),
+                    proto: Some("http".to_string()),
+                    ..Default::default()
+                },
+                ForwardedElement {
+                    for_: Some("[2001:db8:cafe::17]:4711".to_string()),
+                    ..Default::default()
+                },
+                ForwardedElement {
+                    for_: Some("_hidden".to_string()),
+                    ..Default::default()
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn brackets_and_quotes_ipv6() {
+        let element = ForwardedElement {
+            for_: Some("2001:db8::1".to_string()),
+            host: Some("example.com".to_string()),
+            ..Default::default()
+        };
+        let header = element.to_header_value();
+        assert_eq!(header, r#"for="[2001:db8::1]";host=example.com"#);
+        assert_eq!(
+            ForwardedElement::parse_header(&header)[0].for_.as_deref(),
+            Some("[2001:db8::1]")
+        );
+    }
+}
@@ -0,0 +1,81 @@
+/*
+ * MIT License
+ *
+ * Copyright (c) 2025 Squirrel
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in
+ * all copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+use crate::{PeakError, PeakRequests, ResponseSettings};
+use reqwest::header::{HeaderMap, CONTENT_LENGTH, TRANSFER_ENCODING};
+use std::error::Error;
+
+impl PeakRequests {
+    pub fn lenient_framing(mut self, lenient: bool) -> Self {
+        self.lenient_framing = lenient;
+        self
+    }
+}
+
+impl ResponseSettings {
+    // rfc 7230 3.3.3: a response carrying both headers is a smuggling red flag. hyper
+    // quietly goes with transfer-encoding, so we only get to refuse it after the fact.
+    pub(crate) fn check_framing(&self, headers: &HeaderMap) -> Result<(), PeakError> {
+        let lengths: Vec<&str> = headers
+            .get_all(CONTENT_LENGTH)
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .flat_map(|value| value.split(','))
+            .map(str::trim)
+            .collect();
+        let conflicting_lengths = lengths.windows(2).any(|pair| pair[0] != pair[1]);
+        let transfer_encoding = headers.contains_key(TRANSFER_ENCODING);
+
+        if transfer_encoding && !lengths.is_empty() {
+            if !self.lenient_framing {
+                return Err(PeakError::MalformedResponse {
+                    reason: "both Content-Length and Transfer-Encoding present".to_string(),
+                });
+            }
+            log::warn!(
+                "response has both Content-Length and Transfer-Encoding, using Transfer-Encoding"
+            );
+        } else if conflicting_lengths {
+            return Err(PeakError::MalformedResponse {
+                reason: format!("conflicting Content-Length values: {}", lengths.join(", ")),
+            });
+        }
+        Ok(())
+    }
+}
+
+// hyper refuses differing Content-Length values itself, this turns its parse
+// error into the same typed error instead of a generic transport failure
+pub(crate) fn malformed_framing(error: &reqwest::Error) -> Option<PeakError> {
+    let mut source = error.source();
+    while let Some(cause) = source {
+        if cause.to_string().contains("invalid content-length") {
+            return Some(PeakError::MalformedResponse {
+                reason: "conflicting Content-Length values".to_string(),
+            });
+        }
+        source = cause.source();
+    }
+    None
+}
@@ -0,0 +1,265 @@
+/*
+ * MIT License
+ *
+ * Copyright (c) 2025 Squirrel
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in
+ * all copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+use crate::{
+    classify_send_error, spawn_named, PeakError, PeakRequests, PreparedRequest, Response,
+    ResponseSettings, StreamingResponse,
+};
+use bytes::BytesMut;
+use reqwest::blocking::RequestBuilder;
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+// how often a waiting group looks at its cancel token
+const CANCEL_POLL: Duration = Duration::from_millis(10);
+
+#[derive(Debug)]
+pub enum GroupResult {
+    Completed(Response),
+    Failed(PeakError),
+    // cancelled, or still going when the deadline passed
+    Cancelled,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct GroupCancel(Arc<AtomicBool>);
+
+impl GroupCancel {
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::SeqCst);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::SeqCst)
+    }
+}
+
+// a fan-out that has to answer by a deadline with whatever made it back
+#[derive(Debug)]
+pub struct RequestGroup {
+    deadline: Duration,
+    requests: Vec<PreparedRequest>,
+    cancel: GroupCancel,
+}
+
+struct Job {
+    index: usize,
+    request_builder: RequestBuilder,
+    // the request's own timeout or the client's, whichever would have applied
+    timeout: Option<Duration>,
+}
+
+impl RequestGroup {
+    // the deadline counts from execute, not from here
+    pub fn new(deadline: Duration) -> Self {
+        RequestGroup {
+            deadline,
+            requests: Vec::new(),
+            cancel: GroupCancel::default(),
+        }
+    }
+
+    pub fn add(&mut self, request: PreparedRequest) -> &mut Self {
+        self.requests.push(request);
+        self
+    }
+
+    // for cancelling from another thread while execute is waiting
+    pub fn cancel_handle(&self) -> GroupCancel {
+        self.cancel.clone()
+    }
+
+    // results come back in the order the requests were added. returns by the
+    // deadline at the latest, transfers still running then are cut off by their
+    // timeout or at their next chunk and show up as Cancelled. goes through the
+    // client's headers and redirect limits, but not auth retries, robots or the
+    // journal, like send_background.
+    pub fn execute(self, client: &PeakRequests, concurrency: usize) -> Vec<GroupResult> {
+        let deadline = Instant::now() + self.deadline;
+        let total = self.requests.len();
+        let mut results: Vec<Option<GroupResult>> = (0..total).map(|_| None).collect();
+
+        let detached = match client.detached_client() {
+            Ok(detached) => detached,
+            // the same settings fail the same way again, so every slot gets its own
+            // typed error
+            Err(error) => {
+                let mut error = Some(error);
+                return (0..total)
+                    .map(|_| {
+                        let error = error.take().or_else(|| client.detached_client().err());
+                        error.map_or(GroupResult::Cancelled, GroupResult::Failed)
+                    })
+                    .collect();
+            }
+        };
+
+        let mut queue = VecDeque::new();
+        for (index, request) in self.requests.iter().enumerate() {
+            let request = client.outgoing(request);
+            match client.request_builder(&detached, &request) {
+                Ok(request_builder) => queue.push_back(Job {
+                    index,
+                    request_builder,
+                    timeout: request.timeout.or(client.timeout.map(Duration::from_secs)),
+                }),
+                Err(error) => results[index] = Some(GroupResult::Failed(error)),
+            }
+        }
+
+        let (sender, receiver) = mpsc::channel();
+        let queue = Arc::new(Mutex::new(queue));
+        let pending = results.iter().filter(|result| result.is_none()).count();
+        let mut started = 0;
+        let mut spawn_error = None;
+        for _ in 0..concurrency.max(1).min(pending) {
+            let queue = Arc::clone(&queue);
+            let sender = sender.clone();
+            let cancel = self.cancel.clone();
+            let settings = client.response_settings();
+            let running = Arc::clone(&client.groups);
+            running.start();
+            let worker_running = Arc::clone(&running);
+            // not scoped: a transfer stuck past the deadline mustn't hold up the caller.
+            // the client still counts it, so shutdown can wait for it to let go
+            let worker = spawn_named("group", move || {
+                loop {
+                    let Some(job) = queue.lock().unwrap_or_else(|e| e.into_inner()).pop_front()
+                    else {
+                        break;
+                    };
+                    let result = run(
+                        job.request_builder,
+                        job.timeout,
+                        deadline,
+                        &cancel,
+                        &settings,
+                    );
+                    // the group already gave up on this one if nobody is listening
+                    if sender.send((job.index, result)).is_err() {
+                        break;
+                    }
+                }
+                worker_running.done();
+            });
+            match worker {
+                Ok(_) => started += 1,
+                Err(error) => {
+                    running.done();
+                    spawn_error = Some(error);
+                    break;
+                }
+            }
+        }
+        drop(sender);
+
+        // fewer workers just means a slower group. with none nothing runs, the first
+        // waiting slot carries the error and the rest count as cancelled
+        if started == 0 {
+            if let Some(error) = spawn_error {
+                let mut error = Some(error);
+                return results
+                    .into_iter()
+                    .map(|result| {
+                        result.unwrap_or_else(|| {
+                            error
+                                .take()
+                                .map_or(GroupResult::Cancelled, GroupResult::Failed)
+                        })
+                    })
+                    .collect();
+            }
+        }
+
+        let mut waiting = pending;
+        while waiting > 0 && !self.cancel.is_cancelled() {
+            let now = Instant::now();
+            if now >= deadline {
+                break;
+            }
+            match receiver.recv_timeout(CANCEL_POLL.min(deadline - now)) {
+                Ok((index, result)) => {
+                    results[index] = Some(result);
+                    waiting -= 1;
+                }
+                Err(mpsc::RecvTimeoutError::Timeout) => {}
+                Err(mpsc::RecvTimeoutError::Disconnected) => break,
+            }
+        }
+        // anything a worker hasn't picked up yet is dropped instead of sent
+        queue.lock().unwrap_or_else(|e| e.into_inner()).clear();
+        self.cancel.cancel();
+
+        results
+            .into_iter()
+            .map(|result| result.unwrap_or(GroupResult::Cancelled))
+            .collect()
+    }
+}
+
+fn run(
+    request_builder: RequestBuilder,
+    timeout: Option<Duration>,
+    deadline: Instant,
+    cancel: &GroupCancel,
+    settings: &ResponseSettings,
+) -> GroupResult {
+    let remaining = deadline.saturating_duration_since(Instant::now());
+    if cancel.is_cancelled() || remaining.is_zero() {
+        return GroupResult::Cancelled;
+    }
+    let timeout = timeout.map_or(remaining, |timeout| timeout.min(remaining));
+
+    let started = settings.now();
+    let response = match request_builder.timeout(timeout).send() {
+        Ok(response) => response,
+        Err(e) if e.is_timeout() && Instant::now() >= deadline => return GroupResult::Cancelled,
+        Err(e) => return GroupResult::Failed(classify_send_error(e, &settings.redaction)),
+    };
+    // read chunk by chunk so a cancel or the deadline can cut the body short
+    let result = Response::from_reqwest_with(response, settings, started, |response| {
+        let mut stream = StreamingResponse::new(response);
+        let mut body = BytesMut::new();
+        loop {
+            if cancel.is_cancelled() || Instant::now() >= deadline {
+                stream.close();
+                return Err(PeakError::Cancelled);
+            }
+            match stream.next() {
+                Some(Ok(chunk)) => body.extend_from_slice(&chunk),
+                Some(Err(error)) => return Err(error),
+                None => return Ok(body.freeze()),
+            }
+        }
+    });
+    match result {
+        Ok(response) => GroupResult::Completed(response),
+        Err(PeakError::Cancelled) => GroupResult::Cancelled,
+        Err(_) if Instant::now() >= deadline => GroupResult::Cancelled,
+        Err(error) => GroupResult::Failed(error),
+    }
+}
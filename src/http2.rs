@@ -0,0 +1,75 @@
+/*
+ * MIT License
+ *
+ * Copyright (c) 2025 Squirrel
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in
+ * all copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+use crate::PeakRequests;
+use reqwest::blocking::ClientBuilder;
+
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Http2Options {
+    pub(crate) http1_only: bool,
+    pub(crate) stream_window: Option<u32>,
+    pub(crate) connection_window: Option<u32>,
+    pub(crate) adaptive_window: bool,
+}
+
+impl PeakRequests {
+    pub fn http1_only(mut self, http1_only: bool) -> Self {
+        self.http2.http1_only = http1_only;
+        self
+    }
+
+    pub fn http2_initial_stream_window_size(mut self, size: u32) -> Self {
+        self.http2.stream_window = Some(size);
+        self
+    }
+
+    pub fn http2_initial_connection_window_size(mut self, size: u32) -> Self {
+        self.http2.connection_window = Some(size);
+        self
+    }
+
+    // lets hyper grow the windows from measured bandwidth-delay, overrides the sizes above
+    pub fn http2_adaptive_window(mut self, adaptive: bool) -> Self {
+        self.http2.adaptive_window = adaptive;
+        self
+    }
+
+    pub(crate) fn apply_http2_options(&self, mut client_builder: ClientBuilder) -> ClientBuilder {
+        let options = &self.http2;
+        // window settings on an http1_only client are ignored, validate() reports them
+        if options.http1_only {
+            return client_builder.http1_only();
+        }
+        if let Some(size) = options.stream_window {
+            client_builder = client_builder.http2_initial_stream_window_size(size);
+        }
+        if let Some(size) = options.connection_window {
+            client_builder = client_builder.http2_initial_connection_window_size(size);
+        }
+        if options.adaptive_window {
+            client_builder = client_builder.http2_adaptive_window(true);
+        }
+        client_builder
+    }
+}
@@ -0,0 +1,181 @@
+/*
+ * MIT License
+ *
+ * Copyright (c) 2025 Squirrel
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in
+ * all copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+use crate::spill::Body;
+use crate::{PeakError, PeakRequests, Response};
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use sha2::{Digest, Sha256, Sha512};
+use std::collections::HashMap;
+use std::io::{self, Read};
+
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct DigestCheck {
+    require_valid: bool,
+}
+
+// what compute_digest leaves on a response
+#[derive(Debug, Clone)]
+pub(crate) struct BodyDigest {
+    sha256: String,
+    verified: Option<bool>,
+}
+
+// fed a chunk at a time, so a spilled body is hashed without reading it back whole.
+// sha-512 only when the server's digest header asks for it.
+struct Hashes {
+    sha256: Sha256,
+    sha512: Option<Sha512>,
+}
+
+impl Hashes {
+    fn new(headers: &HashMap<String, String>) -> Self {
+        let wants_sha512 = digest_header(headers)
+            .is_some_and(|header| header.to_ascii_lowercase().contains("sha-512"));
+        Hashes {
+            sha256: Sha256::new(),
+            sha512: wants_sha512.then(Sha512::new),
+        }
+    }
+
+    fn update(&mut self, chunk: &[u8]) {
+        self.sha256.update(chunk);
+        if let Some(sha512) = self.sha512.as_mut() {
+            sha512.update(chunk);
+        }
+    }
+
+    fn finish(self, headers: &HashMap<String, String>) -> BodyDigest {
+        let sha256 = self.sha256.finalize().to_vec();
+        let sha512 = self.sha512.map(|sha512| sha512.finalize().to_vec());
+        BodyDigest {
+            sha256: sha256.iter().map(|byte| format!("{:02x}", byte)).collect(),
+            verified: verify(&sha256, sha512.as_deref(), headers),
+        }
+    }
+}
+
+impl DigestCheck {
+    pub(crate) fn of(&self, body: &[u8], headers: &HashMap<String, String>) -> BodyDigest {
+        let mut hashes = Hashes::new(headers);
+        hashes.update(body);
+        hashes.finish(headers)
+    }
+
+    pub(crate) fn of_body(
+        &self,
+        body: &Body,
+        headers: &HashMap<String, String>,
+    ) -> Result<BodyDigest, PeakError> {
+        if let Some(bytes) = body.in_memory() {
+            return Ok(self.of(bytes, headers));
+        }
+        let mut hashes = Hashes::new(headers);
+        let mut reader = body.reader()?;
+        let mut chunk = vec![0; 64 * 1024];
+        loop {
+            match reader.read(&mut chunk) {
+                Ok(0) => return Ok(hashes.finish(headers)),
+                Ok(n) => hashes.update(&chunk[..n]),
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+                Err(e) => return Err(e.into()),
+            }
+        }
+    }
+
+    pub(crate) fn check(&self, response: &Response) -> Result<(), PeakError> {
+        if self.require_valid && response.digest_verified() == Some(false) {
+            return Err(PeakError::DigestMismatch {
+                url: response.display_url(),
+            });
+        }
+        Ok(())
+    }
+}
+
+// Repr-Digest (rfc 9530, sha-256=:base64:) wins over the older Digest (rfc 3230,
+// SHA-256=base64). None when neither names an algorithm we know, otherwise every
+// one we know has to match.
+fn verify(sha256: &[u8], sha512: Option<&[u8]>, headers: &HashMap<String, String>) -> Option<bool> {
+    let header = digest_header(headers)?;
+    let mut verified = None;
+    for entry in header.split(',') {
+        let Some((algorithm, value)) = entry.split_once('=') else {
+            continue;
+        };
+        let expected = match algorithm.trim().to_ascii_lowercase().as_str() {
+            "sha-256" => sha256,
+            "sha-512" => match sha512 {
+                Some(sha512) => sha512,
+                None => continue,
+            },
+            _ => continue,
+        };
+        let value = value.trim();
+        let value = value
+            .strip_prefix(':')
+            .and_then(|value| value.strip_suffix(':'))
+            .unwrap_or(value);
+        let matches = STANDARD.decode(value).is_ok_and(|got| got == expected);
+        verified = Some(verified.unwrap_or(true) && matches);
+    }
+    verified
+}
+
+fn digest_header(headers: &HashMap<String, String>) -> Option<&String> {
+    headers.get("repr-digest").or_else(|| headers.get("digest"))
+}
+
+impl PeakRequests {
+    // the hash is over the raw body, exactly what came off the wire
+    pub fn compute_digest(mut self, compute: bool) -> Self {
+        self.body_digest = compute.then(|| self.body_digest.unwrap_or_default());
+        self
+    }
+
+    // true turns compute_digest on as well. false only relaxes a check that's
+    // already on, it never starts hashing by itself.
+    pub fn require_valid_digest(mut self, require: bool) -> Self {
+        if require {
+            self.body_digest = Some(DigestCheck {
+                require_valid: true,
+            });
+        } else if let Some(check) = self.body_digest.as_mut() {
+            check.require_valid = false;
+        }
+        self
+    }
+}
+
+impl Response {
+    // lowercase hex, None unless compute_digest is on
+    pub fn sha256(&self) -> Option<&str> {
+        self.digest.as_ref().map(|digest| digest.sha256.as_str())
+    }
+
+    // None when the server sent no Repr-Digest or Digest we can check
+    pub fn digest_verified(&self) -> Option<bool> {
+        self.digest.as_ref().and_then(|digest| digest.verified)
+    }
+}
@@ -0,0 +1,295 @@
+/*
+ * MIT License
+ *
+ * Copyright (c) 2025 Squirrel
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in
+ * all copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+use crate::{redirect, unique_token, PeakError, PeakRequests, PreparedRequest, Response};
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fs::{self, File, OpenOptions};
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+const JOURNAL_FILE: &str = "journal.log";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JournalFlush {
+    Always,
+    Interval(Duration),
+}
+
+#[derive(Debug, Clone)]
+pub struct JournalConfig {
+    pub dir: PathBuf,
+    pub max_bytes: u64,
+    pub flush: JournalFlush,
+}
+
+// one json record per line. a crash halfway through a write leaves at most one
+// broken trailing line, which the reader skips.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum Record {
+    Entry(JournalEntry),
+    Complete { id: String },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct JournalEntry {
+    id: String,
+    method: String,
+    url: String,
+    headers: Vec<(String, String)>,
+    form: Option<Vec<(String, String)>>,
+    json: Option<Value>,
+    body: Option<String>,
+}
+
+#[derive(Debug)]
+pub(crate) struct Journal {
+    config: JournalConfig,
+    file: Option<File>,
+    size: u64,
+    last_sync: Option<Instant>,
+}
+
+impl JournalEntry {
+    // credentials never go to disk. a replay sends without them, so auth has to come
+    // from the client's own config, which gets applied again on the way out
+    fn from_request(request: &PreparedRequest) -> Self {
+        JournalEntry {
+            id: unique_token(),
+            method: request.method.clone(),
+            url: request.url.clone(),
+            headers: request
+                .headers
+                .iter()
+                .filter(|(key, _)| !redirect::is_sensitive(key))
+                .cloned()
+                .collect(),
+            form: request.form.clone(),
+            json: request.json.clone(),
+            body: request.body.as_ref().map(|body| STANDARD.encode(body)),
+        }
+    }
+
+    fn to_request(&self) -> PreparedRequest {
+        let mut request = PreparedRequest::new(&self.method, &self.url);
+        request.headers = self.headers.clone();
+        request.form = self.form.clone();
+        request.json = self.json.clone();
+        request.body = self
+            .body
+            .as_ref()
+            .and_then(|body| STANDARD.decode(body).ok());
+        request
+    }
+}
+
+impl Journal {
+    fn path(&self) -> PathBuf {
+        self.config.dir.join(JOURNAL_FILE)
+    }
+
+    fn file(&mut self) -> Result<&mut File, PeakError> {
+        if self.file.is_none() {
+            fs::create_dir_all(&self.config.dir)?;
+            let file = OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(self.path())?;
+            self.size = file.metadata()?.len();
+            // a torn last line from a crash would swallow the next record, so end it first
+            if self.size > 0 && last_byte(&self.path())? != b'\n' {
+                (&file).write_all(b"\n")?;
+                self.size += 1;
+            }
+            self.file = Some(file);
+        }
+        Ok(self.file.as_mut().unwrap())
+    }
+
+    fn append(&mut self, record: &Record, now: Instant) -> Result<(), PeakError> {
+        let mut line = serde_json::to_string(record)?;
+        line.push('\n');
+        let length = line.len() as u64;
+
+        self.file()?;
+        if self.size + length > self.config.max_bytes {
+            self.compact()?;
+            if self.size + length > self.config.max_bytes {
+                return Err(PeakError::JournalFull {
+                    max_bytes: self.config.max_bytes,
+                });
+            }
+        }
+
+        let file = self.file()?;
+        file.write_all(line.as_bytes())?;
+        self.size += length;
+
+        let sync = match self.config.flush {
+            JournalFlush::Always => true,
+            JournalFlush::Interval(interval) => self
+                .last_sync
+                .is_none_or(|last| now.saturating_duration_since(last) >= interval),
+        };
+        if sync {
+            self.file()?.sync_data()?;
+            self.last_sync = Some(now);
+        }
+        Ok(())
+    }
+
+    pub(crate) fn sync(&mut self) -> Result<(), PeakError> {
+        if let Some(file) = &self.file {
+            file.sync_data()?;
+        }
+        Ok(())
+    }
+
+    fn pending(&self) -> Result<Vec<JournalEntry>, PeakError> {
+        let text = match fs::read(self.path()) {
+            Ok(bytes) => String::from_utf8_lossy(&bytes).into_owned(),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e.into()),
+        };
+
+        let mut entries: Vec<JournalEntry> = Vec::new();
+        for (number, line) in text.lines().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            match serde_json::from_str::<Record>(line) {
+                Ok(Record::Entry(entry)) => entries.push(entry),
+                Ok(Record::Complete { id }) => entries.retain(|entry| entry.id != id),
+                Err(e) => log::warn!("skipping unreadable journal line {}: {}", number + 1, e),
+            }
+        }
+        Ok(entries)
+    }
+
+    // drops finished entries by rewriting what's still pending, then swapping it in
+    fn compact(&mut self) -> Result<(), PeakError> {
+        let pending = self.pending()?;
+        let temp = self.config.dir.join(format!("{}.tmp", JOURNAL_FILE));
+        let mut out = File::create(&temp)?;
+        for entry in pending {
+            let mut line = serde_json::to_string(&Record::Entry(entry))?;
+            line.push('\n');
+            out.write_all(line.as_bytes())?;
+        }
+        out.sync_all()?;
+        drop(out);
+        self.file = None;
+        fs::rename(&temp, self.path())?;
+        self.file()?;
+        Ok(())
+    }
+}
+
+fn last_byte(path: &Path) -> Result<u8, PeakError> {
+    let mut file = File::open(path)?;
+    file.seek(SeekFrom::End(-1))?;
+    let mut last = [0u8; 1];
+    file.read_exact(&mut last)?;
+    Ok(last[0])
+}
+
+// a 5xx or 429 means it didn't land, keep it for the next replay
+fn delivered(result: &Result<Response, PeakError>) -> bool {
+    matches!(result, Ok(response) if response.status_code < 500 && response.status_code != 429)
+}
+
+impl PeakRequests {
+    pub fn journal(mut self, config: JournalConfig) -> Self {
+        self.journal = Some(Journal {
+            config,
+            file: None,
+            size: 0,
+            last_sync: None,
+        });
+        self
+    }
+
+    pub(crate) fn execute_durable(
+        &mut self,
+        request: &PreparedRequest,
+    ) -> Result<Response, PeakError> {
+        let mut request = request.clone();
+        // lets the receiver drop the duplicate if a replay re-sends something that did land
+        if !request
+            .headers
+            .iter()
+            .any(|(key, _)| key.eq_ignore_ascii_case("idempotency-key"))
+        {
+            request.set_header("Idempotency-Key", unique_token());
+        }
+
+        let now = self.clock().now();
+        let Some(journal) = self.journal.as_mut() else {
+            return self.execute_prepared(&request);
+        };
+        let entry = JournalEntry::from_request(&request);
+        let id = entry.id.clone();
+        journal.append(&Record::Entry(entry), now)?;
+
+        let result = self.execute_prepared(&request);
+        if delivered(&result) {
+            self.complete(id);
+        }
+        result
+    }
+
+    // re-sends whatever a previous run journaled but never finished, oldest first.
+    // returns how many went through, the rest stay in the journal.
+    pub fn replay_journal(&mut self) -> Result<usize, PeakError> {
+        let Some(journal) = self.journal.as_ref() else {
+            return Ok(0);
+        };
+        let pending = journal.pending()?;
+
+        let mut replayed = 0;
+        for entry in pending {
+            let result = self.execute_prepared(&entry.to_request());
+            if delivered(&result) {
+                self.complete(entry.id);
+                replayed += 1;
+            }
+        }
+        Ok(replayed)
+    }
+
+    // the request already went out, failing to note that shouldn't fail the call.
+    // worst case it gets replayed and the idempotency key covers it.
+    fn complete(&mut self, id: String) {
+        let now = self.clock().now();
+        if let Some(journal) = self.journal.as_mut() {
+            if let Err(e) = journal.append(&Record::Complete { id }, now) {
+                log::warn!("could not mark journal entry complete: {}", e);
+            }
+        }
+    }
+}
@@ -0,0 +1,114 @@
+/*
+ * MIT License
+ *
+ * Copyright (c) 2025 Squirrel
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in
+ * all copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+use crate::{spawn_named, PeakRequests};
+use reqwest::blocking::Client;
+use std::sync::mpsc::{self, RecvTimeoutError, Sender};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+// anything shorter pings faster than an idle timeout could need, and zero would
+// spin the thread without ever waiting
+const MIN_INTERVAL: Duration = Duration::from_secs(1);
+
+#[derive(Debug)]
+pub(crate) struct Keepalive {
+    pub(crate) interval: Duration,
+    url: String,
+    last_used: Arc<Mutex<Option<Instant>>>,
+    stop: Option<Sender<()>>,
+}
+
+impl PeakRequests {
+    // intervals under a second are raised to one
+    pub fn keepalive_ping(mut self, interval: Duration, url: &str) -> Self {
+        if interval < MIN_INTERVAL {
+            log::warn!(
+                "keepalive_ping interval {:?} is under {:?}, using {:?}",
+                interval,
+                MIN_INTERVAL,
+                MIN_INTERVAL
+            );
+        }
+        self.keepalive = Some(Keepalive {
+            interval: interval.max(MIN_INTERVAL),
+            url: url.to_string(),
+            last_used: Arc::new(Mutex::new(None)),
+            stop: None,
+        });
+        self
+    }
+}
+
+impl Keepalive {
+    pub(crate) fn touch(&self) {
+        *self.last_used.lock().unwrap_or_else(|e| e.into_inner()) = Some(Instant::now());
+    }
+
+    // the thread shares the client's connection pool, so a ping keeps the pooled
+    // connection warm (or replaces a dead one) before real traffic needs it.
+    // dropping the sender, which happens when the client drops, stops the thread.
+    pub(crate) fn start(&mut self, client: Client) {
+        if self.stop.is_some() {
+            return;
+        }
+
+        let (stop, stopped) = mpsc::channel::<()>();
+        let interval = self.interval;
+        let url = self.url.clone();
+        let last_used = Arc::clone(&self.last_used);
+        self.touch();
+
+        let pinger = spawn_named("keepalive", move || loop {
+            let idle = last_used
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .map(|at| at.elapsed())
+                .unwrap_or_default();
+            let wait = interval.saturating_sub(idle);
+
+            match stopped.recv_timeout(wait) {
+                Err(RecvTimeoutError::Timeout) => {}
+                _ => return,
+            }
+
+            let idle = last_used
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .map(|at| at.elapsed())
+                .unwrap_or(interval);
+            if idle >= interval {
+                let _ = client.head(&url).send();
+                *last_used.lock().unwrap_or_else(|e| e.into_inner()) = Some(Instant::now());
+            }
+        });
+        // requests still work without it, they just don't find a warm connection
+        if let Err(e) = pinger {
+            log::warn!("could not start the keepalive thread: {}", e);
+            return;
+        }
+
+        self.stop = Some(stop);
+    }
+}
@@ -22,18 +22,159 @@
  * SOFTWARE.
  */
 
-use reqwest::{blocking::Client, header, redirect::Policy};
+use bytes::Bytes;
+use encoding_rs::{Encoding, UTF_8};
+use percent_encoding::{utf8_percent_encode, AsciiSet, NON_ALPHANUMERIC};
+use reqwest::blocking::{Client, ClientBuilder, RequestBuilder};
+use reqwest::{header, redirect::Policy, Proxy};
+#[cfg(feature = "json")]
+use serde_json::from_str;
+#[cfg(feature = "json")]
 use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::borrow::Cow;
 use std::collections::HashMap;
-use std::time::Duration;
-use serde_json::from_str;
+use std::fmt;
+use std::ops::Deref;
+use std::panic::{self, AssertUnwindSafe};
+#[cfg(feature = "json")]
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, OnceLock};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+mod alt_svc;
+#[cfg(feature = "json")]
+mod archive;
+mod auth;
+mod background;
+mod builder;
+mod capabilities;
+mod captive;
+mod capture;
+mod clock;
+mod conditional;
+mod connection;
+mod content_type;
+mod cookies;
+mod diff;
+mod download;
+mod effective;
+mod error;
+mod expect;
+mod forwarded;
+mod framing;
+mod group;
+mod http2;
+mod integrity;
+#[cfg(feature = "json")]
+mod journal;
+#[cfg(feature = "json")]
+mod json_encoding;
+mod keepalive;
+mod long_url;
+mod mirror;
+mod multipart;
+mod negotiate;
+mod normalize;
+#[cfg(feature = "json")]
+mod paginate;
+mod prefer;
+mod probe;
+mod problem;
+mod proxy;
+mod ranges;
+mod redact;
+mod redirect;
+mod relay;
+mod retry;
+mod robots;
+mod scoped;
+mod scrape;
+mod shutdown;
+mod sink;
+mod spec;
+mod spill;
+mod stream;
+mod support;
+mod template;
+#[cfg(feature = "testing")]
+pub mod testing;
+mod tls;
+mod token;
+mod unix_socket;
+pub mod url;
+mod validate;
+mod via;
+
+pub use alt_svc::AltService;
+#[cfg(feature = "json")]
+pub use archive::{ArchiveConfig, ArchiveStats};
+pub use auth::{AuthChallenge, AuthScheme};
+pub use background::{BackgroundDrop, BackgroundHandle};
+pub use builder::PeakRequestBuilder;
+pub use capture::{Capture, CaptureRequirements, CaptureStats};
+pub use conditional::{FetchResult, Validators};
+pub use cookies::StoredCookie;
+pub use diff::{BodyComparison, BodyDiff, Change, DiffOptions, ResponseDiff};
+pub use download::{BulkOptions, DownloadJob, DownloadOutcome, DownloadProgress, SkipExisting};
+pub use effective::{
+    EffectiveConfig, EffectiveHttp2, EffectiveLimits, EffectivePool, EffectiveProxy,
+    EffectiveRedirects, EffectiveTls,
+};
+pub use error::PeakError;
+pub use forwarded::ForwardedElement;
+pub use group::{GroupCancel, GroupResult, RequestGroup};
+#[cfg(feature = "json")]
+pub use journal::{JournalConfig, JournalFlush};
+#[cfg(feature = "json")]
+pub use json_encoding::JsonEncodeOptions;
+pub use mirror::{MirrorConfig, MirrorOutcome, MirrorStats};
+pub use multipart::{Multipart, RelatedPart};
+pub use normalize::UrlNormalization;
+#[cfg(feature = "json")]
+pub use paginate::{CursorSpec, Offset, Paginator};
+pub use prefer::Preference;
+pub use probe::ProbeResult;
+#[cfg(feature = "json")]
+pub use problem::ProblemDetails;
+pub use ranges::RangePart;
+pub use redact::RedactionProfile;
+pub use redirect::{RedirectEvent, RedirectInfo};
+pub use retry::{AttemptOutcome, AttemptRecord, RetryHeaders};
+pub use scoped::{Overrides, ScopedClient};
+pub use shutdown::ShutdownReport;
+pub use sink::{BodySink, CountingSink, FileSink, HashSink, WriteSink};
+pub use spec::{RequestSpec, SpecBody, SpecOptions, SpecProxy};
+pub use stream::StreamingResponse;
+pub use template::{PlaceholderEncoding, RequestTemplate};
+pub use token::{TokenProvider, TokenRequestReason};
+pub use validate::ConfigWarning;
+pub use via::ViaEntry;
 
-#[derive(Debug)]
+const DEFAULT_MAX_HEADER_SIZE: usize = 1024 * 1024;
+const DEFAULT_MAX_HEADER_COUNT: usize = 1024;
+
+#[derive(Debug, Clone)]
 pub struct Response {
     pub status_code: u16,
-    pub text: String,
     pub headers: HashMap<String, String>,
     pub url: String,
+    pub negotiated_accept: Option<String>,
+    // every Set-Cookie the server sent on this response, jar or not
+    pub cookies: Vec<StoredCookie>,
+    // both only matter to json()
+    #[cfg_attr(not(feature = "json"), allow(dead_code))]
+    strict_content_type: bool,
+    #[cfg_attr(not(feature = "json"), allow(dead_code))]
+    captive_portal_check: bool,
+    redaction: Arc<redact::RedactionProfile>,
+    attempts: Box<[retry::AttemptRecord]>,
+    digest: Option<Box<integrity::BodyDigest>>,
+    #[cfg_attr(not(feature = "scrape"), allow(dead_code))]
+    patterns: Arc<scrape::PatternCache>,
+    body: spill::Body,
 }
 
 #[derive(Debug, Default)]
@@ -43,9 +184,60 @@ pub struct PeakRequests {
     timeout: Option<u64>,
     allow_redirects: bool,
     max_redirects: usize,
+    auth: Option<AuthScheme>,
+    digest: Option<auth::DigestState>,
+    keepalive: Option<keepalive::Keepalive>,
+    robots: Option<robots::RobotsPolicy>,
+    strict_content_type: bool,
+    cookie_jar: Option<Arc<cookies::CookieJar>>,
+    persist_session_cookies: bool,
+    max_response_header_size: usize,
+    max_header_count: usize,
+    proxy_loop_token: Option<String>,
+    proxy: Option<String>,
+    proxy_clients: HashMap<Option<String>, Client>,
+    on_redirect: Option<redirect::RedirectHook>,
+    detect_captive_portal: bool,
+    lenient_framing: bool,
+    token_provider: Option<token::TokenProvider>,
+    #[cfg(feature = "json")]
+    json_encoding: json_encoding::JsonEncodeOptions,
+    require_san: bool,
+    check_revocation: bool,
+    mirror: Option<mirror::Mirror>,
+    on_mirror: Option<mirror::MirrorHook>,
+    clock: Option<clock::SharedClock>,
+    seed: Option<u64>,
+    http2: http2::Http2Options,
+    #[cfg(feature = "json")]
+    journal: Option<journal::Journal>,
+    url_normalization: normalize::UrlNormalization,
+    normalize_outgoing: bool,
+    background_workers: usize,
+    background_drop: background::BackgroundDrop,
+    background_client: OnceLock<Client>,
+    background: OnceLock<background::BackgroundPool>,
+    // group workers, which can outlive the execute() that started them
+    groups: Arc<background::InFlight>,
+    disable_keep_alive: bool,
+    retry_headers: Option<retry::RetryHeaders>,
+    max_url_length: Option<usize>,
+    long_get: Option<long_url::LongGet>,
+    body_digest: Option<integrity::DigestCheck>,
+    unix_socket: Option<unix_socket::UnixSocket>,
+    unix_socket_max_body: u64,
+    #[cfg(feature = "json")]
+    cookie_file: Option<PathBuf>,
+    #[cfg(feature = "json")]
+    archive: Option<archive::Archive>,
+    redaction: Arc<redact::RedactionProfile>,
+    alt_services: HashMap<String, Vec<alt_svc::Learned>>,
+    on_alt_svc: Option<alt_svc::AltSvcHook>,
+    patterns: Arc<scrape::PatternCache>,
+    spill: Option<spill::SpillPolicy>,
+    captures: capture::Captures,
 }
 
-
 impl PeakRequests {
     pub fn new() -> Self {
         PeakRequests {
@@ -54,6 +246,57 @@ impl PeakRequests {
             timeout: None,
             allow_redirects: true,
             max_redirects: 10,
+            auth: None,
+            digest: None,
+            keepalive: None,
+            robots: None,
+            strict_content_type: false,
+            cookie_jar: None,
+            persist_session_cookies: false,
+            max_response_header_size: DEFAULT_MAX_HEADER_SIZE,
+            max_header_count: DEFAULT_MAX_HEADER_COUNT,
+            proxy_loop_token: None,
+            proxy: None,
+            proxy_clients: HashMap::new(),
+            on_redirect: None,
+            detect_captive_portal: false,
+            lenient_framing: false,
+            token_provider: None,
+            #[cfg(feature = "json")]
+            json_encoding: json_encoding::JsonEncodeOptions::default(),
+            require_san: false,
+            check_revocation: false,
+            mirror: None,
+            on_mirror: None,
+            clock: None,
+            seed: None,
+            http2: http2::Http2Options::default(),
+            #[cfg(feature = "json")]
+            journal: None,
+            url_normalization: normalize::UrlNormalization::default(),
+            normalize_outgoing: false,
+            background_workers: 2,
+            background_drop: background::BackgroundDrop::default(),
+            background_client: OnceLock::new(),
+            background: OnceLock::new(),
+            groups: Arc::default(),
+            disable_keep_alive: false,
+            retry_headers: None,
+            max_url_length: None,
+            long_get: None,
+            body_digest: None,
+            unix_socket: None,
+            unix_socket_max_body: unix_socket::DEFAULT_MAX_BODY,
+            #[cfg(feature = "json")]
+            cookie_file: None,
+            #[cfg(feature = "json")]
+            archive: None,
+            redaction: Arc::default(),
+            alt_services: HashMap::new(),
+            on_alt_svc: None,
+            patterns: Arc::default(),
+            spill: None,
+            captures: capture::Captures::default(),
         }
     }
 
@@ -77,134 +320,873 @@ impl PeakRequests {
         self
     }
 
-    fn init_client(&mut self) -> Result<(), String> {
-        let mut client_builder = Client::builder();
+    pub fn max_response_header_size(mut self, bytes: usize) -> Self {
+        self.max_response_header_size = bytes;
+        self
+    }
 
-        if !self.headers.is_empty() {
-            let mut headers = header::HeaderMap::new();
-            for (key, value) in &self.headers {
-                headers.insert(
-                    header::HeaderName::from_bytes(key.as_bytes()).map_err(|e| e.to_string())?,
-                    header::HeaderValue::from_str(value).map_err(|e| e.to_string())?,
-                );
-            }
-            client_builder = client_builder.default_headers(headers);
+    pub fn max_header_count(mut self, count: usize) -> Self {
+        self.max_header_count = count;
+        self
+    }
+
+    fn init_client(&mut self) -> Result<(), PeakError> {
+        for warning in self.validate() {
+            log::warn!("{}", warning);
+        }
+        let client = self.client_builder()?.build()?;
+        if let Some(keepalive) = &mut self.keepalive {
+            keepalive.start(client.clone());
         }
+        self.client = Some(client);
+        Ok(())
+    }
+
+    fn client_builder(&self) -> Result<ClientBuilder, PeakError> {
+        self.client_builder_via(None)
+    }
+
+    // proxy: None uses the client's own, Some(None) goes direct and Some(Some(url))
+    // swaps in that one. reqwest keeps every proxy it's given, so only one is added.
+    pub(crate) fn client_builder_via(
+        &self,
+        proxy: Option<Option<&str>>,
+    ) -> Result<ClientBuilder, PeakError> {
+        self.check_capabilities()?;
+        let mut client_builder = Client::builder();
+
+        client_builder = match proxy.unwrap_or(self.proxy.as_deref()) {
+            Some(url) => client_builder.proxy(Proxy::all(url)?),
+            None if proxy.is_some() => client_builder.no_proxy(),
+            None => client_builder,
+        };
 
         if let Some(timeout_secs) = self.timeout {
             client_builder = client_builder.timeout(Duration::from_secs(timeout_secs));
         }
 
-        client_builder = if self.allow_redirects {
-            client_builder.redirect(Policy::limited(self.max_redirects))
+        // redirects are followed by the crate itself (see redirect.rs) so every hop is visible
+        client_builder = client_builder.redirect(Policy::none());
+
+        if let Some(jar) = &self.cookie_jar {
+            client_builder = client_builder.cookie_provider(Arc::clone(jar));
+        }
+
+        if self.disable_keep_alive {
+            client_builder = client_builder.pool_max_idle_per_host(0);
+        }
+
+        client_builder = self.apply_http2_options(client_builder);
+        self.apply_tls_options(client_builder)
+    }
+
+    // for work that runs off the caller's thread (download_all, mirroring). the manual
+    // redirect loop needs &mut self, so redirects are left to reqwest with the same limit.
+    pub(crate) fn detached_client(&self) -> Result<Client, PeakError> {
+        Ok(self
+            .client_builder()?
+            .default_headers(self.default_headers()?)
+            .redirect(self.detached_redirects())
+            .build()?)
+    }
+
+    pub(crate) fn detached_redirects(&self) -> Policy {
+        if self.allow_redirects {
+            Policy::limited(self.max_redirects)
         } else {
-            client_builder.redirect(Policy::none())
-        };
+            Policy::none()
+        }
+    }
 
-        self.client = Some(client_builder.build().map_err(|e| e.to_string())?);
-        Ok(())
+    // only for clients that let reqwest follow redirects, it drops the sensitive ones
+    // itself on a cross-origin hop. the main client gets them per request instead.
+    pub(crate) fn default_headers(&self) -> Result<header::HeaderMap, PeakError> {
+        let mut headers = header::HeaderMap::new();
+        for (key, value) in &self.headers {
+            headers.insert(header_name(key)?, header_value(value)?);
+        }
+        Ok(headers)
     }
 
-    pub fn get(&mut self, url: &str) -> Result<Response, String> {
+    pub fn get(&mut self, url: &str) -> Result<Response, PeakError> {
         self._request("GET", url, None, None)
     }
 
+    pub fn get_with_params(
+        &mut self,
+        url: &str,
+        params: HashMap<&str, &str>,
+    ) -> Result<Response, PeakError> {
+        self._request("GET", url, Some(params), None)
+    }
+
+    #[cfg(feature = "json")]
     pub fn post(
         &mut self,
         url: &str,
         data: Option<HashMap<&str, &str>>,
         json: Option<Value>,
-    ) -> Result<Response, String> {
-        self._request("POST", url, data, json)
+    ) -> Result<Response, PeakError> {
+        self._request_json("POST", url, data, json)
     }
 
+    #[cfg(not(feature = "json"))]
+    pub fn post(
+        &mut self,
+        url: &str,
+        data: Option<HashMap<&str, &str>>,
+    ) -> Result<Response, PeakError> {
+        self._request("POST", url, None, data)
+    }
+
+    #[cfg(feature = "json")]
     pub fn put(
         &mut self,
         url: &str,
         data: Option<HashMap<&str, &str>>,
         json: Option<Value>,
-    ) -> Result<Response, String> {
-        self._request("PUT", url, data, json)
+    ) -> Result<Response, PeakError> {
+        self._request_json("PUT", url, data, json)
+    }
+
+    #[cfg(not(feature = "json"))]
+    pub fn put(
+        &mut self,
+        url: &str,
+        data: Option<HashMap<&str, &str>>,
+    ) -> Result<Response, PeakError> {
+        self._request("PUT", url, None, data)
     }
 
-    pub fn delete(&mut self, url: &str) -> Result<Response, String> {
+    pub fn delete(&mut self, url: &str) -> Result<Response, PeakError> {
         self._request("DELETE", url, None, None)
     }
 
-    fn _request(
+    pub fn head(&mut self, url: &str) -> Result<Response, PeakError> {
+        self._request("HEAD", url, None, None)
+    }
+
+    pub fn get_stream(&mut self, url: &str) -> Result<StreamingResponse, PeakError> {
+        let response = self._send(&PreparedRequest::new("GET", url))?;
+        Ok(StreamingResponse::new(response))
+    }
+
+    fn _send(
         &mut self,
-        method: &str,
-        url: &str,
-        data: Option<HashMap<&str, &str>>,
-        json: Option<Value>,
-    ) -> Result<Response, String> {
-        if self.client.is_none() {
-            self.init_client()?;
+        request: &PreparedRequest,
+    ) -> Result<reqwest::blocking::Response, PeakError> {
+        let allow_redirects = request.allow_redirects.unwrap_or(self.allow_redirects);
+        let max_redirects = request.max_redirects.unwrap_or(self.max_redirects);
+        let mut current = request.clone();
+        let mut hop = 0;
+        // the jar already sees every hop, this is only for clients without one
+        let chain_cookies =
+            (allow_redirects && self.cookie_jar.is_none()).then(cookies::CookieJar::default);
+        loop {
+            let response = match &chain_cookies {
+                Some(chain) => self.send_once(&redirect::with_chain_cookies(&current, chain))?,
+                None => self.send_once(&current)?,
+            };
+            if !allow_redirects {
+                return Ok(response);
+            }
+            if let Some(chain) = &chain_cookies {
+                for cookie in cookies::response_cookies(&response) {
+                    chain.store(cookie);
+                }
+            }
+            let status = response.status().as_u16();
+            let Some(next) = response
+                .headers()
+                .get(header::LOCATION)
+                .and_then(|location| location.to_str().ok())
+                .and_then(|location| redirect::next_hop(&current, status, location))
+            else {
+                return Ok(response);
+            };
+
+            hop += 1;
+            if hop > max_redirects {
+                return Err(PeakError::TooManyRedirects { max: max_redirects });
+            }
+            if let Some(on_redirect) = &self.on_redirect {
+                contain("on_redirect", || {
+                    on_redirect(&RedirectEvent {
+                        previous_url: &current.url,
+                        next_url: &next.url,
+                        status,
+                        hop,
+                        headers: &header_map(response.headers()),
+                    })
+                })?;
+            }
+            current = next;
         }
+    }
 
-        let client = self.client.as_ref().unwrap();
-        let mut request_builder = match method {
+    fn send_once(
+        &mut self,
+        request: &PreparedRequest,
+    ) -> Result<reqwest::blocking::Response, PeakError> {
+        let client = match &request.proxy {
+            _ if request.connection_close => self.one_off_client(request.proxy.as_ref())?,
+            Some(proxy) => self.proxy_client(proxy.as_deref())?,
+            None => {
+                if self.client.is_none() {
+                    self.init_client()?;
+                }
+                self.client.clone().unwrap()
+            }
+        };
+        if let Some(keepalive) = &self.keepalive {
+            keepalive.touch();
+        }
+
+        #[cfg(unix)]
+        let response = match self.unix_socket_route(request) {
+            Some(socket) => self.send_unix(socket, &client, request)?,
+            None => self
+                .request_builder(&client, request)?
+                .send()
+                .map_err(|e| classify_send_error(e, &self.redaction))?,
+        };
+        #[cfg(not(unix))]
+        let response = self
+            .request_builder(&client, request)?
+            .send()
+            .map_err(|e| classify_send_error(e, &self.redaction))?;
+        self.response_settings().check_head(response.headers())?;
+        Ok(response)
+    }
+
+    pub(crate) fn request_builder(
+        &self,
+        client: &Client,
+        request: &PreparedRequest,
+    ) -> Result<RequestBuilder, PeakError> {
+        let url = request.url.as_str();
+        let mut request_builder = match request.method.as_str() {
             "GET" => client.get(url),
             "POST" => client.post(url),
             "PUT" => client.put(url),
             "DELETE" => client.delete(url),
-            _ => return Err(format!("unsupported HTTP method: {}", method)),
+            "HEAD" => client.head(url),
+            method => return Err(PeakError::UnsupportedMethod(method.to_string())),
         };
 
-        if let Some(form_data) = data {
-            request_builder = request_builder.form(&form_data);
+        for (key, value) in &request.headers {
+            request_builder = request_builder.header(key, value);
         }
 
-        if let Some(json_data) = json {
-            request_builder = request_builder.json(&json_data);
+        // client-wide headers go on each request instead of as reqwest defaults, so a
+        // redirect that leaves the origin can leave the credentials among them behind
+        for (key, value) in &self.headers {
+            let overridden = request
+                .headers
+                .iter()
+                .any(|(name, _)| name.eq_ignore_ascii_case(key));
+            if overridden || (request.left_origin && redirect::is_sensitive(key)) {
+                continue;
+            }
+            request_builder = request_builder.header(header_name(key)?, header_value(value)?);
         }
 
-        let response = request_builder.send().map_err(|e| e.to_string())?;
-        let status_code = response.status().as_u16();
-        let response_url = response.url().to_string();
+        if let Some(token) = &self.proxy_loop_token {
+            request_builder = request_builder.header(header::VIA, format!("1.1 {}", token));
+        }
+
+        if request.connection_close || self.disable_keep_alive {
+            request_builder = request_builder.header(header::CONNECTION, "close");
+        }
 
-        let mut headers = HashMap::new();
-        for (key, value) in response.headers() {
-            headers.insert(key.to_string(), value.to_str().unwrap_or("").to_string());
+        if let Some(timeout) = request.timeout {
+            request_builder = request_builder.timeout(timeout);
+        }
+
+        if let Some(body) = &request.body {
+            request_builder = request_builder.body(body.clone());
+        }
+
+        if let Some(form_data) = &request.form {
+            request_builder = request_builder.form(form_data);
+        }
+
+        // encoded here rather than with RequestBuilder::json so json_encoding applies
+        #[cfg(feature = "json")]
+        if let Some(json_data) = &request.json {
+            let body = json_encoding::encode_json(json_data, self.json_encoding)?;
+            let has_content_type = request
+                .headers
+                .iter()
+                .any(|(key, _)| key.eq_ignore_ascii_case("content-type"));
+            if !has_content_type {
+                request_builder = request_builder.header(header::CONTENT_TYPE, "application/json");
+            }
+            request_builder = request_builder.body(body);
         }
 
-        let text = response.text().map_err(|e| e.to_string())?;
+        Ok(request_builder)
+    }
+
+    pub(crate) fn response_settings(&self) -> ResponseSettings {
+        ResponseSettings {
+            max_header_size: self.max_response_header_size,
+            max_header_count: self.max_header_count,
+            lenient_framing: self.lenient_framing,
+            strict_content_type: self.strict_content_type,
+            body_digest: self.body_digest,
+            redaction: Arc::clone(&self.redaction),
+            patterns: Arc::clone(&self.patterns),
+            clock: self.clock.clone(),
+            spill: self.spill.clone(),
+        }
+    }
+
+    fn _request(
+        &mut self,
+        method: &str,
+        url: &str,
+        params: Option<HashMap<&str, &str>>,
+        data: Option<HashMap<&str, &str>>,
+    ) -> Result<Response, PeakError> {
+        let request = PreparedRequest::simple(method, url, params, data);
+        self.execute_prepared(&request)
+    }
+
+    #[cfg(feature = "json")]
+    fn _request_json(
+        &mut self,
+        method: &str,
+        url: &str,
+        data: Option<HashMap<&str, &str>>,
+        json: Option<Value>,
+    ) -> Result<Response, PeakError> {
+        let mut request = PreparedRequest::simple(method, url, None, data);
+        request.json = json;
+        self.execute_prepared(&request)
+    }
+
+    // the journal and negotiation both end up in execute_prepared for each send
+    pub(crate) fn dispatch(&mut self, request: &PreparedRequest) -> Result<Response, PeakError> {
+        #[cfg(feature = "json")]
+        if request.durable {
+            return self.execute_durable(request);
+        }
+        if !request.accept_fallback.is_empty() {
+            return self.execute_negotiated(request, &request.accept_fallback);
+        }
+        self.execute_prepared(request)
+    }
+
+    fn execute_prepared(&mut self, request: &PreparedRequest) -> Result<Response, PeakError> {
+        let request = self.shorten_long_get(self.outgoing(request));
+        let request = request.as_ref();
+        self.check_url_length(request)?;
+        self.check_robots(request)?;
+        #[cfg(feature = "json")]
+        let sent_at = SystemTime::now();
+        let started = self.clock().now();
+        let mut response = self.execute_with_auth(request)?;
+        let elapsed = self.clock().now().saturating_duration_since(started);
+        response.attempts = Box::new([retry::AttemptRecord {
+            attempt: 1,
+            outcome: retry::AttemptOutcome::Status(response.status_code),
+            elapsed,
+            backoff: Duration::ZERO,
+        }]);
+        self.check_proxy_loop(&response)?;
+        self.mirror_request(request, &response);
+        #[cfg(feature = "json")]
+        self.archive_exchange(request, &response, sent_at);
+        self.capture_exchange(request, &response, elapsed)?;
+        Ok(response)
+    }
+
+    fn fetch(&mut self, request: &PreparedRequest) -> Result<Response, PeakError> {
+        let settings = self.response_settings();
+        let started = settings.now();
+        let response = self._send(request)?;
+        let response = Response::from_reqwest(response, &settings, started)?;
+        #[cfg(feature = "json")]
+        let response = Response {
+            captive_portal_check: self.detect_captive_portal && self.expects_json(request),
+            ..response
+        };
+        self.learn_alt_svc(&response)?;
+        Ok(response)
+    }
+}
 
-        Ok(Response {
+// what turning a reqwest response into a Response takes from the client. cheap
+// to clone, so the background paths carry one onto their threads
+#[derive(Debug, Clone)]
+pub(crate) struct ResponseSettings {
+    max_header_size: usize,
+    max_header_count: usize,
+    lenient_framing: bool,
+    strict_content_type: bool,
+    body_digest: Option<integrity::DigestCheck>,
+    redaction: Arc<redact::RedactionProfile>,
+    patterns: Arc<scrape::PatternCache>,
+    clock: Option<clock::SharedClock>,
+    spill: Option<spill::SpillPolicy>,
+}
+
+impl ResponseSettings {
+    pub(crate) fn now(&self) -> Instant {
+        match &self.clock {
+            Some(clock) => clock.now(),
+            None => Instant::now(),
+        }
+    }
+
+    // header limits and framing, for anything that reads a response head
+    pub(crate) fn check_head(&self, headers: &header::HeaderMap) -> Result<(), PeakError> {
+        self.check_header_limits(headers)?;
+        self.check_framing(headers)
+    }
+
+    // hyper already refuses a response head past ~400kb or 100 headers, so this
+    // is for callers who want a tighter bound than that
+    fn check_header_limits(&self, headers: &header::HeaderMap) -> Result<(), PeakError> {
+        let count = headers.len();
+        let size: usize = headers
+            .iter()
+            .map(|(key, value)| key.as_str().len() + value.len() + 4)
+            .sum();
+        if count > self.max_header_count || size > self.max_header_size {
+            return Err(PeakError::HeadersTooLarge { size, count });
+        }
+        Ok(())
+    }
+}
+
+impl Response {
+    // the one place a Response gets built: checks the head, reads the whole body,
+    // digests it when asked and records the single attempt
+    pub(crate) fn from_reqwest(
+        response: reqwest::blocking::Response,
+        settings: &ResponseSettings,
+        started: Instant,
+    ) -> Result<Response, PeakError> {
+        Self::read_from_reqwest(response, settings, started, |response| {
+            match &settings.spill {
+                Some(policy) => {
+                    let length = response.content_length();
+                    policy.read(response, length)
+                }
+                None => response
+                    .bytes()
+                    .map(spill::Body::Memory)
+                    .map_err(|e| PeakError::Http(settings.redaction.reqwest_error(e))),
+            }
+        })
+    }
+
+    // same, with the caller reading the body, for paths that need to give up halfway
+    pub(crate) fn from_reqwest_with(
+        response: reqwest::blocking::Response,
+        settings: &ResponseSettings,
+        started: Instant,
+        read_body: impl FnOnce(reqwest::blocking::Response) -> Result<Bytes, PeakError>,
+    ) -> Result<Response, PeakError> {
+        Self::read_from_reqwest(response, settings, started, |response| {
+            read_body(response).map(spill::Body::Memory)
+        })
+    }
+
+    fn read_from_reqwest(
+        response: reqwest::blocking::Response,
+        settings: &ResponseSettings,
+        started: Instant,
+        read_body: impl FnOnce(reqwest::blocking::Response) -> Result<spill::Body, PeakError>,
+    ) -> Result<Response, PeakError> {
+        settings.check_head(response.headers())?;
+        let status_code = response.status().as_u16();
+        let url = response.url().to_string();
+        let headers = header_map(response.headers());
+        let cookies = cookies::response_cookies(&response);
+        let body = read_body(response)?;
+        let digest = settings
+            .body_digest
+            .map(|check| check.of_body(&body, &headers).map(Box::new))
+            .transpose()?;
+
+        let response = Response {
             status_code,
-            text,
             headers,
-            url: response_url,
-        })
+            url,
+            negotiated_accept: None,
+            cookies,
+            strict_content_type: settings.strict_content_type,
+            captive_portal_check: false,
+            redaction: Arc::clone(&settings.redaction),
+            attempts: Box::new([retry::AttemptRecord {
+                attempt: 1,
+                outcome: retry::AttemptOutcome::Status(status_code),
+                elapsed: settings.now().saturating_duration_since(started),
+                backoff: Duration::ZERO,
+            }]),
+            digest,
+            patterns: Arc::clone(&settings.patterns),
+            body,
+        };
+        if let Some(check) = settings.body_digest {
+            check.check(&response)?;
+        }
+        Ok(response)
+    }
+}
+
+// sends on a detached client and reads the whole body, for the background paths
+pub(crate) fn send_detached(
+    request_builder: RequestBuilder,
+    settings: &ResponseSettings,
+) -> Result<Response, PeakError> {
+    let started = settings.now();
+    let response = request_builder
+        .send()
+        .map_err(|e| classify_send_error(e, &settings.redaction))?;
+    Response::from_reqwest(response, settings, started)
+}
+
+fn classify_send_error(error: reqwest::Error, redaction: &redact::RedactionProfile) -> PeakError {
+    framing::malformed_framing(&error)
+        .or_else(|| tls::certificate_failure(&error))
+        .unwrap_or_else(|| PeakError::Http(redaction.reqwest_error(error)))
+}
+
+// user-supplied closures don't implement Debug, this lets the structs holding them still derive it
+pub(crate) struct Callback<F: ?Sized>(Arc<F>);
+
+impl<F: ?Sized> Clone for Callback<F> {
+    fn clone(&self) -> Self {
+        Callback(Arc::clone(&self.0))
+    }
+}
+
+impl<F: ?Sized> Deref for Callback<F> {
+    type Target = F;
+
+    fn deref(&self) -> &F {
+        &self.0
+    }
+}
+
+impl<F: ?Sized> fmt::Debug for Callback<F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Callback")
+    }
+}
+
+// a panicking hook would otherwise unwind through the request and take the
+// caller's thread (or their whole pool) with it
+pub(crate) fn contain<T>(hook: &'static str, f: impl FnOnce() -> T) -> Result<T, PeakError> {
+    panic::catch_unwind(AssertUnwindSafe(f)).map_err(|payload| {
+        let message = payload
+            .downcast_ref::<&str>()
+            .map(|message| message.to_string())
+            .or_else(|| payload.downcast_ref::<String>().cloned())
+            .unwrap_or_else(|| "non-string panic payload".to_string());
+        PeakError::HookPanicked { hook, message }
+    })
+}
+
+// every thread the crate starts is peakreq-something, so they're easy to pick
+// out of a debugger or a thread dump
+pub(crate) fn spawn_named<T: Send + 'static>(
+    name: &str,
+    f: impl FnOnce() -> T + Send + 'static,
+) -> Result<thread::JoinHandle<T>, PeakError> {
+    Ok(thread::Builder::new()
+        .name(format!("peakreq-{}", name))
+        .spawn(f)?)
+}
+
+pub(crate) fn spawn_scoped_named<'scope, T: Send + 'scope>(
+    scope: &'scope thread::Scope<'scope, '_>,
+    name: &str,
+    f: impl FnOnce() -> T + Send + 'scope,
+) -> Result<thread::ScopedJoinHandle<'scope, T>, PeakError> {
+    Ok(thread::Builder::new()
+        .name(format!("peakreq-{}", name))
+        .spawn_scoped(scope, f)?)
+}
+
+#[derive(Debug, Clone)]
+pub struct PreparedRequest {
+    method: String,
+    url: String,
+    headers: Vec<(String, String)>,
+    form: Option<Vec<(String, String)>>,
+    #[cfg(feature = "json")]
+    json: Option<Value>,
+    body: Option<Vec<u8>>,
+    proxy: Option<Option<String>>,
+    timeout: Option<Duration>,
+    allow_redirects: Option<bool>,
+    max_redirects: Option<usize>,
+    connection_close: bool,
+    // set once a redirect went to another origin, from then on the client's
+    // Authorization/Cookie headers stay behind
+    left_origin: bool,
+    redaction: Arc<redact::RedactionProfile>,
+    // what the builder asked for on top of the request itself, so a prepared
+    // request sends the same way the builder would have
+    accept_fallback: Vec<String>,
+    #[cfg(feature = "json")]
+    durable: bool,
+}
+
+impl PreparedRequest {
+    fn new(method: &str, url: &str) -> Self {
+        PreparedRequest {
+            method: method.to_string(),
+            url: url.to_string(),
+            headers: Vec::new(),
+            form: None,
+            #[cfg(feature = "json")]
+            json: None,
+            body: None,
+            proxy: None,
+            timeout: None,
+            allow_redirects: None,
+            max_redirects: None,
+            connection_close: false,
+            left_origin: false,
+            redaction: Arc::default(),
+            accept_fallback: Vec::new(),
+            #[cfg(feature = "json")]
+            durable: false,
+        }
+    }
+
+    // what the get/post/put/delete shortcuts send
+    fn simple(
+        method: &str,
+        url: &str,
+        params: Option<HashMap<&str, &str>>,
+        data: Option<HashMap<&str, &str>>,
+    ) -> Self {
+        let mut request = PreparedRequest::new(method, url);
+        if let Some(params) = params {
+            // sorted so the same map always gives the same url
+            let mut params: Vec<(&str, &str)> = params.into_iter().collect();
+            params.sort();
+            for (key, value) in params {
+                request.append_query(key, value);
+            }
+        }
+        request.form = data.map(|form_data| {
+            form_data
+                .into_iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect()
+        });
+        request
+    }
+
+    pub(crate) fn has_body(&self) -> bool {
+        #[cfg(feature = "json")]
+        if self.json.is_some() {
+            return true;
+        }
+        self.body.is_some() || self.form.is_some()
     }
+
+    pub(crate) fn clear_body(&mut self) {
+        self.form = None;
+        #[cfg(feature = "json")]
+        {
+            self.json = None;
+        }
+        self.body = None;
+    }
+
+    pub(crate) fn with_redaction(mut self, redaction: &Arc<redact::RedactionProfile>) -> Self {
+        self.redaction = Arc::clone(redaction);
+        self
+    }
+
+    pub fn method(&self) -> &str {
+        &self.method
+    }
+
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    // method, url and headers through the client's redaction profile, safe to log
+    pub fn display(&self) -> String {
+        let mut out = format!("{} {}", self.method, self.redaction.url(&self.url));
+        for (key, value) in &self.headers {
+            out.push_str(&format!("\n{}: {}", key, self.redaction.header(key, value)));
+        }
+        out
+    }
+
+    pub fn headers(&self) -> &[(String, String)] {
+        &self.headers
+    }
+
+    pub fn send(&self, client: &mut PeakRequests) -> Result<Response, PeakError> {
+        client.dispatch(self)
+    }
+
+    // replaces the value where the header already sits instead of appending, so
+    // retries and negotiation attempts keep the same header order as the first try
+    pub(crate) fn set_header(&mut self, key: &str, value: String) {
+        let mut existing = self
+            .headers
+            .iter()
+            .enumerate()
+            .filter(|(_, (name, _))| name.eq_ignore_ascii_case(key))
+            .map(|(at, _)| at);
+        match existing.next() {
+            Some(first) => {
+                let duplicates: Vec<usize> = existing.collect();
+                self.headers[first].1 = value;
+                for at in duplicates.into_iter().rev() {
+                    self.headers.remove(at);
+                }
+            }
+            None => self.headers.push((key.to_string(), value)),
+        }
+    }
+
+    fn append_query(&mut self, key: &str, value: &str) {
+        let fragment = self.url.find('#').map(|at| self.url.split_off(at));
+        self.url
+            .push(if self.url.contains('?') { '&' } else { '?' });
+        self.url.push_str(&encode_component(key));
+        self.url.push('=');
+        self.url.push_str(&encode_component(value));
+        if let Some(fragment) = fragment {
+            self.url.push_str(&fragment);
+        }
+    }
+}
+
+const COMPONENT: &AsciiSet = &NON_ALPHANUMERIC
+    .remove(b'-')
+    .remove(b'.')
+    .remove(b'_')
+    .remove(b'~');
+
+pub(crate) fn unique_token() -> String {
+    static COUNTER: AtomicU64 = AtomicU64::new(0);
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_nanos())
+        .unwrap_or_default();
+    let seed = format!(
+        "{}:{}:{}",
+        nanos,
+        std::process::id(),
+        COUNTER.fetch_add(1, Ordering::Relaxed)
+    );
+    format!("{:x}", Sha256::digest(seed.as_bytes()))[..16].to_string()
+}
+
+pub(crate) fn encode_component(value: &str) -> String {
+    utf8_percent_encode(value, COMPONENT).to_string()
+}
+
+fn header_name(key: &str) -> Result<header::HeaderName, PeakError> {
+    header::HeaderName::from_bytes(key.as_bytes())
+        .map_err(|e| PeakError::InvalidHeader(e.to_string()))
+}
+
+fn header_value(value: &str) -> Result<header::HeaderValue, PeakError> {
+    header::HeaderValue::from_str(value).map_err(|e| PeakError::InvalidHeader(e.to_string()))
+}
+
+pub(crate) fn header_map(headers: &header::HeaderMap) -> HashMap<String, String> {
+    let mut map: HashMap<String, String> = HashMap::new();
+    for (key, value) in headers {
+        let value = value.to_str().unwrap_or("");
+        match map.get_mut(key.as_str()) {
+            // set-cookie can't be folded into one line, so it keeps the last value
+            Some(existing) if key != header::SET_COOKIE => {
+                existing.push_str(", ");
+                existing.push_str(value);
+            }
+            _ => {
+                map.insert(key.to_string(), value.to_string());
+            }
+        }
+    }
+    map
 }
 
 impl Response {
-    pub fn json(&self) -> Result<Value, String> {
-        from_str(&self.text).map_err(|e| e.to_string())
+    pub fn display_url(&self) -> String {
+        self.redaction.url(&self.url)
+    }
+
+    // the body exactly as it came off the wire. cloning it is a refcount bump,
+    // so it can be sliced and forwarded without copying. a spilled body is read
+    // back into memory on the first call and kept there.
+    pub fn bytes_ref(&self) -> &Bytes {
+        self.body.as_bytes()
+    }
+
+    // a spilled body is read from disk on every call
+    pub fn bytes(&self) -> Bytes {
+        self.body.bytes()
+    }
+
+    // decoded by the Content-Type charset (utf-8 when there is none), borrowed
+    // from the body unless decoding had to change something or it was spilled
+    pub fn text(&self) -> Cow<'_, str> {
+        let encoding = self
+            .headers
+            .get("content-type")
+            .and_then(|value| content_type::charset(value))
+            .and_then(|label| Encoding::for_label(label.as_bytes()))
+            .unwrap_or(UTF_8);
+        match self.body.in_memory() {
+            Some(bytes) => encoding.decode(bytes).0,
+            None => Cow::Owned(encoding.decode(&self.body.bytes()).0.into_owned()),
+        }
+    }
+
+    #[cfg(feature = "json")]
+    pub fn json(&self) -> Result<Value, PeakError> {
+        self.check_captive_portal()?;
+        if self.strict_content_type {
+            return self.json_strict();
+        }
+        Ok(from_str(&self.text())?)
     }
 }
 
-pub fn get(url: &str) -> Result<Response, String> {
+pub fn get(url: &str) -> Result<Response, PeakError> {
     PeakRequests::new().get(url)
 }
 
-pub fn post(url: &str, data: HashMap<&str, &str>) -> Result<Response, String> {
-    PeakRequests::new().post(url, Some(data), None)
+pub fn post(url: &str, data: HashMap<&str, &str>) -> Result<Response, PeakError> {
+    PeakRequests::new()._request("POST", url, None, Some(data))
 }
 
-pub fn post_json(url: &str, json: Value) -> Result<Response, String> {
+#[cfg(feature = "json")]
+pub fn post_json(url: &str, json: Value) -> Result<Response, PeakError> {
     PeakRequests::new().post(url, None, Some(json))
 }
 
-pub fn put(url: &str, data: HashMap<&str, &str>) -> Result<Response, String> {
-    PeakRequests::new().put(url, Some(data), None)
+pub fn put(url: &str, data: HashMap<&str, &str>) -> Result<Response, PeakError> {
+    PeakRequests::new()._request("PUT", url, None, Some(data))
 }
 
-pub fn put_json(url: &str, json: Value) -> Result<Response, String> {
+#[cfg(feature = "json")]
+pub fn put_json(url: &str, json: Value) -> Result<Response, PeakError> {
     PeakRequests::new().put(url, None, Some(json))
 }
 
-pub fn delete(url: &str) -> Result<Response, String> {
+pub fn delete(url: &str) -> Result<Response, PeakError> {
     PeakRequests::new().delete(url)
 }
+
+pub fn head(url: &str) -> Result<Response, PeakError> {
+    PeakRequests::new().head(url)
+}
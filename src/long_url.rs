@@ -0,0 +1,94 @@
+/*
+ * MIT License
+ *
+ * Copyright (c) 2025 Squirrel
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in
+ * all copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+use crate::{PeakError, PeakRequests, PreparedRequest};
+use std::borrow::Cow;
+
+#[derive(Debug, Clone)]
+pub(crate) struct LongGet {
+    pub(crate) threshold: usize,
+    header: String,
+}
+
+impl PeakRequests {
+    // checked after any conversion below, so a converted GET only has to fit its path
+    pub fn max_url_length(mut self, bytes: usize) -> Self {
+        self.max_url_length = Some(bytes);
+        self
+    }
+
+    // a GET whose url is longer than threshold goes out as a POST with the query as a
+    // form body, and header_name: GET so the gateway knows what it really is
+    pub fn convert_long_get_to_post(mut self, threshold: usize, header_name: &str) -> Self {
+        self.long_get = Some(LongGet {
+            threshold,
+            header: header_name.to_string(),
+        });
+        self
+    }
+
+    pub(crate) fn shorten_long_get<'a>(
+        &self,
+        request: Cow<'a, PreparedRequest>,
+    ) -> Cow<'a, PreparedRequest> {
+        let Some(long_get) = &self.long_get else {
+            return request;
+        };
+        if !request.method.eq_ignore_ascii_case("GET")
+            || request.has_body()
+            || request.url.len() <= long_get.threshold
+        {
+            return request;
+        }
+        let url = request
+            .url
+            .split_once('#')
+            .map_or(&*request.url, |(url, _)| url);
+        let Some((path, query)) = url.split_once('?') else {
+            return request;
+        };
+        let (path, query) = (path.to_string(), query.to_string());
+
+        let mut converted = request.into_owned();
+        converted.method = "POST".to_string();
+        converted.body = Some(query.into_bytes());
+        converted.url = path;
+        converted.set_header(
+            "Content-Type",
+            "application/x-www-form-urlencoded".to_string(),
+        );
+        converted.set_header(&long_get.header, "GET".to_string());
+        Cow::Owned(converted)
+    }
+
+    pub(crate) fn check_url_length(&self, request: &PreparedRequest) -> Result<(), PeakError> {
+        match self.max_url_length {
+            Some(limit) if request.url.len() > limit => Err(PeakError::UrlTooLong {
+                length: request.url.len(),
+                limit,
+            }),
+            _ => Ok(()),
+        }
+    }
+}
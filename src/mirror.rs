@@ -0,0 +1,292 @@
+/*
+ * MIT License
+ *
+ * Copyright (c) 2025 Squirrel
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in
+ * all copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+use crate::background::InFlight;
+use crate::clock::Random;
+use crate::redirect;
+use crate::{
+    contain, send_detached, spawn_named, Callback, DiffOptions, PeakError, PeakRequests,
+    PreparedRequest, Response, ResponseDiff, ResponseSettings,
+};
+use reqwest::blocking::{Client, RequestBuilder};
+use reqwest::Url;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::mpsc::{self, Receiver, SyncSender};
+use std::sync::Arc;
+
+#[derive(Debug, Clone)]
+pub struct MirrorConfig {
+    pub target_base_url: String,
+    pub sample_rate: f64,
+    pub compare: bool,
+    // POST and PATCH are never replayed unless this is set
+    pub mirror_non_idempotent: bool,
+    // fixes the sampling sequence, handy in tests
+    pub seed: Option<u64>,
+    // replays waiting for the worker. when it's full new ones are dropped and counted.
+    pub queue: usize,
+}
+
+impl Default for MirrorConfig {
+    fn default() -> Self {
+        MirrorConfig {
+            target_base_url: String::new(),
+            sample_rate: 0.0,
+            compare: false,
+            mirror_non_idempotent: false,
+            seed: None,
+            queue: 64,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MirrorStats {
+    // replays the worker finished, whatever their outcome
+    pub mirrored: u64,
+    pub dropped: u64,
+}
+
+#[derive(Debug, Default)]
+struct Counters {
+    mirrored: AtomicU64,
+    dropped: AtomicU64,
+}
+
+#[derive(Debug)]
+struct Job {
+    url: String,
+    request_builder: RequestBuilder,
+    primary: Option<Response>,
+    settings: ResponseSettings,
+    hook: Option<MirrorHook>,
+}
+
+#[derive(Debug)]
+pub enum MirrorOutcome {
+    Sent { url: String, status: u16 },
+    Compared { url: String, diff: ResponseDiff },
+    Failed { url: String, error: PeakError },
+}
+
+pub(crate) type MirrorHook = Callback<dyn Fn(&MirrorOutcome) + Send + Sync>;
+
+#[derive(Debug)]
+pub(crate) struct Mirror {
+    config: MirrorConfig,
+    random: Random,
+    client: Option<Client>,
+    sender: SyncSender<Job>,
+    counters: Arc<Counters>,
+    in_flight: Arc<InFlight>,
+    closed: Arc<AtomicBool>,
+}
+
+impl Mirror {
+    fn sample(&mut self) -> bool {
+        self.random.next_f64() < self.config.sample_rate
+    }
+}
+
+impl Mirror {
+    pub(crate) fn in_flight(&self) -> &InFlight {
+        &self.in_flight
+    }
+
+    // for shutdown: whatever is still queued is skipped instead of replayed
+    pub(crate) fn close(&self) {
+        self.closed.store(true, Ordering::SeqCst);
+    }
+}
+
+impl PeakRequests {
+    pub fn mirror(mut self, config: MirrorConfig) -> Self {
+        let random = config.seed.map_or_else(|| self.random(), Random::new);
+        let (sender, receiver) = mpsc::sync_channel::<Job>(config.queue);
+        let counters = Arc::new(Counters::default());
+        let in_flight = Arc::new(InFlight::default());
+        let closed = Arc::new(AtomicBool::new(false));
+        let (worker_counters, worker_in_flight) = (Arc::clone(&counters), Arc::clone(&in_flight));
+        let worker_closed = Arc::clone(&closed);
+        // exits once the client (and with it the sender) is dropped. without the
+        // thread the receiver is gone too, so every job counts as dropped
+        let worker = spawn_named("mirror", move || {
+            replay_all(
+                receiver,
+                &worker_counters,
+                &worker_in_flight,
+                &worker_closed,
+            )
+        });
+        if let Err(e) = worker {
+            log::error!("could not start the mirror worker: {}", e);
+        }
+        self.mirror = Some(Mirror {
+            config,
+            random,
+            client: None,
+            sender,
+            counters,
+            in_flight,
+            closed,
+        });
+        self
+    }
+
+    pub fn mirror_stats(&self) -> MirrorStats {
+        let Some(mirror) = &self.mirror else {
+            return MirrorStats::default();
+        };
+        MirrorStats {
+            mirrored: mirror.counters.mirrored.load(Ordering::SeqCst),
+            dropped: mirror.counters.dropped.load(Ordering::SeqCst),
+        }
+    }
+
+    pub fn on_mirror(mut self, f: impl Fn(&MirrorOutcome) + Send + Sync + 'static) -> Self {
+        self.on_mirror = Some(Callback(Arc::new(f)));
+        self
+    }
+
+    // runs after the primary response is in hand. nothing in here can fail or slow
+    // down the caller: the replay is queued for the worker and problems only reach the hook.
+    pub(crate) fn mirror_request(&mut self, request: &PreparedRequest, primary: &Response) {
+        let Some(mirror) = self.mirror.as_mut() else {
+            return;
+        };
+        let idempotent = !matches!(request.method.as_str(), "POST" | "PATCH");
+        if !idempotent && !mirror.config.mirror_non_idempotent {
+            return;
+        }
+        if !mirror.sample() {
+            return;
+        }
+
+        let compare = mirror.config.compare;
+        let Some(url) = mirror_url(&mirror.config.target_base_url, &request.url) else {
+            return;
+        };
+        let client = match mirror.client.clone() {
+            Some(client) => client,
+            None => match self.mirror_client() {
+                Ok(client) => {
+                    if let Some(mirror) = self.mirror.as_mut() {
+                        mirror.client = Some(client.clone());
+                    }
+                    client
+                }
+                Err(error) => return self.report_mirror(MirrorOutcome::Failed { url, error }),
+            },
+        };
+
+        let mut replay = request.clone();
+        replay.url = url.clone();
+        // the shadow backend isn't who the credentials were meant for, so it's
+        // treated like a redirect that left the origin
+        replay.left_origin = true;
+        replay
+            .headers
+            .retain(|(name, _)| !redirect::is_sensitive(name));
+        let request_builder = match self.request_builder(&client, &replay) {
+            Ok(request_builder) => request_builder,
+            Err(error) => return self.report_mirror(MirrorOutcome::Failed { url, error }),
+        };
+
+        let job = Job {
+            url,
+            request_builder,
+            primary: compare.then(|| primary.clone()),
+            settings: self.response_settings(),
+            hook: self.on_mirror.clone(),
+        };
+        let Some(mirror) = self.mirror.as_ref() else {
+            return;
+        };
+        mirror.in_flight.start();
+        // full queue: drop the replay rather than hold up the caller
+        if mirror.sender.try_send(job).is_err() {
+            mirror.in_flight.done();
+            mirror.counters.dropped.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    // like detached_client, minus the default headers and the cookie jar. the
+    // client-wide headers still go on per request, without the credentials.
+    fn mirror_client(&self) -> Result<Client, PeakError> {
+        Ok(self
+            .client_builder()?
+            .cookie_store(false)
+            .redirect(self.detached_redirects())
+            .build()?)
+    }
+
+    fn report_mirror(&self, outcome: MirrorOutcome) {
+        if let Some(hook) = &self.on_mirror {
+            if let Err(e) = contain("on_mirror", || hook(&outcome)) {
+                log::warn!("{}", e);
+            }
+        }
+    }
+}
+
+fn replay_all(jobs: Receiver<Job>, counters: &Counters, in_flight: &InFlight, closed: &AtomicBool) {
+    for job in jobs {
+        if closed.load(Ordering::SeqCst) {
+            in_flight.done();
+            continue;
+        }
+        let url = job.url;
+        let outcome = match send_detached(job.request_builder, &job.settings) {
+            Ok(mirrored) => match job.primary {
+                Some(primary) => MirrorOutcome::Compared {
+                    url,
+                    diff: primary.diff(&mirrored, DiffOptions::default()),
+                },
+                None => MirrorOutcome::Sent {
+                    url,
+                    status: mirrored.status_code,
+                },
+            },
+            Err(error) => MirrorOutcome::Failed { url, error },
+        };
+        if let Some(hook) = job.hook {
+            if let Err(e) = contain("on_mirror", || hook(&outcome)) {
+                log::warn!("{}", e);
+            }
+        }
+        counters.mirrored.fetch_add(1, Ordering::SeqCst);
+        in_flight.done();
+    }
+}
+
+// keeps path and query, swaps everything before them for the mirror's base
+fn mirror_url(base: &str, url: &str) -> Option<String> {
+    let url = Url::parse(url).ok()?;
+    let mut path = url.path().to_string();
+    if let Some(query) = url.query() {
+        path.push('?');
+        path.push_str(query);
+    }
+    Some(format!("{}{}", base.trim_end_matches('/'), path))
+}
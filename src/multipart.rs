@@ -0,0 +1,155 @@
+/*
+ * MIT License
+ *
+ * Copyright (c) 2025 Squirrel
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in
+ * all copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+use crate::{unique_token, PeakRequestBuilder};
+#[cfg(feature = "json")]
+use serde_json::Value;
+
+// multipart/related (rfc 2387): ordered parts tied together by Content-ID, the
+// first one (or the one named by start()) is the root the others hang off
+#[derive(Debug, Clone)]
+pub struct Multipart {
+    boundary: String,
+    parts: Vec<RelatedPart>,
+    start: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct RelatedPart {
+    content_type: String,
+    content_id: String,
+    headers: Vec<(String, String)>,
+    body: Vec<u8>,
+}
+
+impl RelatedPart {
+    pub fn new(content_type: &str, body: impl Into<Vec<u8>>) -> Self {
+        RelatedPart {
+            content_type: content_type.to_string(),
+            content_id: format!("{}@peakrequests", unique_token()),
+            headers: Vec::new(),
+            body: body.into(),
+        }
+    }
+
+    #[cfg(feature = "json")]
+    pub fn json(value: &Value) -> Self {
+        RelatedPart::new("application/json", value.to_string())
+    }
+
+    // without the angle brackets, they get added on the wire
+    pub fn content_id(mut self, content_id: &str) -> Self {
+        self.content_id = content_id
+            .trim_start_matches('<')
+            .trim_end_matches('>')
+            .to_string();
+        self
+    }
+
+    pub fn header(mut self, key: &str, value: &str) -> Self {
+        self.headers.push((key.to_string(), value.to_string()));
+        self
+    }
+
+    pub fn id(&self) -> &str {
+        &self.content_id
+    }
+
+    // what another part uses to point at this one
+    pub fn cid_url(&self) -> String {
+        format!("cid:{}", self.content_id)
+    }
+}
+
+impl Multipart {
+    pub fn related() -> Self {
+        Multipart {
+            boundary: format!("peakrequests-{}", unique_token()),
+            parts: Vec::new(),
+            start: None,
+        }
+    }
+
+    pub fn part(mut self, part: RelatedPart) -> Self {
+        self.parts.push(part);
+        self
+    }
+
+    pub fn start(mut self, content_id: &str) -> Self {
+        self.start = Some(
+            content_id
+                .trim_start_matches('<')
+                .trim_end_matches('>')
+                .to_string(),
+        );
+        self
+    }
+
+    fn root(&self) -> Option<&RelatedPart> {
+        match &self.start {
+            Some(start) => self.parts.iter().find(|part| &part.content_id == start),
+            None => self.parts.first(),
+        }
+    }
+
+    // type is required by rfc 2387 and has to be the root part's media type
+    pub fn content_type(&self) -> String {
+        let mut value = format!("multipart/related; boundary=\"{}\"", self.boundary);
+        if let Some(root) = self.root() {
+            let media_type = root.content_type.split(';').next().unwrap_or("").trim();
+            value.push_str(&format!("; type=\"{}\"", media_type));
+        }
+        if let Some(start) = &self.start {
+            value.push_str(&format!("; start=\"<{}>\"", start));
+        }
+        value
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut body = Vec::new();
+        for part in &self.parts {
+            body.extend_from_slice(format!("--{}\r\n", self.boundary).as_bytes());
+            body.extend_from_slice(format!("Content-Type: {}\r\n", part.content_type).as_bytes());
+            body.extend_from_slice(format!("Content-ID: <{}>\r\n", part.content_id).as_bytes());
+            for (key, value) in &part.headers {
+                body.extend_from_slice(format!("{}: {}\r\n", key, value).as_bytes());
+            }
+            body.extend_from_slice(b"\r\n");
+            body.extend_from_slice(&part.body);
+            body.extend_from_slice(b"\r\n");
+        }
+        body.extend_from_slice(format!("--{}--\r\n", self.boundary).as_bytes());
+        body
+    }
+}
+
+impl PeakRequestBuilder<'_> {
+    pub fn multipart(mut self, multipart: Multipart) -> Self {
+        self.request.clear_body();
+        self.request.body = Some(multipart.to_bytes());
+        self.request
+            .set_header("Content-Type", multipart.content_type());
+        self
+    }
+}
@@ -0,0 +1,83 @@
+/*
+ * MIT License
+ *
+ * Copyright (c) 2025 Squirrel
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in
+ * all copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+use crate::content_type::media_type_matches;
+use crate::{PeakError, PeakRequestBuilder, PeakRequests, PreparedRequest, Response};
+
+impl PeakRequestBuilder<'_> {
+    pub fn accept_fallback(mut self, types: &[&str]) -> Self {
+        self.request.accept_fallback = types.iter().map(|t| t.to_string()).collect();
+        self
+    }
+}
+
+impl PeakRequests {
+    pub(crate) fn execute_negotiated(
+        &mut self,
+        request: &PreparedRequest,
+        types: &[String],
+    ) -> Result<Response, PeakError> {
+        let mut candidates = vec![weighted_accept(types)];
+        if types.len() > 1 {
+            candidates.extend(types.iter().cloned());
+        }
+
+        let mut attempted = Vec::new();
+        for accept in candidates {
+            let mut attempt = request.clone();
+            attempt.set_header("Accept", accept.clone());
+
+            let mut response = self.execute_prepared(&attempt)?;
+            let unexpected_type = self.strict_content_type
+                && !response.content_type().is_some_and(|got| {
+                    accept
+                        .split(',')
+                        .any(|candidate| media_type_matches(candidate, &got))
+                });
+            if response.status_code != 406 && !unexpected_type {
+                response.negotiated_accept = Some(accept);
+                return Ok(response);
+            }
+            attempted.push(accept);
+        }
+
+        Err(PeakError::NotAcceptable { attempted })
+    }
+}
+
+fn weighted_accept(types: &[String]) -> String {
+    types
+        .iter()
+        .enumerate()
+        .map(|(i, media_type)| {
+            let q = 10usize.saturating_sub(i).max(1);
+            if q == 10 {
+                media_type.clone()
+            } else {
+                format!("{};q=0.{}", media_type, q)
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
@@ -0,0 +1,310 @@
+/*
+ * MIT License
+ *
+ * Copyright (c) 2025 Squirrel
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in
+ * all copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+use crate::{PeakRequests, PreparedRequest};
+use reqwest::Url;
+use std::borrow::Cow;
+
+// every step can be switched off on its own. the defaults are the ones that
+// never change what a server sees (rfc 3986 6.2.2), the query sort and slash
+// removal can, so they're opt-in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UrlNormalization {
+    pub lowercase_scheme_and_host: bool,
+    pub strip_default_port: bool,
+    pub uppercase_percent_encoding: bool,
+    pub decode_unreserved: bool,
+    pub sort_query: bool,
+    pub remove_trailing_slash: bool,
+}
+
+impl Default for UrlNormalization {
+    fn default() -> Self {
+        UrlNormalization {
+            lowercase_scheme_and_host: true,
+            strip_default_port: true,
+            uppercase_percent_encoding: true,
+            decode_unreserved: true,
+            sort_query: false,
+            remove_trailing_slash: false,
+        }
+    }
+}
+
+// a url cut up as written, without any of the parsing Url does to it
+pub(crate) struct UrlParts<'a> {
+    pub(crate) scheme: &'a str,
+    pub(crate) authority: &'a str,
+    pub(crate) path: &'a str,
+    pub(crate) query: Option<&'a str>,
+    pub(crate) fragment: Option<&'a str>,
+}
+
+impl<'a> UrlParts<'a> {
+    pub(crate) fn split(url: &'a str) -> Option<Self> {
+        let (scheme, rest) = url.split_once("://")?;
+        let (rest, fragment) = match rest.split_once('#') {
+            Some((rest, fragment)) => (rest, Some(fragment)),
+            None => (rest, None),
+        };
+        let authority_end = rest.find(['/', '?']).unwrap_or(rest.len());
+        let (authority, rest) = rest.split_at(authority_end);
+        let (path, query) = match rest.split_once('?') {
+            Some((path, query)) => (path, Some(query)),
+            None => (rest, None),
+        };
+        Some(UrlParts {
+            scheme,
+            authority,
+            path,
+            query,
+            fragment,
+        })
+    }
+}
+
+impl UrlNormalization {
+    // works on the string as given, anything that doesn't look like scheme://... is
+    // handed back untouched
+    pub fn normalize(&self, url: &str) -> String {
+        let Some(UrlParts {
+            scheme,
+            authority,
+            path,
+            query,
+            fragment,
+        }) = UrlParts::split(url)
+        else {
+            return url.to_string();
+        };
+
+        let scheme = if self.lowercase_scheme_and_host {
+            scheme.to_ascii_lowercase()
+        } else {
+            scheme.to_string()
+        };
+
+        let (userinfo, host_port) = match authority.rsplit_once('@') {
+            Some((userinfo, host_port)) => (Some(userinfo), host_port),
+            None => (None, authority),
+        };
+        let mut host_port = if self.lowercase_scheme_and_host {
+            host_port.to_ascii_lowercase()
+        } else {
+            host_port.to_string()
+        };
+        if self.strip_default_port {
+            let default_port = match scheme.to_ascii_lowercase().as_str() {
+                "http" | "ws" => Some(":80"),
+                "https" | "wss" => Some(":443"),
+                _ => None,
+            };
+            if let Some(port) = default_port {
+                if host_port.ends_with(port) {
+                    host_port.truncate(host_port.len() - port.len());
+                }
+            }
+        }
+
+        let mut path = if path.is_empty() {
+            "/".to_string()
+        } else {
+            path.to_string()
+        };
+        if self.remove_trailing_slash && path.len() > 1 && path.ends_with('/') {
+            path.pop();
+        }
+
+        let query = query.map(|query| {
+            if !self.sort_query {
+                return query.to_string();
+            }
+            let mut pairs: Vec<&str> = query.split('&').filter(|pair| !pair.is_empty()).collect();
+            pairs.sort_by_key(|pair| pair.split_once('=').map_or(*pair, |(key, _)| key));
+            pairs.join("&")
+        });
+
+        let mut out = format!("{}://", scheme);
+        if let Some(userinfo) = userinfo {
+            out.push_str(userinfo);
+            out.push('@');
+        }
+        out.push_str(&host_port);
+        out.push_str(&self.percent_encoding(&path));
+        if let Some(query) = query {
+            out.push('?');
+            out.push_str(&self.percent_encoding(&query));
+        }
+        if let Some(fragment) = fragment {
+            out.push('#');
+            out.push_str(fragment);
+        }
+        out
+    }
+
+    // %7E and ~ are the same url, so unreserved characters are decoded. anything
+    // else stays encoded, only its hex digits are uppercased
+    fn percent_encoding(&self, input: &str) -> String {
+        if !self.uppercase_percent_encoding && !self.decode_unreserved {
+            return input.to_string();
+        }
+        let bytes = input.as_bytes();
+        let mut out = String::with_capacity(input.len());
+        let mut at = 0;
+        while at < bytes.len() {
+            if bytes[at] == b'%'
+                && at + 2 < bytes.len()
+                && bytes[at + 1].is_ascii_hexdigit()
+                && bytes[at + 2].is_ascii_hexdigit()
+            {
+                let decoded = hex_value(bytes[at + 1]) << 4 | hex_value(bytes[at + 2]);
+                if self.decode_unreserved && is_unreserved(decoded) {
+                    out.push(decoded as char);
+                } else if self.uppercase_percent_encoding {
+                    out.push('%');
+                    out.push(bytes[at + 1].to_ascii_uppercase() as char);
+                    out.push(bytes[at + 2].to_ascii_uppercase() as char);
+                } else {
+                    out.push_str(&input[at..at + 3]);
+                }
+                at += 3;
+                continue;
+            }
+            let c = input[at..].chars().next().unwrap();
+            out.push(c);
+            at += c.len_utf8();
+        }
+        out
+    }
+}
+
+fn hex_value(digit: u8) -> u8 {
+    match digit {
+        b'0'..=b'9' => digit - b'0',
+        _ => digit.to_ascii_uppercase() - b'A' + 10,
+    }
+}
+
+fn is_unreserved(byte: u8) -> bool {
+    byte.is_ascii_alphanumeric() || matches!(byte, b'-' | b'.' | b'_' | b'~')
+}
+
+impl PeakRequests {
+    pub fn url_normalization(mut self, normalization: UrlNormalization) -> Self {
+        self.url_normalization = normalization;
+        self
+    }
+
+    // off by default: the url on the wire is the one the caller wrote
+    pub fn normalize_outgoing(mut self, normalize: bool) -> Self {
+        self.normalize_outgoing = normalize;
+        self
+    }
+
+    // the key to use when deduplicating or caching by url with this client's rules
+    pub fn normalize_url(&self, url: &str) -> String {
+        self.url_normalization.normalize(url)
+    }
+
+    // what robots and alt-svc key their per-origin caches on, and robots matches its
+    // rules against, so every spelling of a url lands on the same entry
+    pub(crate) fn parse_normalized(&self, url: &str) -> Option<Url> {
+        Url::parse(&self.normalize_url(url)).ok()
+    }
+
+    pub(crate) fn outgoing<'a>(&self, request: &'a PreparedRequest) -> Cow<'a, PreparedRequest> {
+        if !self.normalize_outgoing {
+            return Cow::Borrowed(request);
+        }
+        let mut normalized = request.clone();
+        normalized.url = self.normalize_url(&request.url);
+        Cow::Owned(normalized)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decodes_only_unreserved_characters() {
+        let normalization = UrlNormalization::default();
+        assert_eq!(
+            normalization.normalize("http://example.com/%7euser/%41%2d%5F?q=%2e%2f%3d"),
+            "http://example.com/~user/A-_?q=.%2F%3D"
+        );
+    }
+
+    #[test]
+    fn decoding_can_be_switched_off() {
+        let normalization = UrlNormalization {
+            decode_unreserved: false,
+            ..Default::default()
+        };
+        assert_eq!(
+            normalization.normalize("http://example.com/%7euser/%2f"),
+            "http://example.com/%7Euser/%2F"
+        );
+
+        let normalization = UrlNormalization {
+            uppercase_percent_encoding: false,
+            ..Default::default()
+        };
+        assert_eq!(
+            normalization.normalize("http://example.com/%7euser/%2f"),
+            "http://example.com/~user/%2f"
+        );
+    }
+
+    #[test]
+    fn default_steps_keep_the_meaning() {
+        let normalization = UrlNormalization::default();
+        assert_eq!(
+            normalization.normalize("HTTP://User@Example.COM:80?b=2&a=%3d#Frag"),
+            "http://User@example.com/?b=2&a=%3D#Frag"
+        );
+        assert_eq!(
+            normalization.normalize("https://example.com:8443/a/"),
+            "https://example.com:8443/a/"
+        );
+        assert_eq!(normalization.normalize("not a url"), "not a url");
+    }
+
+    #[test]
+    fn opt_in_steps() {
+        let normalization = UrlNormalization {
+            sort_query: true,
+            remove_trailing_slash: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            normalization.normalize("http://example.com/a/?b=2&&a=1&a=0"),
+            "http://example.com/a?a=1&a=0&b=2"
+        );
+        assert_eq!(
+            normalization.normalize("http://example.com/"),
+            "http://example.com/"
+        );
+    }
+}
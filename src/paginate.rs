@@ -0,0 +1,201 @@
+/*
+ * MIT License
+ *
+ * Copyright (c) 2025 Squirrel
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in
+ * all copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+use crate::{PeakError, PeakRequests, PreparedRequest, Response};
+use serde_json::Value;
+use std::collections::HashSet;
+
+const DEFAULT_MAX_PAGES: usize = 100;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Offset<'a> {
+    pub param: &'a str,
+    pub limit: u64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CursorSpec<'a> {
+    pub request_param: &'a str,
+    pub response_pointer: &'a str,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Style<'a> {
+    Single,
+    Offset(Offset<'a>),
+    Cursor(CursorSpec<'a>),
+}
+
+pub struct Paginator<'a> {
+    client: &'a mut PeakRequests,
+    url: String,
+    style: Style<'a>,
+    limit_param: &'a str,
+    items_pointer: Option<&'a str>,
+    max_pages: usize,
+    pages: usize,
+    offset: u64,
+    cursor: Option<String>,
+    seen_cursors: HashSet<String>,
+    done: bool,
+}
+
+impl PeakRequests {
+    pub fn paginate(&mut self, url: &str) -> Paginator<'_> {
+        Paginator {
+            client: self,
+            url: url.to_string(),
+            style: Style::Single,
+            limit_param: "limit",
+            items_pointer: None,
+            max_pages: DEFAULT_MAX_PAGES,
+            pages: 0,
+            offset: 0,
+            cursor: None,
+            seen_cursors: HashSet::new(),
+            done: false,
+        }
+    }
+}
+
+impl<'a> Paginator<'a> {
+    pub fn query_style(mut self, offset: Offset<'a>) -> Self {
+        self.style = Style::Offset(offset);
+        self
+    }
+
+    pub fn cursor_style(mut self, cursor: CursorSpec<'a>) -> Self {
+        self.style = Style::Cursor(cursor);
+        self
+    }
+
+    pub fn limit_param(mut self, name: &'a str) -> Self {
+        self.limit_param = name;
+        self
+    }
+
+    // where the page's items live in the json body, for empty-page detection.
+    // without it a top-level array (or an empty body) is what counts.
+    pub fn items_pointer(mut self, pointer: &'a str) -> Self {
+        self.items_pointer = Some(pointer);
+        self
+    }
+
+    pub fn max_pages(mut self, max: usize) -> Self {
+        self.max_pages = max;
+        self
+    }
+
+    fn next_request(&self) -> PreparedRequest {
+        let mut request = PreparedRequest::new("GET", &self.url);
+        match self.style {
+            Style::Single => {}
+            Style::Offset(offset) => {
+                request.append_query(offset.param, &self.offset.to_string());
+                request.append_query(self.limit_param, &offset.limit.to_string());
+            }
+            Style::Cursor(spec) => {
+                if let Some(cursor) = &self.cursor {
+                    request.append_query(spec.request_param, cursor);
+                }
+            }
+        }
+        request
+    }
+
+    fn is_empty_page(&self, response: &Response) -> bool {
+        if response.text().trim().is_empty() {
+            return true;
+        }
+        let Ok(body) = serde_json::from_str::<Value>(&response.text()) else {
+            return false;
+        };
+        let items = match self.items_pointer {
+            Some(pointer) => body.pointer(pointer),
+            None => Some(&body),
+        };
+        match items {
+            Some(Value::Array(items)) => items.is_empty(),
+            Some(Value::Null) => true,
+            _ => false,
+        }
+    }
+}
+
+impl Iterator for Paginator<'_> {
+    type Item = Result<Response, PeakError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done || self.pages >= self.max_pages {
+            return None;
+        }
+
+        let request = self.next_request();
+        let response = match self.client.execute_prepared(&request) {
+            Ok(response) => response,
+            Err(e) => {
+                self.done = true;
+                return Some(Err(e));
+            }
+        };
+        self.pages += 1;
+
+        if !(200..300).contains(&response.status_code) || self.is_empty_page(&response) {
+            self.done = true;
+            return Some(Ok(response));
+        }
+
+        match self.style {
+            Style::Single => self.done = true,
+            Style::Offset(offset) => self.offset += offset.limit,
+            Style::Cursor(spec) => {
+                let next = serde_json::from_str::<Value>(&response.text())
+                    .ok()
+                    .and_then(|body| match body.pointer(spec.response_pointer) {
+                        Some(Value::String(cursor)) if !cursor.is_empty() => Some(cursor.clone()),
+                        Some(Value::Number(cursor)) => Some(cursor.to_string()),
+                        _ => None,
+                    });
+
+                match next {
+                    None => self.done = true,
+                    Some(cursor) => {
+                        if let Some(current) = self.cursor.take() {
+                            self.seen_cursors.insert(current);
+                        }
+                        // a server that keeps handing back a cursor we've already
+                        // followed would otherwise page forever
+                        if self.seen_cursors.contains(&cursor) {
+                            self.done = true;
+                            return Some(Err(PeakError::PaginationLoop { cursor }));
+                        }
+                        self.cursor = Some(cursor);
+                    }
+                }
+            }
+        }
+
+        Some(Ok(response))
+    }
+}
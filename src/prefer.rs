@@ -0,0 +1,270 @@
+/*
+ * MIT License
+ *
+ * Copyright (c) 2025 Squirrel
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in
+ * all copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+use crate::{PeakRequestBuilder, Response};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Preference {
+    RespondAsync,
+    ReturnMinimal,
+    ReturnRepresentation,
+    Wait(u64),
+    HandlingStrict,
+    HandlingLenient,
+    Other {
+        name: String,
+        value: Option<String>,
+        params: Vec<(String, Option<String>)>,
+    },
+}
+
+impl Preference {
+    pub fn raw(name: &str, value: Option<&str>) -> Self {
+        Preference::Other {
+            name: name.to_string(),
+            value: value.map(|v| v.to_string()),
+            params: Vec::new(),
+        }
+    }
+
+    fn to_header_value(&self) -> String {
+        match self {
+            Preference::RespondAsync => "respond-async".to_string(),
+            Preference::ReturnMinimal => "return=minimal".to_string(),
+            Preference::ReturnRepresentation => "return=representation".to_string(),
+            Preference::Wait(seconds) => format!("wait={}", seconds),
+            Preference::HandlingStrict => "handling=strict".to_string(),
+            Preference::HandlingLenient => "handling=lenient".to_string(),
+            Preference::Other {
+                name,
+                value,
+                params,
+            } => {
+                let mut out = pair(name, value.as_deref());
+                for (param, param_value) in params {
+                    out.push_str("; ");
+                    out.push_str(&pair(param, param_value.as_deref()));
+                }
+                out
+            }
+        }
+    }
+
+    fn from_parts(
+        name: &str,
+        value: Option<String>,
+        params: Vec<(String, Option<String>)>,
+    ) -> Self {
+        let lower_value = value.as_deref().map(str::to_ascii_lowercase);
+        if params.is_empty() {
+            match (name.to_ascii_lowercase().as_str(), lower_value.as_deref()) {
+                ("respond-async", None) => return Preference::RespondAsync,
+                ("return", Some("minimal")) => return Preference::ReturnMinimal,
+                ("return", Some("representation")) => return Preference::ReturnRepresentation,
+                ("handling", Some("strict")) => return Preference::HandlingStrict,
+                ("handling", Some("lenient")) => return Preference::HandlingLenient,
+                ("wait", Some(seconds)) => {
+                    if let Ok(seconds) = seconds.parse() {
+                        return Preference::Wait(seconds);
+                    }
+                }
+                _ => {}
+            }
+        }
+        Preference::Other {
+            name: name.to_string(),
+            value,
+            params,
+        }
+    }
+}
+
+pub(crate) fn format_preferences(preferences: &[Preference]) -> String {
+    preferences
+        .iter()
+        .map(Preference::to_header_value)
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+pub(crate) fn parse_preferences(header: &str) -> Vec<Preference> {
+    split_unquoted(header, ',')
+        .iter()
+        .filter_map(|element| {
+            let mut parts = split_unquoted(element, ';').into_iter();
+            let (name, value) = split_pair(&parts.next()?)?;
+            let params = parts.filter_map(|param| split_pair(&param)).collect();
+            Some(Preference::from_parts(&name, value, params))
+        })
+        .collect()
+}
+
+pub(crate) fn pair(name: &str, value: Option<&str>) -> String {
+    match value {
+        None => name.to_string(),
+        Some(value) if !value.is_empty() && value.chars().all(is_tchar) => {
+            format!("{}={}", name, value)
+        }
+        Some(value) => format!(
+            "{}=\"{}\"",
+            name,
+            value.replace('\\', "\\\\").replace('"', "\\\"")
+        ),
+    }
+}
+
+pub(crate) fn split_pair(part: &str) -> Option<(String, Option<String>)> {
+    let part = part.trim();
+    if part.is_empty() {
+        return None;
+    }
+    match part.split_once('=') {
+        None => Some((part.to_string(), None)),
+        Some((name, value)) => {
+            let value = value.trim();
+            // a bare "name=" is the same as no value per rfc 7240
+            let value = if value.is_empty() {
+                None
+            } else {
+                Some(unquote(value))
+            };
+            Some((name.trim().to_string(), value))
+        }
+    }
+}
+
+fn unquote(value: &str) -> String {
+    if !(value.len() >= 2 && value.starts_with('"') && value.ends_with('"')) {
+        return value.to_string();
+    }
+    let mut out = String::new();
+    let mut chars = value[1..value.len() - 1].chars();
+    while let Some(c) = chars.next() {
+        if c == '\\' {
+            if let Some(escaped) = chars.next() {
+                out.push(escaped);
+            }
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
+
+pub(crate) fn split_unquoted(input: &str, separator: char) -> Vec<String> {
+    let mut parts = Vec::new();
+    let mut current = String::new();
+    let mut quoted = false;
+    let mut escaped = false;
+    for c in input.chars() {
+        if escaped {
+            escaped = false;
+        } else if quoted && c == '\\' {
+            escaped = true;
+        } else if c == '"' {
+            quoted = !quoted;
+        } else if c == separator && !quoted {
+            parts.push(std::mem::take(&mut current));
+            continue;
+        }
+        current.push(c);
+    }
+    parts.push(current);
+    parts
+}
+
+fn is_tchar(c: char) -> bool {
+    c.is_ascii_alphanumeric() || "!#$%&'*+-.^_`|~".contains(c)
+}
+
+impl PeakRequestBuilder<'_> {
+    pub fn prefer(self, preferences: &[Preference]) -> Self {
+        if preferences.is_empty() {
+            return self;
+        }
+        self.header("Prefer", &format_preferences(preferences))
+    }
+}
+
+impl Response {
+    pub fn preferences_applied(&self) -> Vec<Preference> {
+        self.headers
+            .get("preference-applied")
+            .map(|header| parse_preferences(header))
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_known_preferences() {
+        assert_eq!(
+            parse_preferences("respond-async, RETURN=Minimal, wait=10, handling=\"strict\""),
+            [
+                Preference::RespondAsync,
+                Preference::ReturnMinimal,
+                Preference::Wait(10),
+                Preference::HandlingStrict,
+            ]
+        );
+    }
+
+    #[test]
+    fn keeps_anything_else_as_other() {
+        let other = |name: &str, value: &str, params: &[(&str, Option<&str>)]| Preference::Other {
+            name: name.to_string(),
+            value: Some(value.to_string()),
+            params: params
+                .iter()
+                .map(|(name, value)| (name.to_string(), value.map(str::to_string)))
+                .collect(),
+        };
+        assert_eq!(
+            parse_preferences(r#"wait=soon, foo="a; b, c"; bar; baz=, return=minimal; x=1"#),
+            [
+                other("wait", "soon", &[]),
+                other("foo", "a; b, c", &[("bar", None), ("baz", None)]),
+                other("return", "minimal", &[("x", Some("1"))]),
+            ]
+        );
+    }
+
+    #[test]
+    fn formatting_round_trips() {
+        let preferences = [
+            Preference::RespondAsync,
+            Preference::Wait(5),
+            Preference::raw("note", Some("say \"hi\", then leave")),
+        ];
+        let header = format_preferences(&preferences);
+        assert_eq!(
+            header,
+            r#"respond-async, wait=5, note="say \"hi\", then leave""#
+        );
+        assert_eq!(parse_preferences(&header), preferences);
+    }
+}
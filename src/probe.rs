@@ -0,0 +1,86 @@
+/*
+ * MIT License
+ *
+ * Copyright (c) 2025 Squirrel
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in
+ * all copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+use crate::{PeakError, PeakRequests};
+use std::time::{Duration, Instant};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProbeResult {
+    pub attempt: usize,
+    pub status: Option<u16>,
+    pub error: Option<String>,
+    pub ttfb: Option<Duration>,
+    pub total: Duration,
+}
+
+impl PeakRequests {
+    // runs on its own client holding at most one idle connection, so every attempt
+    // after the first goes over the same socket unless the server drops it
+    pub fn probe(
+        &mut self,
+        url: &str,
+        count: usize,
+        interval: Duration,
+    ) -> Result<Vec<ProbeResult>, PeakError> {
+        let client = self
+            .client_builder()?
+            .default_headers(self.default_headers()?)
+            .pool_max_idle_per_host(1)
+            .build()?;
+        let mut results = Vec::with_capacity(count);
+
+        for attempt in 1..=count {
+            if attempt > 1 {
+                self.clock().sleep(interval);
+            }
+
+            let started = self.clock().now();
+            let since = |started: Instant| self.clock().now().saturating_duration_since(started);
+            let result = match client.get(url).send() {
+                Ok(response) => {
+                    let ttfb = since(started);
+                    let status = response.status().as_u16();
+                    let body = response.bytes();
+                    ProbeResult {
+                        attempt,
+                        status: Some(status),
+                        error: body.err().map(|e| e.to_string()),
+                        ttfb: Some(ttfb),
+                        total: since(started),
+                    }
+                }
+                Err(e) => ProbeResult {
+                    attempt,
+                    status: None,
+                    error: Some(e.to_string()),
+                    ttfb: None,
+                    total: since(started),
+                },
+            };
+            results.push(result);
+        }
+
+        Ok(results)
+    }
+}
@@ -0,0 +1,130 @@
+/*
+ * MIT License
+ *
+ * Copyright (c) 2025 Squirrel
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in
+ * all copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+use crate::content_type::snippet;
+use crate::{PeakError, Response};
+#[cfg(feature = "json")]
+use serde::Serialize;
+#[cfg(feature = "json")]
+use serde_json::{Map, Value};
+
+#[cfg(feature = "json")]
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct ProblemDetails {
+    #[serde(rename = "type")]
+    pub problem_type: Option<String>,
+    pub title: Option<String>,
+    pub status: Option<u16>,
+    pub detail: Option<String>,
+    pub instance: Option<String>,
+    #[serde(flatten)]
+    pub extensions: Map<String, Value>,
+}
+
+#[cfg(feature = "json")]
+impl ProblemDetails {
+    // members with the wrong json type are treated as absent rather than failing
+    // the whole parse, which is what rfc 7807 asks consumers to do
+    pub fn from_json(value: &Value) -> Option<Self> {
+        let mut members = value.as_object()?.clone();
+        let mut text = |name: &str| match members.remove(name) {
+            Some(Value::String(value)) => Some(value),
+            _ => None,
+        };
+
+        let problem_type = text("type");
+        let title = text("title");
+        let detail = text("detail");
+        let instance = text("instance");
+        let status = match members.remove("status") {
+            Some(status) => status.as_u64().and_then(|s| u16::try_from(s).ok()),
+            None => None,
+        };
+
+        Some(ProblemDetails {
+            problem_type,
+            title,
+            status,
+            detail,
+            instance,
+            extensions: members,
+        })
+    }
+}
+
+impl Response {
+    #[cfg(feature = "json")]
+    pub fn problem(&self) -> Option<ProblemDetails> {
+        if self.content_type()? != "application/problem+json" {
+            return None;
+        }
+        let value: Value = serde_json::from_str(&self.text()).ok()?;
+        ProblemDetails::from_json(&value)
+    }
+
+    pub fn error_for_status(self) -> Result<Response, PeakError> {
+        if self.status_code < 400 {
+            return Ok(self);
+        }
+        if self.status_code == 426 {
+            let protocols = self
+                .headers
+                .get("upgrade")
+                .map(|upgrade| {
+                    upgrade
+                        .split(',')
+                        .map(str::trim)
+                        .filter(|protocol| !protocol.is_empty())
+                        .map(str::to_string)
+                        .collect()
+                })
+                .unwrap_or_default();
+            return Err(PeakError::UpgradeRequired {
+                protocols,
+                response: Box::new(self),
+            });
+        }
+        Err(PeakError::Status {
+            status: self.status_code,
+            url: self.display_url(),
+            body_snippet: snippet(&self.text()),
+            #[cfg(feature = "json")]
+            problem: self.problem().map(Box::new),
+            response: Box::new(self),
+        })
+    }
+}
+
+#[cfg(feature = "json")]
+pub(crate) fn describe_status(status: u16, url: &str, problem: Option<&ProblemDetails>) -> String {
+    let mut message = format!("HTTP status {} for {}", status, url);
+    if let Some(problem) = problem {
+        for part in [&problem.title, &problem.detail].into_iter().flatten() {
+            // these come from the server, so they get cut like the body snippet
+            message.push_str(": ");
+            message.push_str(&snippet(part));
+        }
+    }
+    message
+}
@@ -0,0 +1,54 @@
+/*
+ * MIT License
+ *
+ * Copyright (c) 2025 Squirrel
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in
+ * all copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+use crate::{PeakError, PeakRequestBuilder, PeakRequests};
+use reqwest::blocking::Client;
+
+impl PeakRequests {
+    pub fn proxy(mut self, url: &str) -> Self {
+        self.proxy = Some(url.to_string());
+        self
+    }
+
+    // one extra client per distinct override, kept around so overriding the proxy
+    // on every call doesn't mean building a client (and a tls config) every call
+    pub(crate) fn proxy_client(&mut self, proxy: Option<&str>) -> Result<Client, PeakError> {
+        let key = proxy.map(|p| p.to_string());
+        if let Some(client) = self.proxy_clients.get(&key) {
+            return Ok(client.clone());
+        }
+
+        let client = self.client_builder_via(Some(proxy))?.build()?;
+        self.proxy_clients.insert(key, client.clone());
+        Ok(client)
+    }
+}
+
+impl PeakRequestBuilder<'_> {
+    // None goes direct even if the client has a proxy, Some(url) swaps it for this call
+    pub fn proxy(mut self, proxy: Option<&str>) -> Self {
+        self.request.proxy = Some(proxy.map(|p| p.to_string()));
+        self
+    }
+}
@@ -0,0 +1,273 @@
+/*
+ * MIT License
+ *
+ * Copyright (c) 2025 Squirrel
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in
+ * all copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+use crate::content_type::media_type;
+use crate::prefer::{split_pair, split_unquoted};
+use crate::{PeakError, PeakRequestBuilder, Response, StreamingResponse};
+use std::collections::HashMap;
+use std::io::Read;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RangePart {
+    pub start: u64,
+    // inclusive, same as in Content-Range
+    pub end: u64,
+    pub total: Option<u64>,
+    pub content_type: Option<String>,
+    pub bytes: Vec<u8>,
+}
+
+impl PeakRequestBuilder<'_> {
+    // (start, None) asks for everything from start to the end
+    pub fn ranges(mut self, ranges: &[(u64, Option<u64>)]) -> Self {
+        let specs: Vec<String> = ranges
+            .iter()
+            .map(|(start, end)| match end {
+                Some(end) => format!("{}-{}", start, end),
+                None => format!("{}-", start),
+            })
+            .collect();
+        self.request
+            .set_header("Range", format!("bytes={}", specs.join(",")));
+        self
+    }
+}
+
+impl Response {
+    pub fn byte_ranges(&self) -> Result<Vec<RangePart>, PeakError> {
+        parse_byte_ranges(self.status_code, &self.headers, self.bytes_ref())
+    }
+}
+
+impl StreamingResponse {
+    pub fn byte_ranges(mut self) -> Result<Vec<RangePart>, PeakError> {
+        let mut body = Vec::new();
+        self.read_to_end(&mut body)?;
+        parse_byte_ranges(self.status_code, &self.headers, &body)
+    }
+}
+
+// parts come back in the order the server sent them, which doesn't have to be
+// the order they were asked for
+fn parse_byte_ranges(
+    status: u16,
+    headers: &HashMap<String, String>,
+    body: &[u8],
+) -> Result<Vec<RangePart>, PeakError> {
+    if status != 206 {
+        return Err(malformed(format!(
+            "expected 206 partial content for a range request, got {}",
+            status
+        )));
+    }
+
+    let content_type = headers.get("content-type");
+    if content_type.map(|value| media_type(value)).as_deref() != Some("multipart/byteranges") {
+        let content_range = headers
+            .get("content-range")
+            .ok_or_else(|| malformed("206 response without a Content-Range".to_string()))?;
+        return Ok(vec![range_part(
+            content_range,
+            content_type.cloned(),
+            body,
+        )?]);
+    }
+
+    let boundary = content_type
+        .and_then(|value| {
+            split_unquoted(value, ';')
+                .iter()
+                .skip(1)
+                .filter_map(|param| split_pair(param))
+                .find(|(name, _)| name.eq_ignore_ascii_case("boundary"))
+                .and_then(|(_, value)| value)
+        })
+        .ok_or_else(|| malformed("multipart/byteranges without a boundary".to_string()))?;
+
+    let delimiter = format!("--{}", boundary).into_bytes();
+    let mut rest = match find(body, &delimiter) {
+        Some(at) => &body[at + delimiter.len()..],
+        None => return Err(malformed("no multipart boundary in the body".to_string())),
+    };
+
+    let mut parts = Vec::new();
+    loop {
+        if rest.starts_with(b"--") {
+            break;
+        }
+        let head_end = find(rest, b"\r\n\r\n")
+            .ok_or_else(|| malformed("multipart part without a header block".to_string()))?;
+        let part_headers = parse_part_headers(&rest[..head_end]);
+        rest = &rest[head_end + 4..];
+
+        let mut closing = b"\r\n".to_vec();
+        closing.extend_from_slice(&delimiter);
+        let body_end = find(rest, &closing).ok_or_else(|| {
+            malformed("multipart part is missing its closing boundary".to_string())
+        })?;
+
+        let content_range = part_headers
+            .get("content-range")
+            .ok_or_else(|| malformed("multipart part without a Content-Range".to_string()))?;
+        parts.push(range_part(
+            content_range,
+            part_headers.get("content-type").cloned(),
+            &rest[..body_end],
+        )?);
+        rest = &rest[body_end + closing.len()..];
+    }
+    Ok(parts)
+}
+
+fn parse_part_headers(block: &[u8]) -> HashMap<String, String> {
+    String::from_utf8_lossy(block)
+        .split("\r\n")
+        .filter_map(|line| line.split_once(':'))
+        .map(|(name, value)| (name.trim().to_ascii_lowercase(), value.trim().to_string()))
+        .collect()
+}
+
+// "bytes 0-99/1234", the total may be "*"
+fn range_part(
+    content_range: &str,
+    content_type: Option<String>,
+    bytes: &[u8],
+) -> Result<RangePart, PeakError> {
+    let invalid = || malformed(format!("bad Content-Range: {}", content_range));
+    let spec = content_range
+        .trim()
+        .strip_prefix("bytes ")
+        .ok_or_else(invalid)?;
+    let (range, total) = spec.split_once('/').ok_or_else(invalid)?;
+    let (start, end) = range.split_once('-').ok_or_else(invalid)?;
+    let start: u64 = start.trim().parse().map_err(|_| invalid())?;
+    let end: u64 = end.trim().parse().map_err(|_| invalid())?;
+    let total = match total.trim() {
+        "*" => None,
+        total => Some(total.parse().map_err(|_| invalid())?),
+    };
+    if end < start || end - start + 1 != bytes.len() as u64 {
+        return Err(malformed(format!(
+            "Content-Range {} doesn't match the {} bytes in the part",
+            content_range,
+            bytes.len()
+        )));
+    }
+
+    Ok(RangePart {
+        start,
+        end,
+        total,
+        content_type,
+        bytes: bytes.to_vec(),
+    })
+}
+
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack
+        .windows(needle.len())
+        .position(|window| window == needle)
+}
+
+fn malformed(reason: String) -> PeakError {
+    PeakError::MalformedResponse { reason }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn headers(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+        pairs
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn splits_multipart_byteranges() {
+        let body = b"preamble\r\n--THIS\r\nContent-Type: text/plain\r\nContent-Range: bytes 0-4/20\r\n\r\nhello\r\n--THIS\r\ncontent-range: bytes 15-19/*\r\n\r\nworld\r\n--THIS--\r\n";
+        let parts = parse_byte_ranges(
+            206,
+            &headers(&[("content-type", "multipart/byteranges; boundary=\"THIS\"")]),
+            body,
+        )
+        .unwrap();
+
+        assert_eq!(
+            parts,
+            [
+                RangePart {
+                    start: 0,
+                    end: 4,
+                    total: Some(20),
+                    content_type: Some("text/plain".to_string()),
+                    bytes: b"hello".to_vec(),
+                },
+                RangePart {
+                    start: 15,
+                    end: 19,
+                    total: None,
+                    content_type: None,
+                    bytes: b"world".to_vec(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn a_single_range_uses_content_range() {
+        let parts = parse_byte_ranges(
+            206,
+            &headers(&[
+                ("content-type", "text/plain"),
+                ("content-range", "bytes 10-14/100"),
+            ]),
+            b"hello",
+        )
+        .unwrap();
+        assert_eq!(parts.len(), 1);
+        assert_eq!(
+            (parts[0].start, parts[0].end, parts[0].total),
+            (10, 14, Some(100))
+        );
+    }
+
+    #[test]
+    fn rejects_what_does_not_add_up() {
+        let single = |status, content_range: &str, body: &[u8]| {
+            parse_byte_ranges(status, &headers(&[("content-range", content_range)]), body)
+        };
+        assert!(single(200, "bytes 0-4/5", b"hello").is_err());
+        assert!(single(206, "bytes 0-9/20", b"hello").is_err());
+        assert!(single(206, "bytes 4-0/20", b"hello").is_err());
+        assert!(single(206, "items 0-4/20", b"hello").is_err());
+
+        let multipart = headers(&[("content-type", "multipart/byteranges; boundary=B")]);
+        let unclosed = b"--B\r\nContent-Range: bytes 0-4/5\r\n\r\nhello";
+        assert!(parse_byte_ranges(206, &multipart, unclosed).is_err());
+        let no_boundary = headers(&[("content-type", "multipart/byteranges")]);
+        assert!(parse_byte_ranges(206, &no_boundary, b"").is_err());
+    }
+}
@@ -0,0 +1,182 @@
+/*
+ * MIT License
+ *
+ * Copyright (c) 2025 Squirrel
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in
+ * all copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+use crate::normalize::UrlParts;
+use crate::{PeakError, PeakRequests};
+use percent_encoding::percent_decode_str;
+use regex::Regex;
+use reqwest::Url;
+use sha2::{Digest, Sha256};
+use std::sync::Arc;
+
+const REDACTED: &str = "[redacted]";
+
+// decides what of a url or header is safe to print. the crate uses it for urls in
+// errors and for archive records, display_url() and display() use it for yours.
+#[derive(Debug, Clone)]
+pub struct RedactionProfile {
+    query_params: Vec<String>,
+    path_segments: Vec<Regex>,
+    headers: Vec<String>,
+}
+
+// credentials headers are always masked, start from none() to drop them too
+impl Default for RedactionProfile {
+    fn default() -> Self {
+        RedactionProfile {
+            query_params: Vec::new(),
+            path_segments: Vec::new(),
+            headers: [
+                "authorization",
+                "proxy-authorization",
+                "cookie",
+                "set-cookie",
+            ]
+            .map(str::to_string)
+            .to_vec(),
+        }
+    }
+}
+
+impl RedactionProfile {
+    pub fn new() -> Self {
+        RedactionProfile::default()
+    }
+
+    pub fn none() -> Self {
+        RedactionProfile {
+            query_params: Vec::new(),
+            path_segments: Vec::new(),
+            headers: Vec::new(),
+        }
+    }
+
+    pub fn mask_query_param(mut self, name: &str) -> Self {
+        self.query_params.push(name.to_string());
+        self
+    }
+
+    // segments matching the pattern become a short hash, so the same id still
+    // lines up across log lines without being readable
+    pub fn hash_path_segments(mut self, pattern: &str) -> Result<Self, PeakError> {
+        self.path_segments.push(Regex::new(pattern)?);
+        Ok(self)
+    }
+
+    pub fn mask_header(mut self, name: &str) -> Self {
+        self.headers.push(name.to_ascii_lowercase());
+        self
+    }
+
+    pub fn url(&self, url: &str) -> String {
+        let Some(parts) = UrlParts::split(url) else {
+            return url.to_string();
+        };
+
+        let mut out = format!("{}://", parts.scheme);
+        // userinfo is never printed, whatever the profile says. api tokens ride in
+        // the username as often as passwords do after the colon.
+        match parts.authority.rsplit_once('@') {
+            Some((userinfo, host)) if !userinfo.is_empty() => {
+                out.push_str(&format!("{}@{}", REDACTED, host));
+            }
+            _ => out.push_str(parts.authority),
+        }
+
+        let path: Vec<String> = parts
+            .path
+            .split('/')
+            .map(|segment| {
+                let decoded = percent_decode_str(segment).decode_utf8_lossy();
+                if !segment.is_empty() && self.path_segments.iter().any(|re| re.is_match(&decoded))
+                {
+                    format!("{:x}", Sha256::digest(segment.as_bytes()))[..12].to_string()
+                } else {
+                    segment.to_string()
+                }
+            })
+            .collect();
+        out.push_str(&path.join("/"));
+
+        if let Some(query) = parts.query {
+            let pairs: Vec<String> = query
+                .split('&')
+                .map(|pair| {
+                    let name = pair.split_once('=').map_or(pair, |(name, _)| name);
+                    let decoded = percent_decode_str(&name.replace('+', " "))
+                        .decode_utf8_lossy()
+                        .into_owned();
+                    if self.query_params.contains(&decoded) {
+                        format!("{}={}", name, REDACTED)
+                    } else {
+                        pair.to_string()
+                    }
+                })
+                .collect();
+            out.push('?');
+            out.push_str(&pairs.join("&"));
+        }
+        if let Some(fragment) = parts.fragment {
+            out.push('#');
+            out.push_str(fragment);
+        }
+        out
+    }
+
+    // reqwest's Display ends in "for url (...)" with the url as sent. userinfo can't
+    // be masked inside a Url, so it is dropped.
+    pub(crate) fn reqwest_error(&self, mut error: reqwest::Error) -> reqwest::Error {
+        let Some(url) = error.url_mut() else {
+            return error;
+        };
+        let _ = url.set_username("");
+        let _ = url.set_password(None);
+        match Url::parse(&self.url(url.as_str())) {
+            Ok(redacted) => {
+                *url = redacted;
+                error
+            }
+            Err(_) => error.without_url(),
+        }
+    }
+
+    pub fn header<'a>(&self, name: &str, value: &'a str) -> &'a str {
+        if self
+            .headers
+            .iter()
+            .any(|masked| masked.eq_ignore_ascii_case(name))
+        {
+            REDACTED
+        } else {
+            value
+        }
+    }
+}
+
+impl PeakRequests {
+    pub fn redaction_profile(mut self, profile: RedactionProfile) -> Self {
+        self.redaction = Arc::new(profile);
+        self
+    }
+}
@@ -0,0 +1,170 @@
+/*
+ * MIT License
+ *
+ * Copyright (c) 2025 Squirrel
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in
+ * all copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+use crate::cookies::CookieJar;
+use crate::{Callback, PeakRequests, PreparedRequest, Response};
+use reqwest::Url;
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+#[derive(Debug)]
+pub struct RedirectEvent<'a> {
+    pub previous_url: &'a str,
+    pub next_url: &'a str,
+    pub status: u16,
+    pub hop: usize,
+    pub headers: &'a HashMap<String, String>,
+}
+
+// what a 3xx says, for callers following redirects themselves
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RedirectInfo {
+    pub status: u16,
+    // absolute. None when there's no Location or it doesn't parse as a url
+    pub target: Option<String>,
+    pub cross_origin: bool,
+    pub permanent: bool,
+}
+
+impl RedirectInfo {
+    // 303 turns everything but HEAD into a GET, 301/302 only POST (what browsers
+    // do and rfc 9110 allows), 307/308 never
+    pub fn changes_method(&self, method: &str) -> bool {
+        changes_to_get(self.status, method)
+    }
+}
+
+impl Response {
+    // only for the statuses next_hop follows, a 304 or 300 is None
+    pub fn redirect_info(&self) -> Option<RedirectInfo> {
+        if !matches!(self.status_code, 301 | 302 | 303 | 307 | 308) {
+            return None;
+        }
+        let target = self.location().filter(|target| Url::parse(target).is_ok());
+        let cross_origin = match (&target, Url::parse(&self.url)) {
+            (Some(target), Ok(current)) => {
+                Url::parse(target).is_ok_and(|target| target.origin() != current.origin())
+            }
+            _ => false,
+        };
+        Some(RedirectInfo {
+            status: self.status_code,
+            target,
+            cross_origin,
+            permanent: matches!(self.status_code, 301 | 308),
+        })
+    }
+}
+
+pub(crate) type RedirectHook = Callback<dyn Fn(&RedirectEvent) + Send + Sync>;
+
+const SENSITIVE_HEADERS: [&str; 3] = ["authorization", "cookie", "proxy-authorization"];
+
+impl PeakRequests {
+    pub fn on_redirect(mut self, f: impl Fn(&RedirectEvent) + Send + Sync + 'static) -> Self {
+        self.on_redirect = Some(Callback(Arc::new(f)));
+        self
+    }
+}
+
+// works out the request for the next hop, or None when the response isn't a
+// redirect we should follow
+pub(crate) fn next_hop(
+    request: &PreparedRequest,
+    status: u16,
+    location: &str,
+) -> Option<PreparedRequest> {
+    if !matches!(status, 301 | 302 | 303 | 307 | 308) {
+        return None;
+    }
+    let current = Url::parse(&request.url).ok()?;
+    let target = current.join(location).ok()?;
+
+    let mut next = request.clone();
+    next.url = target.to_string();
+
+    if changes_to_get(status, &request.method) {
+        next.method = "GET".to_string();
+        next.clear_body();
+        next.headers.retain(|(key, _)| {
+            !key.eq_ignore_ascii_case("content-type") && !key.eq_ignore_ascii_case("content-length")
+        });
+    }
+
+    if current.origin() != target.origin() {
+        next.headers.retain(|(key, _)| !is_sensitive(key));
+        next.left_origin = true;
+    }
+
+    Some(next)
+}
+
+pub(crate) fn is_sensitive(header: &str) -> bool {
+    SENSITIVE_HEADERS
+        .iter()
+        .any(|sensitive| header.eq_ignore_ascii_case(sensitive))
+}
+
+fn changes_to_get(status: u16, method: &str) -> bool {
+    match status {
+        301 | 302 => method.eq_ignore_ascii_case("POST"),
+        303 => !method.eq_ignore_ascii_case("HEAD"),
+        _ => false,
+    }
+}
+
+// a client without a jar still has to hand back what an earlier hop set, or
+// cookie-gate redirects (set a cookie, bounce back, check it) loop or 403.
+// only the copy that goes out gets the header, the next hop is worked out from
+// the request as the caller built it.
+pub(crate) fn with_chain_cookies<'a>(
+    request: &'a PreparedRequest,
+    chain: &CookieJar,
+) -> Cow<'a, PreparedRequest> {
+    let Ok(url) = Url::parse(&request.url) else {
+        return Cow::Borrowed(request);
+    };
+    let cookies = chain
+        .matching(&url)
+        .iter()
+        .map(|cookie| format!("{}={}", cookie.name, cookie.value))
+        .collect::<Vec<_>>()
+        .join("; ");
+    if cookies.is_empty() {
+        return Cow::Borrowed(request);
+    }
+
+    let mut request = request.clone();
+    let header = match request
+        .headers
+        .iter()
+        .find(|(key, _)| key.eq_ignore_ascii_case("cookie"))
+    {
+        Some((_, existing)) if !existing.is_empty() => format!("{}; {}", existing, cookies),
+        _ => cookies,
+    };
+    request.set_header("Cookie", header);
+    Cow::Owned(request)
+}
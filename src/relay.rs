@@ -0,0 +1,95 @@
+/*
+ * MIT License
+ *
+ * Copyright (c) 2025 Squirrel
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in
+ * all copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+use crate::{
+    classify_send_error, PeakError, PeakRequests, PreparedRequest, Response, StreamingResponse,
+};
+use reqwest::blocking::Body;
+use std::io::{self, Read};
+use std::sync::{Arc, Mutex};
+
+// remembers a read failure on the source so it isn't mistaken for the upload failing
+struct SourceReader {
+    inner: StreamingResponse,
+    failure: Arc<Mutex<Option<io::Error>>>,
+}
+
+impl Read for SourceReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.inner.read(buf).map_err(|e| {
+            let forwarded = io::Error::new(e.kind(), e.to_string());
+            *self.failure.lock().unwrap_or_else(|e| e.into_inner()) = Some(e);
+            forwarded
+        })
+    }
+}
+
+impl PeakRequests {
+    // the body goes straight from one connection to the other, nothing is buffered.
+    // it can't be replayed, so redirects and auth challenges on the upload come back
+    // as they are instead of being retried.
+    pub fn relay(
+        &mut self,
+        from: StreamingResponse,
+        method: &str,
+        to_url: &str,
+    ) -> Result<Response, PeakError> {
+        let mut request = PreparedRequest::new(method, to_url);
+        if let Some(content_type) = from.headers.get("content-type") {
+            request.set_header("Content-Type", content_type.clone());
+        }
+        let request = self.outgoing(&request).into_owned();
+
+        if self.client.is_none() {
+            self.init_client()?;
+        }
+        let client = self.client.clone().unwrap();
+
+        let source_url = from.url.clone();
+        let remaining = from
+            .content_length()
+            .map(|length| length.saturating_sub(from.bytes_read()));
+        let failure = Arc::new(Mutex::new(None));
+        let reader = SourceReader {
+            inner: from,
+            failure: Arc::clone(&failure),
+        };
+        let body = match remaining {
+            Some(length) => Body::sized(reader, length),
+            None => Body::new(reader),
+        };
+
+        let settings = self.response_settings();
+        let started = settings.now();
+        let result = self.request_builder(&client, &request)?.body(body).send();
+        if let Some(source) = failure.lock().unwrap_or_else(|e| e.into_inner()).take() {
+            return Err(PeakError::RelaySource {
+                url: self.redaction.url(&source_url),
+                source,
+            });
+        }
+        let response = result.map_err(|e| classify_send_error(e, &self.redaction))?;
+        Response::from_reqwest(response, &settings, started)
+    }
+}
@@ -0,0 +1,131 @@
+/*
+ * MIT License
+ *
+ * Copyright (c) 2025 Squirrel
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in
+ * all copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+use crate::{PeakError, PeakRequests, Response};
+use reqwest::blocking::RequestBuilder;
+use std::time::{Duration, SystemTime};
+
+// header names sent on retries, for servers that want to spot and shed them
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RetryHeaders {
+    pub attempt: String,
+    pub reason: String,
+    pub first_attempt_at: String,
+}
+
+impl Default for RetryHeaders {
+    fn default() -> Self {
+        RetryHeaders {
+            attempt: "X-Retry-Attempt".to_string(),
+            reason: "X-Retry-Reason".to_string(),
+            first_attempt_at: "X-Retry-First-Attempt".to_string(),
+        }
+    }
+}
+
+// one try of a request, oldest first on Response::attempts and PeakError::attempts
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AttemptRecord {
+    // starts at 1
+    pub attempt: usize,
+    pub outcome: AttemptOutcome,
+    pub elapsed: Duration,
+    // slept after this attempt before the next one, zero on the last
+    pub backoff: Duration,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AttemptOutcome {
+    Status(u16),
+    // the same kinds as the retry reason header: timeout, connect, transport
+    Error(String),
+}
+
+impl AttemptOutcome {
+    pub(crate) fn of(result: &Result<Response, PeakError>) -> Self {
+        match result {
+            Ok(response) => AttemptOutcome::Status(response.status_code),
+            Err(PeakError::Expectation { got_status, .. }) => AttemptOutcome::Status(*got_status),
+            Err(PeakError::Status { status, .. }) => AttemptOutcome::Status(*status),
+            Err(error) => AttemptOutcome::Error(retry_reason(error)),
+        }
+    }
+}
+
+impl Response {
+    // a single entry unless the request went through a retry loop
+    pub fn attempts(&self) -> &[AttemptRecord] {
+        &self.attempts
+    }
+}
+
+// what a retry needs to say about itself, the first attempt carries none of it
+#[derive(Debug, Clone)]
+pub(crate) struct RetryInfo {
+    pub(crate) attempt: usize,
+    pub(crate) reason: String,
+    pub(crate) first_attempt_at: SystemTime,
+}
+
+impl RetryHeaders {
+    pub(crate) fn apply(
+        &self,
+        request_builder: RequestBuilder,
+        retry: Option<&RetryInfo>,
+    ) -> RequestBuilder {
+        let Some(retry) = retry else {
+            return request_builder;
+        };
+        request_builder
+            .header(&self.attempt, retry.attempt.to_string())
+            .header(&self.reason, &retry.reason)
+            .header(
+                &self.first_attempt_at,
+                httpdate::fmt_http_date(retry.first_attempt_at),
+            )
+    }
+}
+
+impl PeakRequests {
+    pub fn emit_retry_headers(mut self, emit: bool) -> Self {
+        self.retry_headers = emit.then(|| self.retry_headers.take().unwrap_or_default());
+        self
+    }
+
+    // turns emitting on as well
+    pub fn retry_header_names(mut self, names: RetryHeaders) -> Self {
+        self.retry_headers = Some(names);
+        self
+    }
+}
+
+// timeout, connect or status-503 and so on
+pub(crate) fn retry_reason(error: &PeakError) -> String {
+    match error {
+        PeakError::Status { status, .. } => format!("status-{}", status),
+        PeakError::Http(e) if e.is_timeout() => "timeout".to_string(),
+        PeakError::Http(e) if e.is_connect() => "connect".to_string(),
+        _ => "transport".to_string(),
+    }
+}
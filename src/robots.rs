@@ -0,0 +1,272 @@
+/*
+ * MIT License
+ *
+ * Copyright (c) 2025 Squirrel
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in
+ * all copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+use crate::{PeakError, PeakRequests, PreparedRequest};
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+const DEFAULT_TTL: Duration = Duration::from_secs(24 * 60 * 60);
+
+#[derive(Debug)]
+pub(crate) struct RobotsPolicy {
+    user_agent: String,
+    ttl: Duration,
+    origins: HashMap<String, CachedRobots>,
+}
+
+#[derive(Debug)]
+struct CachedRobots {
+    fetched_at: Instant,
+    rules: RobotsRules,
+    last_request: Option<Instant>,
+}
+
+#[derive(Debug, Default)]
+struct RobotsRules {
+    rules: Vec<Rule>,
+    crawl_delay: Option<Duration>,
+}
+
+#[derive(Debug, Clone)]
+struct Rule {
+    allow: bool,
+    pattern: String,
+}
+
+impl PeakRequests {
+    pub fn respect_robots_txt(mut self, user_agent_token: &str) -> Self {
+        self.robots = Some(RobotsPolicy {
+            user_agent: user_agent_token.to_string(),
+            ttl: DEFAULT_TTL,
+            origins: HashMap::new(),
+        });
+        self
+    }
+
+    pub fn robots_txt_ttl(mut self, ttl: Duration) -> Self {
+        if let Some(robots) = &mut self.robots {
+            robots.ttl = ttl;
+        }
+        self
+    }
+
+    pub(crate) fn check_robots(&mut self, request: &PreparedRequest) -> Result<(), PeakError> {
+        let Some(policy) = &self.robots else {
+            return Ok(());
+        };
+        let Some(url) = self.parse_normalized(&request.url) else {
+            return Ok(());
+        };
+        if url.path() == "/robots.txt" {
+            return Ok(());
+        }
+
+        let origin = url.origin().ascii_serialization();
+        let stale = policy.origins.get(&origin).is_none_or(|cached| {
+            self.clock()
+                .now()
+                .saturating_duration_since(cached.fetched_at)
+                >= policy.ttl
+        });
+
+        if stale {
+            let user_agent = policy.user_agent.clone();
+            let robots_url = format!("{}/robots.txt", origin);
+            let rules = match self.fetch(&PreparedRequest::new("GET", &robots_url)) {
+                Ok(response) if (200..300).contains(&response.status_code) => {
+                    RobotsRules::parse(&response.text(), &user_agent)
+                }
+                _ => RobotsRules::default(),
+            };
+            let fetched_at = self.clock().now();
+            let policy = self.robots.as_mut().unwrap();
+            let last_request = policy
+                .origins
+                .get(&origin)
+                .and_then(|cached| cached.last_request);
+            policy.origins.insert(
+                origin.clone(),
+                CachedRobots {
+                    fetched_at,
+                    rules,
+                    last_request,
+                },
+            );
+        }
+
+        let now = self.clock().now();
+        let cached = self
+            .robots
+            .as_mut()
+            .unwrap()
+            .origins
+            .get_mut(&origin)
+            .unwrap();
+
+        let path = match url.query() {
+            Some(query) => format!("{}?{}", url.path(), query),
+            None => url.path().to_string(),
+        };
+        if let Some(rule) = cached.rules.matching_rule(&path) {
+            if !rule.allow {
+                return Err(PeakError::DisallowedByRobots {
+                    rule: format!("Disallow: {}", rule.pattern),
+                });
+            }
+        }
+
+        let wait = match (cached.rules.crawl_delay, cached.last_request) {
+            (Some(delay), Some(last)) => delay.saturating_sub(now.saturating_duration_since(last)),
+            _ => Duration::ZERO,
+        };
+        cached.last_request = Some(now + wait);
+        if !wait.is_zero() {
+            self.clock().sleep(wait);
+        }
+
+        Ok(())
+    }
+}
+
+impl RobotsRules {
+    fn parse(body: &str, user_agent: &str) -> Self {
+        let ours = product_token(user_agent);
+        let mut specific = RobotsRules::default();
+        let mut wildcard = RobotsRules::default();
+        let mut matched_specific = false;
+
+        let mut group_agents: Vec<String> = Vec::new();
+        let mut in_rules = false;
+
+        for line in body.lines() {
+            let line = line.split('#').next().unwrap_or("").trim();
+            let Some((key, value)) = line.split_once(':') else {
+                continue;
+            };
+            let key = key.trim().to_ascii_lowercase();
+            let value = value.trim();
+
+            if key == "user-agent" {
+                if in_rules {
+                    group_agents.clear();
+                    in_rules = false;
+                }
+                group_agents.push(value.to_ascii_lowercase());
+                continue;
+            }
+
+            if group_agents.is_empty() {
+                continue;
+            }
+            in_rules = true;
+
+            let for_us = !ours.is_empty()
+                && group_agents
+                    .iter()
+                    .any(|agent| product_token(agent).eq_ignore_ascii_case(ours));
+            let for_everyone = group_agents.iter().any(|agent| agent == "*");
+            if for_us {
+                matched_specific = true;
+            }
+
+            let targets: Vec<&mut RobotsRules> = match (for_us, for_everyone) {
+                (true, true) => vec![&mut specific, &mut wildcard],
+                (true, false) => vec![&mut specific],
+                (false, true) => vec![&mut wildcard],
+                (false, false) => continue,
+            };
+
+            for target in targets {
+                match key.as_str() {
+                    "allow" | "disallow" if !value.is_empty() => target.rules.push(Rule {
+                        allow: key == "allow",
+                        pattern: value.to_string(),
+                    }),
+                    "crawl-delay" => {
+                        if let Ok(seconds) = value.parse::<f64>() {
+                            if seconds.is_finite() && seconds >= 0.0 {
+                                target.crawl_delay = Some(Duration::from_secs_f64(seconds));
+                            }
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        if matched_specific {
+            specific
+        } else {
+            wildcard
+        }
+    }
+
+    // longest matching pattern wins, and allow wins a tie
+    fn matching_rule(&self, path: &str) -> Option<&Rule> {
+        self.rules
+            .iter()
+            .filter(|rule| pattern_matches(&rule.pattern, path))
+            .max_by(|a, b| {
+                a.pattern
+                    .len()
+                    .cmp(&b.pattern.len())
+                    .then(a.allow.cmp(&b.allow))
+            })
+    }
+}
+
+// groups name crawlers by product token, so "ExampleBot/2.1 (+https://example.com/bot)"
+// is matched as examplebot, whichever side the version is on
+fn product_token(agent: &str) -> &str {
+    agent
+        .split(|c: char| c == '/' || c.is_whitespace())
+        .next()
+        .unwrap_or_default()
+}
+
+fn pattern_matches(pattern: &str, path: &str) -> bool {
+    let (pattern, anchored) = match pattern.strip_suffix('$') {
+        Some(pattern) => (pattern, true),
+        None => (pattern, false),
+    };
+
+    let pieces: Vec<&str> = pattern.split('*').collect();
+    let mut rest = match path.strip_prefix(pieces[0]) {
+        Some(rest) => rest,
+        None => return false,
+    };
+
+    for (i, piece) in pieces.iter().enumerate().skip(1) {
+        let last = i == pieces.len() - 1;
+        if last && anchored {
+            return rest.ends_with(piece);
+        }
+        match rest.find(piece) {
+            Some(at) => rest = &rest[at + piece.len()..],
+            None => return false,
+        }
+    }
+
+    !anchored || rest.is_empty()
+}
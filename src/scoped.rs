@@ -0,0 +1,196 @@
+/*
+ * MIT License
+ *
+ * Copyright (c) 2025 Squirrel
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in
+ * all copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+use crate::{PeakError, PeakRequestBuilder, PeakRequests, PreparedRequest, Response};
+#[cfg(feature = "json")]
+use serde_json::Value;
+use std::collections::HashMap;
+use std::time::Duration;
+
+// settings layered over the client for a stretch of calls. anything a single
+// request sets for itself still wins over these.
+#[derive(Debug, Clone, Default)]
+pub struct Overrides {
+    timeout: Option<Duration>,
+    headers: Vec<(String, String)>,
+    allow_redirects: Option<bool>,
+    max_redirects: Option<usize>,
+    proxy: Option<Option<String>>,
+}
+
+impl Overrides {
+    pub fn new() -> Self {
+        Overrides::default()
+    }
+
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    pub fn header(mut self, key: &str, value: &str) -> Self {
+        self.headers.push((key.to_string(), value.to_string()));
+        self
+    }
+
+    pub fn allow_redirects(mut self, allow: bool) -> Self {
+        self.allow_redirects = Some(allow);
+        self
+    }
+
+    pub fn max_redirects(mut self, max: usize) -> Self {
+        self.max_redirects = Some(max);
+        self
+    }
+
+    // None sends these calls direct, same as PeakRequestBuilder::proxy
+    pub fn proxy(mut self, proxy: Option<&str>) -> Self {
+        self.proxy = Some(proxy.map(|p| p.to_string()));
+        self
+    }
+
+    pub(crate) fn apply(&self, request: &mut PreparedRequest) {
+        let scoped: Vec<(String, String)> = self
+            .headers
+            .iter()
+            .filter(|(key, _)| {
+                !request
+                    .headers
+                    .iter()
+                    .any(|(existing, _)| existing.eq_ignore_ascii_case(key))
+            })
+            .cloned()
+            .collect();
+        request.headers.splice(0..0, scoped);
+        request.timeout = request.timeout.or(self.timeout);
+        request.allow_redirects = request.allow_redirects.or(self.allow_redirects);
+        request.max_redirects = request.max_redirects.or(self.max_redirects);
+        if request.proxy.is_none() {
+            request.proxy = self.proxy.clone();
+        }
+    }
+}
+
+// borrows the client, so the connection pool (and cookies, auth, ...) are the
+// same ones the base client uses. dropping it just ends the scope.
+pub struct ScopedClient<'a> {
+    client: &'a mut PeakRequests,
+    overrides: Overrides,
+}
+
+impl PeakRequests {
+    pub fn scoped(&mut self, overrides: Overrides) -> ScopedClient<'_> {
+        ScopedClient {
+            client: self,
+            overrides,
+        }
+    }
+}
+
+impl ScopedClient<'_> {
+    pub fn request(&mut self, method: &str, url: &str) -> PeakRequestBuilder<'_> {
+        let mut builder = self.client.request(method, url);
+        builder.overrides = Some(self.overrides.clone());
+        builder
+    }
+
+    pub fn get(&mut self, url: &str) -> Result<Response, PeakError> {
+        self.request("GET", url).send()
+    }
+
+    #[cfg(feature = "json")]
+    pub fn post(
+        &mut self,
+        url: &str,
+        data: Option<HashMap<&str, &str>>,
+        json: Option<Value>,
+    ) -> Result<Response, PeakError> {
+        self.with_json("POST", url, data, json)
+    }
+
+    #[cfg(not(feature = "json"))]
+    pub fn post(
+        &mut self,
+        url: &str,
+        data: Option<HashMap<&str, &str>>,
+    ) -> Result<Response, PeakError> {
+        self.with_form("POST", url, data).send()
+    }
+
+    #[cfg(feature = "json")]
+    pub fn put(
+        &mut self,
+        url: &str,
+        data: Option<HashMap<&str, &str>>,
+        json: Option<Value>,
+    ) -> Result<Response, PeakError> {
+        self.with_json("PUT", url, data, json)
+    }
+
+    #[cfg(not(feature = "json"))]
+    pub fn put(
+        &mut self,
+        url: &str,
+        data: Option<HashMap<&str, &str>>,
+    ) -> Result<Response, PeakError> {
+        self.with_form("PUT", url, data).send()
+    }
+
+    pub fn delete(&mut self, url: &str) -> Result<Response, PeakError> {
+        self.request("DELETE", url).send()
+    }
+
+    pub fn head(&mut self, url: &str) -> Result<Response, PeakError> {
+        self.request("HEAD", url).send()
+    }
+
+    fn with_form(
+        &mut self,
+        method: &str,
+        url: &str,
+        data: Option<HashMap<&str, &str>>,
+    ) -> PeakRequestBuilder<'_> {
+        let builder = self.request(method, url);
+        match data {
+            Some(data) => builder.form(data),
+            None => builder,
+        }
+    }
+
+    #[cfg(feature = "json")]
+    fn with_json(
+        &mut self,
+        method: &str,
+        url: &str,
+        data: Option<HashMap<&str, &str>>,
+        json: Option<Value>,
+    ) -> Result<Response, PeakError> {
+        let builder = self.with_form(method, url, data);
+        match json {
+            Some(json) => builder.json(json),
+            None => builder,
+        }
+        .send()
+    }
+}
@@ -0,0 +1,80 @@
+/*
+ * MIT License
+ *
+ * Copyright (c) 2025 Squirrel
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in
+ * all copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+#[cfg(feature = "scrape")]
+use crate::{PeakError, Response};
+use regex::Regex;
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+// shared between a client and every response it hands out, so extract() in a
+// loop compiles each pattern once
+#[derive(Debug, Default)]
+#[cfg_attr(not(feature = "scrape"), allow(dead_code))]
+pub(crate) struct PatternCache {
+    compiled: Mutex<HashMap<String, Regex>>,
+}
+
+#[cfg(feature = "scrape")]
+impl PatternCache {
+    fn get(&self, pattern: &str) -> Result<Regex, PeakError> {
+        let mut compiled = self.compiled.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(regex) = compiled.get(pattern) {
+            return Ok(regex.clone());
+        }
+        let regex = Regex::new(pattern)?;
+        compiled.insert(pattern.to_string(), regex.clone());
+        Ok(regex)
+    }
+}
+
+#[cfg(feature = "scrape")]
+impl Response {
+    pub fn contains(&self, needle: &str) -> bool {
+        self.text().contains(needle)
+    }
+
+    // the first capture group if the pattern has one, the whole match if not
+    pub fn extract(&self, pattern: &str) -> Result<Option<String>, PeakError> {
+        let regex = self.patterns.get(pattern)?;
+        Ok(regex
+            .captures(&self.text())
+            .and_then(|captures| wanted(&captures))
+            .map(str::to_string))
+    }
+
+    pub fn extract_all(&self, pattern: &str) -> Result<Vec<String>, PeakError> {
+        let regex = self.patterns.get(pattern)?;
+        Ok(regex
+            .captures_iter(&self.text())
+            .filter_map(|captures| wanted(&captures).map(str::to_string))
+            .collect())
+    }
+}
+
+#[cfg(feature = "scrape")]
+fn wanted<'a>(captures: &regex::Captures<'a>) -> Option<&'a str> {
+    let group = if captures.len() > 1 { 1 } else { 0 };
+    captures.get(group).map(|found| found.as_str())
+}
@@ -0,0 +1,93 @@
+/*
+ * MIT License
+ *
+ * Copyright (c) 2025 Squirrel
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in
+ * all copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+use crate::background::InFlight;
+use crate::{PeakError, PeakRequests};
+use std::time::{Duration, Instant};
+
+#[derive(Debug, Default)]
+pub struct ShutdownReport {
+    // background requests, mirrored requests and archive records that finished
+    // inside the grace period
+    pub completed: usize,
+    // still running or queued when it ran out. queued background requests end
+    // with ClientClosed and queued replays are skipped. archive records are
+    // still written, but nothing waits for them.
+    pub abandoned: usize,
+    // saving the cookie jar or syncing the journal, neither stops the shutdown
+    pub errors: Vec<PeakError>,
+}
+
+impl PeakRequests {
+    // waits for work off the caller's thread, saves what load_cookies read back to
+    // the same file, syncs the journal, then drops every connection. group workers
+    // still winding down are waited for too, but not counted: their group already
+    // reported them as Cancelled.
+    pub fn shutdown(mut self, grace: Duration) -> ShutdownReport {
+        let deadline = Instant::now() + grace;
+        let mut report = ShutdownReport::default();
+        self.keepalive = None;
+
+        let mut queues: Vec<&InFlight> = Vec::new();
+        if let Some(pool) = self.background.get() {
+            queues.push(pool.in_flight());
+        }
+        if let Some(mirror) = &self.mirror {
+            queues.push(mirror.in_flight());
+        }
+        #[cfg(feature = "json")]
+        if let Some(archive) = &self.archive {
+            queues.push(archive.in_flight());
+        }
+        let pending: usize = queues.iter().map(|in_flight| in_flight.count()).sum();
+        let left: usize = queues
+            .iter()
+            .map(|in_flight| in_flight.wait_until(deadline))
+            .sum();
+        report.completed = pending.saturating_sub(left);
+        report.abandoned = left;
+        self.groups.wait_until(deadline);
+
+        if let Some(mirror) = &self.mirror {
+            mirror.close();
+        }
+        if let Some(pool) = self.background.get_mut() {
+            pool.abandon_on_drop();
+        }
+
+        #[cfg(feature = "json")]
+        if let Some(path) = self.cookie_file.clone() {
+            if let Err(e) = self.save_cookies(path) {
+                report.errors.push(e);
+            }
+        }
+        #[cfg(feature = "json")]
+        if let Some(journal) = self.journal.as_mut() {
+            if let Err(e) = journal.sync() {
+                report.errors.push(e);
+            }
+        }
+        report
+    }
+}
@@ -0,0 +1,209 @@
+/*
+ * MIT License
+ *
+ * Copyright (c) 2025 Squirrel
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in
+ * all copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+use crate::{unique_token, PeakError, StreamingResponse};
+use sha2::{Digest, Sha256};
+use std::fs::{self, File};
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+pub trait BodySink {
+    fn name(&self) -> &str;
+    fn on_chunk(&mut self, chunk: &[u8]) -> Result<(), PeakError>;
+    fn on_complete(&mut self) -> Result<(), PeakError> {
+        Ok(())
+    }
+}
+
+impl StreamingResponse {
+    // one read loop feeding every sink, so the body is only pulled off the wire once.
+    // the first sink to fail stops the transfer and the rest never see on_complete.
+    pub fn pipe(mut self, mut sinks: Vec<Box<dyn BodySink>>) -> Result<u64, PeakError> {
+        let mut buf = vec![0u8; 64 * 1024];
+        let mut total = 0u64;
+        loop {
+            let n = self.read(&mut buf)?;
+            if n == 0 {
+                break;
+            }
+            total += n as u64;
+            for (index, sink) in sinks.iter_mut().enumerate() {
+                sink.on_chunk(&buf[..n])
+                    .map_err(|e| sink_error(index, sink.as_ref(), e))?;
+            }
+        }
+
+        for (index, sink) in sinks.iter_mut().enumerate() {
+            sink.on_complete()
+                .map_err(|e| sink_error(index, sink.as_ref(), e))?;
+        }
+        Ok(total)
+    }
+}
+
+fn sink_error(index: usize, sink: &dyn BodySink, source: PeakError) -> PeakError {
+    PeakError::Sink {
+        index,
+        name: sink.name().to_string(),
+        source: Box::new(source),
+    }
+}
+
+// writes to a temp file next to the target and renames it into place on
+// completion, so a failed transfer never leaves a half written file behind
+#[derive(Debug)]
+pub struct FileSink {
+    path: PathBuf,
+    temp_path: PathBuf,
+    file: Option<File>,
+}
+
+impl FileSink {
+    pub fn new(path: impl AsRef<Path>) -> Result<Self, PeakError> {
+        let path = path.as_ref().to_path_buf();
+        let mut temp_name = path.file_name().unwrap_or_default().to_os_string();
+        temp_name.push(format!(".{}.part", unique_token()));
+        let temp_path = path.with_file_name(temp_name);
+        let file = File::create(&temp_path)?;
+        Ok(FileSink {
+            path,
+            temp_path,
+            file: Some(file),
+        })
+    }
+}
+
+impl BodySink for FileSink {
+    fn name(&self) -> &str {
+        "file"
+    }
+
+    fn on_chunk(&mut self, chunk: &[u8]) -> Result<(), PeakError> {
+        match self.file.as_mut() {
+            Some(file) => Ok(file.write_all(chunk)?),
+            None => Ok(()),
+        }
+    }
+
+    fn on_complete(&mut self) -> Result<(), PeakError> {
+        if let Some(file) = self.file.take() {
+            file.sync_all()?;
+            drop(file);
+            fs::rename(&self.temp_path, &self.path)?;
+        }
+        Ok(())
+    }
+}
+
+impl Drop for FileSink {
+    fn drop(&mut self) {
+        if self.file.take().is_some() {
+            let _ = fs::remove_file(&self.temp_path);
+        }
+    }
+}
+
+// clones share state, keep one around to read the digest after pipe() is done
+#[derive(Debug, Clone, Default)]
+pub struct HashSink {
+    hasher: Arc<Mutex<Sha256>>,
+}
+
+impl HashSink {
+    pub fn new() -> Self {
+        HashSink::default()
+    }
+
+    pub fn sha256_hex(&self) -> String {
+        let hasher = self
+            .hasher
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone();
+        hasher
+            .finalize()
+            .iter()
+            .map(|byte| format!("{:02x}", byte))
+            .collect()
+    }
+}
+
+impl BodySink for HashSink {
+    fn name(&self) -> &str {
+        "sha256"
+    }
+
+    fn on_chunk(&mut self, chunk: &[u8]) -> Result<(), PeakError> {
+        self.hasher
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .update(chunk);
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct CountingSink {
+    count: Arc<AtomicU64>,
+}
+
+impl CountingSink {
+    pub fn new() -> Self {
+        CountingSink::default()
+    }
+
+    pub fn count(&self) -> u64 {
+        self.count.load(Ordering::SeqCst)
+    }
+}
+
+impl BodySink for CountingSink {
+    fn name(&self) -> &str {
+        "counter"
+    }
+
+    fn on_chunk(&mut self, chunk: &[u8]) -> Result<(), PeakError> {
+        self.count.fetch_add(chunk.len() as u64, Ordering::SeqCst);
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+pub struct WriteSink<W: Write>(pub W);
+
+impl<W: Write> BodySink for WriteSink<W> {
+    fn name(&self) -> &str {
+        "writer"
+    }
+
+    fn on_chunk(&mut self, chunk: &[u8]) -> Result<(), PeakError> {
+        Ok(self.0.write_all(chunk)?)
+    }
+
+    fn on_complete(&mut self) -> Result<(), PeakError> {
+        Ok(self.0.flush()?)
+    }
+}
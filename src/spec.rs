@@ -0,0 +1,163 @@
+/*
+ * MIT License
+ *
+ * Copyright (c) 2025 Squirrel
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in
+ * all copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+use crate::{PeakError, PeakRequests, PreparedRequest, Response};
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "json")]
+use serde_json::Value;
+use std::time::Duration;
+
+// a request as plain data, for queues and anything else that describes work now
+// and sends it later. build it by hand or take one off a PreparedRequest.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RequestSpec {
+    pub method: String,
+    pub url: String,
+    #[serde(default)]
+    pub headers: Vec<(String, String)>,
+    #[serde(default)]
+    pub body: SpecBody,
+    #[serde(default)]
+    pub options: SpecOptions,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SpecBody {
+    #[default]
+    Empty,
+    Form(Vec<(String, String)>),
+    #[cfg(feature = "json")]
+    Json(Value),
+    // base64 on the wire, a json array of numbers is four times the size
+    Bytes(#[serde(with = "base64_bytes")] Vec<u8>),
+}
+
+// None everywhere means the client's own setting
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SpecOptions {
+    pub timeout: Option<Duration>,
+    pub allow_redirects: Option<bool>,
+    pub max_redirects: Option<usize>,
+    pub proxy: Option<SpecProxy>,
+    pub connection_close: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SpecProxy {
+    Via(String),
+    Direct,
+}
+
+impl RequestSpec {
+    pub fn new(method: &str, url: &str) -> Self {
+        RequestSpec {
+            method: method.to_ascii_uppercase(),
+            url: url.to_string(),
+            headers: Vec::new(),
+            body: SpecBody::Empty,
+            options: SpecOptions::default(),
+        }
+    }
+
+    // a request can carry more than one body, this keeps the one that would be
+    // sent (json over form over raw bytes, same as request_builder)
+    pub fn from_prepared(request: &PreparedRequest) -> Self {
+        let body = match (&request.form, &request.body) {
+            (Some(form), _) => SpecBody::Form(form.clone()),
+            (None, Some(bytes)) => SpecBody::Bytes(bytes.clone()),
+            (None, None) => SpecBody::Empty,
+        };
+        #[cfg(feature = "json")]
+        let body = match &request.json {
+            Some(json) => SpecBody::Json(json.clone()),
+            None => body,
+        };
+        RequestSpec {
+            method: request.method.clone(),
+            url: request.url.clone(),
+            headers: request.headers.clone(),
+            body,
+            options: SpecOptions {
+                timeout: request.timeout,
+                allow_redirects: request.allow_redirects,
+                max_redirects: request.max_redirects,
+                proxy: request.proxy.clone().map(|proxy| match proxy {
+                    Some(url) => SpecProxy::Via(url),
+                    None => SpecProxy::Direct,
+                }),
+                connection_close: request.connection_close,
+            },
+        }
+    }
+
+    pub(crate) fn to_prepared(&self) -> PreparedRequest {
+        let mut request = PreparedRequest::new(&self.method.to_ascii_uppercase(), &self.url);
+        request.headers = self.headers.clone();
+        match &self.body {
+            SpecBody::Empty => {}
+            SpecBody::Form(form) => request.form = Some(form.clone()),
+            #[cfg(feature = "json")]
+            SpecBody::Json(json) => request.json = Some(json.clone()),
+            SpecBody::Bytes(bytes) => request.body = Some(bytes.clone()),
+        }
+        let options = &self.options;
+        request.timeout = options.timeout;
+        request.allow_redirects = options.allow_redirects;
+        request.max_redirects = options.max_redirects;
+        request.proxy = options.proxy.clone().map(|proxy| match proxy {
+            SpecProxy::Via(url) => Some(url),
+            SpecProxy::Direct => None,
+        });
+        request.connection_close = options.connection_close;
+        request
+    }
+}
+
+impl PeakRequests {
+    pub fn execute(&mut self, spec: &RequestSpec) -> Result<Response, PeakError> {
+        let request = spec.to_prepared().with_redaction(&self.redaction);
+        self.execute_prepared(&request)
+    }
+}
+
+mod base64_bytes {
+    use base64::engine::general_purpose::STANDARD;
+    use base64::Engine;
+    use serde::de::Error;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub(super) fn serialize<S: Serializer>(bytes: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&STANDARD.encode(bytes))
+    }
+
+    pub(super) fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Vec<u8>, D::Error> {
+        let encoded = String::deserialize(deserializer)?;
+        STANDARD.decode(encoded).map_err(D::Error::custom)
+    }
+}
@@ -0,0 +1,205 @@
+/*
+ * MIT License
+ *
+ * Copyright (c) 2025 Squirrel
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in
+ * all copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+use crate::{unique_token, PeakError, PeakRequests, Response};
+use bytes::Bytes;
+use std::fs::{self, File};
+use std::io::{self, Cursor, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, OnceLock};
+
+const CHUNK_SIZE: usize = 64 * 1024;
+
+#[derive(Debug, Clone)]
+pub(crate) struct SpillPolicy {
+    threshold: u64,
+    dir: PathBuf,
+}
+
+// the temp file behind a spilled body, removed once the last Response holding it
+// (clones included) is dropped
+#[derive(Debug)]
+pub(crate) struct SpillFile {
+    path: PathBuf,
+    len: u64,
+}
+
+impl Drop for SpillFile {
+    fn drop(&mut self) {
+        if let Err(e) = fs::remove_file(&self.path) {
+            log::warn!("could not remove {}: {}", self.path.display(), e);
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub(crate) enum Body {
+    Memory(Bytes),
+    // loaded is only filled by bytes_ref(), which has to hand out a reference
+    Spilled {
+        file: Arc<SpillFile>,
+        loaded: Arc<OnceLock<Bytes>>,
+    },
+}
+
+impl PeakRequests {
+    // bodies over bytes go to a temp file in dir (the system temp dir for None)
+    // instead of memory. bytes() and text() read them back from disk, so they only
+    // take up memory while the caller holds on to what they returned.
+    pub fn spill_to_disk_over(mut self, bytes: u64, dir: Option<PathBuf>) -> Self {
+        self.spill = Some(SpillPolicy {
+            threshold: bytes,
+            dir: dir.unwrap_or_else(std::env::temp_dir),
+        });
+        self
+    }
+}
+
+impl SpillPolicy {
+    // buffers until the threshold is passed, then moves what it has to disk and
+    // streams the rest after it. a Content-Length already over it skips the buffer.
+    pub(crate) fn read(
+        &self,
+        mut response: impl Read,
+        length: Option<u64>,
+    ) -> Result<Body, PeakError> {
+        let mut buffer = Vec::new();
+        let mut chunk = vec![0; CHUNK_SIZE];
+        if length.is_none_or(|length| length <= self.threshold) {
+            while buffer.len() as u64 <= self.threshold {
+                let n = response.read(&mut chunk)?;
+                if n == 0 {
+                    return Ok(Body::Memory(Bytes::from(buffer)));
+                }
+                buffer.extend_from_slice(&chunk[..n]);
+            }
+        }
+
+        let path = self
+            .dir
+            .join(format!("peakrequests-{}.body", unique_token()));
+        let mut out = File::create(&path)?;
+        // from here on the guard owns the file, so a failed read still cleans it up
+        let mut file = SpillFile { path, len: 0 };
+        out.write_all(&buffer)?;
+        file.len = buffer.len() as u64;
+        drop(buffer);
+        loop {
+            let n = response.read(&mut chunk)?;
+            if n == 0 {
+                break;
+            }
+            out.write_all(&chunk[..n])?;
+            file.len += n as u64;
+        }
+        out.flush()?;
+        Ok(Body::Spilled {
+            file: Arc::new(file),
+            loaded: Arc::default(),
+        })
+    }
+}
+
+impl Body {
+    #[cfg_attr(not(feature = "json"), allow(dead_code))]
+    pub(crate) fn len(&self) -> u64 {
+        match self {
+            Body::Memory(bytes) => bytes.len() as u64,
+            Body::Spilled { file, .. } => file.len,
+        }
+    }
+
+    // None for a spilled body nothing has pulled back into memory
+    pub(crate) fn in_memory(&self) -> Option<&Bytes> {
+        match self {
+            Body::Memory(bytes) => Some(bytes),
+            Body::Spilled { loaded, .. } => loaded.get(),
+        }
+    }
+
+    pub(crate) fn reader(&self) -> io::Result<Box<dyn Read + Send>> {
+        match self {
+            Body::Spilled { file, loaded } if loaded.get().is_none() => {
+                Ok(Box::new(File::open(&file.path)?))
+            }
+            _ => Ok(Box::new(Cursor::new(self.bytes()))),
+        }
+    }
+
+    // at most limit bytes from the start, without reading the rest of a spilled body
+    #[cfg_attr(not(feature = "json"), allow(dead_code))]
+    pub(crate) fn prefix(&self, limit: u64) -> io::Result<Bytes> {
+        if let Some(bytes) = self.in_memory() {
+            return Ok(bytes.slice(..bytes.len().min(limit as usize)));
+        }
+        let mut prefix = Vec::new();
+        self.reader()?.take(limit).read_to_end(&mut prefix)?;
+        Ok(Bytes::from(prefix))
+    }
+
+    pub(crate) fn bytes(&self) -> Bytes {
+        match self {
+            Body::Memory(bytes) => bytes.clone(),
+            Body::Spilled { file, loaded } => match loaded.get() {
+                Some(bytes) => bytes.clone(),
+                None => read_back(&file.path),
+            },
+        }
+    }
+
+    pub(crate) fn as_bytes(&self) -> &Bytes {
+        match self {
+            Body::Memory(bytes) => bytes,
+            Body::Spilled { file, loaded } => loaded.get_or_init(|| read_back(&file.path)),
+        }
+    }
+}
+
+// the file is ours and was complete when the response was built, so failing to
+// read it back is not something the caller can act on. logged, and the body
+// comes back empty.
+fn read_back(path: &Path) -> Bytes {
+    match fs::read(path) {
+        Ok(bytes) => Bytes::from(bytes),
+        Err(e) => {
+            log::error!(
+                "could not read the spilled body in {}: {}",
+                path.display(),
+                e
+            );
+            Bytes::new()
+        }
+    }
+}
+
+impl Response {
+    pub fn is_spilled(&self) -> bool {
+        matches!(self.body, Body::Spilled { .. })
+    }
+
+    // the body a chunk at a time, straight from disk when it was spilled
+    pub fn body_reader(&self) -> Result<impl Read + Send, PeakError> {
+        Ok(self.body.reader()?)
+    }
+}
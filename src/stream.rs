@@ -0,0 +1,139 @@
+/*
+ * MIT License
+ *
+ * Copyright (c) 2025 Squirrel
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in
+ * all copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+use crate::{header_map, PeakError};
+use bytes::Bytes;
+use std::collections::HashMap;
+use std::io::{self, Read};
+use std::time::{Duration, Instant};
+
+const DRAIN_MAX_BYTES: u64 = 64 * 1024;
+const CHUNK_SIZE: usize = 64 * 1024;
+const DRAIN_MAX_TIME: Duration = Duration::from_millis(100);
+
+#[derive(Debug)]
+pub struct StreamingResponse {
+    pub status_code: u16,
+    pub headers: HashMap<String, String>,
+    pub url: String,
+    content_length: Option<u64>,
+    bytes_read: u64,
+    inner: Option<reqwest::blocking::Response>,
+    // read buffer for the iterator, allocated on the first chunk
+    chunk: Vec<u8>,
+}
+
+impl StreamingResponse {
+    pub(crate) fn new(response: reqwest::blocking::Response) -> Self {
+        StreamingResponse {
+            status_code: response.status().as_u16(),
+            headers: header_map(response.headers()),
+            url: response.url().to_string(),
+            content_length: response.content_length(),
+            bytes_read: 0,
+            inner: Some(response),
+            chunk: Vec::new(),
+        }
+    }
+
+    pub fn content_length(&self) -> Option<u64> {
+        self.content_length
+    }
+
+    pub fn bytes_read(&self) -> u64 {
+        self.bytes_read
+    }
+
+    // drops the connection without draining whatever is left of the body
+    pub fn close(mut self) {
+        self.inner.take();
+    }
+
+    // if only a little of the body is left, read it off so the connection can
+    // go back to the pool instead of being torn down. gives up past the byte or
+    // time budget, same idea as go's net/http. the time budget is only checked
+    // between reads, and a read blocks for as long as the client timeout allows,
+    // none by default. so only a known, small remainder is drained: a chunked body
+    // could stall mid-chunk and hang the drop.
+    fn drain(&mut self) {
+        let Some(mut response) = self.inner.take() else {
+            return;
+        };
+
+        let small = self
+            .content_length
+            .is_some_and(|length| length.saturating_sub(self.bytes_read) <= DRAIN_MAX_BYTES);
+        if !small {
+            return;
+        }
+
+        let started = Instant::now();
+        let mut drained = 0u64;
+        let mut buf = [0u8; 8192];
+        while drained <= DRAIN_MAX_BYTES && started.elapsed() < DRAIN_MAX_TIME {
+            match response.read(&mut buf) {
+                Ok(0) | Err(_) => return,
+                Ok(n) => drained += n as u64,
+            }
+        }
+    }
+}
+
+impl Read for StreamingResponse {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match self.inner.as_mut() {
+            Some(response) => {
+                let n = response.read(buf)?;
+                self.bytes_read += n as u64;
+                Ok(n)
+            }
+            None => Ok(0),
+        }
+    }
+}
+
+// reads into one buffer and hands each chunk over as Bytes sized to what was
+// read, so callers can slice and forward them without copying again
+impl Iterator for StreamingResponse {
+    type Item = Result<Bytes, PeakError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let response = self.inner.as_mut()?;
+        self.chunk.resize(CHUNK_SIZE, 0);
+        match response.read(&mut self.chunk) {
+            Ok(0) => None,
+            Ok(n) => {
+                self.bytes_read += n as u64;
+                Some(Ok(Bytes::copy_from_slice(&self.chunk[..n])))
+            }
+            Err(e) => Some(Err(PeakError::Io(e))),
+        }
+    }
+}
+
+impl Drop for StreamingResponse {
+    fn drop(&mut self) {
+        self.drain();
+    }
+}
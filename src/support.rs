@@ -0,0 +1,130 @@
+/*
+ * MIT License
+ *
+ * Copyright (c) 2025 Squirrel
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in
+ * all copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+use crate::validate::ConfigWarning;
+use crate::{PeakError, PeakRequests};
+
+// what an option needs from the build. everything is checked with cfg! so the
+// option itself compiles everywhere and only build() says no
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Capability {
+    Json,
+    UnixSockets,
+    // neither tls backend reqwest offers can do crl/ocsp, nothing turns this on yet
+    RevocationChecking,
+}
+
+impl Capability {
+    fn available(self) -> bool {
+        match self {
+            Capability::Json => cfg!(feature = "json"),
+            Capability::UnixSockets => cfg!(unix),
+            Capability::RevocationChecking => false,
+        }
+    }
+
+    fn needs(self) -> &'static str {
+        match self {
+            Capability::Json => "the json feature",
+            Capability::UnixSockets => "a unix target",
+            Capability::RevocationChecking => "a tls backend with revocation checking",
+        }
+    }
+
+    // the other half of a ConfigWarning, "x conflicts with <this>"
+    fn missing(self) -> &'static str {
+        match self {
+            Capability::Json => "building without the json feature",
+            Capability::UnixSockets => "a non-unix target",
+            Capability::RevocationChecking => "the tls backend",
+        }
+    }
+}
+
+struct Requirement {
+    option: &'static str,
+    needs: Capability,
+    // best-effort options still build, validate() says what gets skipped
+    best_effort: Option<&'static str>,
+    set: fn(&PeakRequests) -> bool,
+}
+
+const REQUIREMENTS: &[Requirement] = &[
+    Requirement {
+        option: "detect_captive_portal",
+        needs: Capability::Json,
+        best_effort: None,
+        set: |c| c.detect_captive_portal,
+    },
+    Requirement {
+        option: "unix_socket",
+        needs: Capability::UnixSockets,
+        best_effort: None,
+        set: |c| c.unix_socket.is_some(),
+    },
+    Requirement {
+        option: "check_revocation",
+        needs: Capability::RevocationChecking,
+        best_effort: None,
+        set: |c| c.check_revocation,
+    },
+    Requirement {
+        option: "persist_session_cookies",
+        needs: Capability::Json,
+        best_effort: Some(
+            "there's no save_cookies, session cookies only live as long as the client",
+        ),
+        set: |c| c.persist_session_cookies,
+    },
+];
+
+fn unmet(client: &PeakRequests) -> impl Iterator<Item = &'static Requirement> + '_ {
+    REQUIREMENTS
+        .iter()
+        .filter(move |requirement| !requirement.needs.available() && (requirement.set)(client))
+}
+
+impl PeakRequests {
+    pub(crate) fn check_capabilities(&self) -> Result<(), PeakError> {
+        match unmet(self).find(|requirement| requirement.best_effort.is_none()) {
+            Some(requirement) => Err(PeakError::UnsupportedFeature {
+                option: requirement.option,
+                needed_feature: requirement.needs.needs(),
+            }),
+            None => Ok(()),
+        }
+    }
+
+    pub(crate) fn capability_warnings(&self) -> Vec<ConfigWarning> {
+        unmet(self)
+            .filter_map(|requirement| {
+                Some(ConfigWarning {
+                    setting: requirement.option,
+                    conflicts_with: requirement.needs.missing(),
+                    effect: requirement.best_effort?,
+                })
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,382 @@
+/*
+ * MIT License
+ *
+ * Copyright (c) 2025 Squirrel
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in
+ * all copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+use crate::{
+    encode_component, header_name, header_value, PeakError, PeakRequestBuilder, PreparedRequest,
+};
+use percent_encoding::{utf8_percent_encode, AsciiSet, NON_ALPHANUMERIC};
+use reqwest::header::HeaderName;
+use std::collections::HashMap;
+
+// rfc 3986 pchar: unreserved, sub-delims, ':' and '@'. '/' is still encoded.
+const PATH_SEGMENT: &AsciiSet = &NON_ALPHANUMERIC
+    .remove(b'-')
+    .remove(b'.')
+    .remove(b'_')
+    .remove(b'~')
+    .remove(b'!')
+    .remove(b'$')
+    .remove(b'\'')
+    .remove(b'(')
+    .remove(b')')
+    .remove(b'*')
+    .remove(b',')
+    .remove(b';')
+    .remove(b':')
+    .remove(b'@')
+    .remove(b'&')
+    .remove(b'=')
+    .remove(b'+');
+
+// a form-style query value: everything that would split or end it is encoded
+const QUERY_VALUE: &AsciiSet = &NON_ALPHANUMERIC
+    .remove(b'-')
+    .remove(b'.')
+    .remove(b'_')
+    .remove(b'~')
+    .remove(b'/')
+    .remove(b'?')
+    .remove(b':')
+    .remove(b'@')
+    .remove(b',')
+    .remove(b'!')
+    .remove(b'$')
+    .remove(b'\'')
+    .remove(b'(')
+    .remove(b')')
+    .remove(b'*')
+    .remove(b';')
+    .remove(b' ');
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PlaceholderEncoding {
+    // everything but unreserved characters, safe anywhere
+    #[default]
+    Component,
+    // keeps what a path segment allows as is
+    PathSegment,
+    // for a query value, a space becomes '+'
+    Query,
+}
+
+impl PlaceholderEncoding {
+    fn encode(self, value: &str) -> String {
+        match self {
+            PlaceholderEncoding::Component => encode_component(value),
+            PlaceholderEncoding::PathSegment => {
+                utf8_percent_encode(value, PATH_SEGMENT).to_string()
+            }
+            PlaceholderEncoding::Query => utf8_percent_encode(value, QUERY_VALUE)
+                .to_string()
+                .replace(' ', "+"),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct RequestTemplate {
+    // everything but the url and headers, cloned as is on every render
+    base: PreparedRequest,
+    url: Vec<Segment>,
+    headers: Vec<(HeaderName, HeaderTemplate)>,
+    strict: bool,
+    defaults: HashMap<String, String>,
+    encodings: HashMap<String, PlaceholderEncoding>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Segment {
+    Literal(String),
+    Placeholder(String),
+}
+
+#[derive(Debug, Clone)]
+enum HeaderTemplate {
+    // validated in new, kept as given: to_str on a HeaderValue refuses non-ascii
+    Fixed(String),
+    // checked once the placeholders are filled in
+    Filled(Vec<Segment>),
+}
+
+impl PeakRequestBuilder<'_> {
+    pub fn template(self) -> Result<RequestTemplate, PeakError> {
+        RequestTemplate::new(self.prepare())
+    }
+}
+
+impl RequestTemplate {
+    // header names and fixed values are parsed once here, rendering only checks
+    // the values that had placeholders in them
+    pub fn new(mut base: PreparedRequest) -> Result<Self, PeakError> {
+        let mut headers = Vec::with_capacity(base.headers.len());
+        for (key, value) in std::mem::take(&mut base.headers) {
+            let name = header_name(&key)?;
+            let segments = parse_segments(&value);
+            let value = if segments
+                .iter()
+                .any(|segment| matches!(segment, Segment::Placeholder(_)))
+            {
+                HeaderTemplate::Filled(segments)
+            } else {
+                header_value(&value)?;
+                HeaderTemplate::Fixed(value)
+            };
+            headers.push((name, value));
+        }
+        let url = parse_segments(&base.url);
+        Ok(RequestTemplate {
+            base,
+            url,
+            headers,
+            strict: false,
+            defaults: HashMap::new(),
+            encodings: HashMap::new(),
+        })
+    }
+
+    // off by default. on, a value no placeholder uses is an error, likely a typo'd
+    // name. a placeholder with no value and no default fails either way.
+    pub fn strict_render(mut self, strict: bool) -> Self {
+        self.strict = strict;
+        self
+    }
+
+    // used when render isn't given a value for the placeholder
+    pub fn default_value(mut self, name: &str, value: &str) -> Self {
+        self.defaults.insert(name.to_string(), value.to_string());
+        self
+    }
+
+    pub fn encoding(mut self, name: &str, encoding: PlaceholderEncoding) -> Self {
+        self.encodings.insert(name.to_string(), encoding);
+        self
+    }
+
+    // url placeholders first, then the ones in header values. a name used in both
+    // gets the same value in both.
+    pub fn placeholders(&self) -> Vec<&str> {
+        let header_segments = self.headers.iter().flat_map(|(_, value)| match value {
+            HeaderTemplate::Fixed(_) => &[][..],
+            HeaderTemplate::Filled(segments) => segments,
+        });
+        let mut names = Vec::new();
+        for segment in self.url.iter().chain(header_segments) {
+            if let Segment::Placeholder(name) = segment {
+                if !names.contains(&name.as_str()) {
+                    names.push(name.as_str());
+                }
+            }
+        }
+        names
+    }
+
+    pub fn render(&self, values: &[(&str, &str)]) -> Result<PreparedRequest, PeakError> {
+        let placeholders = self.placeholders();
+        let missing: Vec<String> = placeholders
+            .iter()
+            .filter(|name| {
+                !values.iter().any(|(key, _)| key == *name) && !self.defaults.contains_key(**name)
+            })
+            .map(|name| name.to_string())
+            .collect();
+        let unused: Vec<String> = values
+            .iter()
+            .filter(|(key, _)| !placeholders.contains(key))
+            .map(|(key, _)| key.to_string())
+            .collect();
+        if !missing.is_empty() || (self.strict && !unused.is_empty()) {
+            return Err(PeakError::Template { missing, unused });
+        }
+
+        let mut request = self.base.clone();
+        request.url = self.fill(&self.url, values, |name, value| {
+            let encoding = self.encodings.get(name).copied().unwrap_or_default();
+            encoding.encode(value)
+        });
+        request.headers.reserve(self.headers.len());
+        for (name, value) in &self.headers {
+            let value = match value {
+                HeaderTemplate::Fixed(value) => value.clone(),
+                // header values aren't percent-encoded, they only have to be valid
+                HeaderTemplate::Filled(segments) => {
+                    let value = self.fill(segments, values, |_, value| value.to_string());
+                    header_value(&value)?;
+                    value
+                }
+            };
+            request.headers.push((name.as_str().to_string(), value));
+        }
+        Ok(request)
+    }
+
+    // every placeholder has a value or a default by now, render checked that
+    fn fill(
+        &self,
+        segments: &[Segment],
+        values: &[(&str, &str)],
+        encode: impl Fn(&str, &str) -> String,
+    ) -> String {
+        let mut out = String::new();
+        for segment in segments {
+            match segment {
+                Segment::Literal(text) => out.push_str(text),
+                Segment::Placeholder(name) => {
+                    let value = values
+                        .iter()
+                        .find(|(key, _)| key == name)
+                        .map(|(_, value)| *value)
+                        .or_else(|| self.defaults.get(name).map(String::as_str))
+                        .unwrap_or_default();
+                    out.push_str(&encode(name, value));
+                }
+            }
+        }
+        out
+    }
+}
+
+fn parse_segments(url: &str) -> Vec<Segment> {
+    let mut segments = Vec::new();
+    let mut rest = url;
+    while let Some(open) = rest.find('{') {
+        let Some(close) = rest[open..].find('}') else {
+            break;
+        };
+        let name = &rest[open + 1..open + close];
+        if name.is_empty() {
+            segments.push(Segment::Literal(rest[..open + close + 1].to_string()));
+        } else {
+            segments.push(Segment::Literal(rest[..open].to_string()));
+            segments.push(Segment::Placeholder(name.to_string()));
+        }
+        rest = &rest[open + close + 1..];
+    }
+    segments.push(Segment::Literal(rest.to_string()));
+    segments.retain(|segment| segment != &Segment::Literal(String::new()));
+    segments
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn template(url: &str) -> RequestTemplate {
+        RequestTemplate::new(PreparedRequest::new("GET", url)).unwrap()
+    }
+
+    #[test]
+    fn unused_values_only_fail_strict_renders() {
+        let template = template("https://api.example/users/{id}");
+        let values = [("id", "7"), ("idd", "8")];
+
+        let request = template.render(&values).unwrap();
+        assert_eq!(request.url, "https://api.example/users/7");
+
+        let Err(PeakError::Template { missing, unused }) =
+            template.strict_render(true).render(&values)
+        else {
+            panic!("strict render accepted an unused value");
+        };
+        assert!(missing.is_empty());
+        assert_eq!(unused, ["idd"]);
+    }
+
+    #[test]
+    fn missing_values_always_fail() {
+        let template = template("https://api.example/{org}/users/{id}");
+
+        for template in [template.clone(), template.strict_render(true)] {
+            let Err(PeakError::Template { missing, unused }) = template.render(&[("id", "7")])
+            else {
+                panic!("rendered without a value for org");
+            };
+            assert_eq!(missing, ["org"]);
+            assert!(unused.is_empty());
+        }
+    }
+
+    #[test]
+    fn defaults_fill_what_render_leaves_out() {
+        let template = template("https://api.example/{version}/users/{id}")
+            .default_value("version", "v2")
+            .strict_render(true);
+
+        let request = template.render(&[("id", "7")]).unwrap();
+        assert_eq!(request.url, "https://api.example/v2/users/7");
+        let request = template.render(&[("id", "7"), ("version", "v3")]).unwrap();
+        assert_eq!(request.url, "https://api.example/v3/users/7");
+    }
+
+    #[test]
+    fn encoders_differ_in_the_url() {
+        let url = |encoding| {
+            template("https://api.example/{name}")
+                .encoding("name", encoding)
+                .render(&[("name", "a b:c/d")])
+                .unwrap()
+                .url
+        };
+
+        assert_eq!(
+            url(PlaceholderEncoding::Component),
+            "https://api.example/a%20b%3Ac%2Fd"
+        );
+        assert_eq!(
+            url(PlaceholderEncoding::PathSegment),
+            "https://api.example/a%20b:c%2Fd"
+        );
+        assert_eq!(
+            url(PlaceholderEncoding::Query),
+            "https://api.example/a+b:c/d"
+        );
+    }
+
+    #[test]
+    fn splits_placeholders_from_literals() {
+        let literal = |text: &str| Segment::Literal(text.to_string());
+        let placeholder = |name: &str| Segment::Placeholder(name.to_string());
+
+        assert_eq!(
+            parse_segments("https://h/{org}/{id}?q={q}"),
+            [
+                literal("https://h/"),
+                placeholder("org"),
+                literal("/"),
+                placeholder("id"),
+                literal("?q="),
+                placeholder("q"),
+            ]
+        );
+        assert_eq!(
+            parse_segments("{a}{b}-{}-{open"),
+            [
+                placeholder("a"),
+                placeholder("b"),
+                literal("-{}"),
+                literal("-{open"),
+            ]
+        );
+        assert!(parse_segments("").is_empty());
+    }
+}
@@ -0,0 +1,171 @@
+/*
+ * MIT License
+ *
+ * Copyright (c) 2025 Squirrel
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in
+ * all copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+use crate::content_type::snippet;
+use crate::Response;
+#[cfg(feature = "json")]
+use serde_json::Value;
+
+#[derive(Debug, Clone)]
+pub enum HeaderMatcher {
+    Equals(String),
+    Contains(String),
+    Present,
+}
+
+pub fn equals(value: &str) -> HeaderMatcher {
+    HeaderMatcher::Equals(value.to_string())
+}
+
+pub fn contains(value: &str) -> HeaderMatcher {
+    HeaderMatcher::Contains(value.to_string())
+}
+
+pub fn present() -> HeaderMatcher {
+    HeaderMatcher::Present
+}
+
+impl From<&str> for HeaderMatcher {
+    fn from(value: &str) -> Self {
+        equals(value)
+    }
+}
+
+impl HeaderMatcher {
+    fn matches(&self, value: &str) -> bool {
+        match self {
+            HeaderMatcher::Equals(expected) => value == expected,
+            HeaderMatcher::Contains(expected) => value.contains(expected.as_str()),
+            HeaderMatcher::Present => true,
+        }
+    }
+}
+
+// every assertion returns the response again so they can be chained
+pub trait ResponseAssertions {
+    fn assert_status(&self, status: u16) -> &Self;
+    fn assert_header(&self, name: &str, matcher: impl Into<HeaderMatcher>) -> &Self;
+    #[cfg(feature = "json")]
+    fn assert_json_matches(&self, expected: Value) -> &Self;
+    fn assert_body_contains(&self, needle: &str) -> &Self;
+}
+
+impl ResponseAssertions for Response {
+    #[track_caller]
+    fn assert_status(&self, status: u16) -> &Self {
+        if self.status_code != status {
+            fail(self, &format!("expected status {}", status));
+        }
+        self
+    }
+
+    #[track_caller]
+    fn assert_header(&self, name: &str, matcher: impl Into<HeaderMatcher>) -> &Self {
+        let matcher = matcher.into();
+        match self.headers.get(&name.to_ascii_lowercase()) {
+            Some(value) if matcher.matches(value) => {}
+            Some(value) => fail(
+                self,
+                &format!("header {} = {:?} does not match {:?}", name, value, matcher),
+            ),
+            None => fail(self, &format!("header {} is missing", name)),
+        }
+        self
+    }
+
+    // subset match: objects may carry extra keys, arrays must line up element by element
+    #[cfg(feature = "json")]
+    #[track_caller]
+    fn assert_json_matches(&self, expected: Value) -> &Self {
+        let actual = match serde_json::from_str::<Value>(&self.text()) {
+            Ok(actual) => actual,
+            Err(e) => {
+                fail(self, &format!("body is not json ({})", e));
+                return self;
+            }
+        };
+        let mut mismatches = Vec::new();
+        subset_mismatches(&expected, &actual, String::new(), &mut mismatches);
+        if !mismatches.is_empty() {
+            fail(
+                self,
+                &format!("json does not match:\n  {}", mismatches.join("\n  ")),
+            );
+        }
+        self
+    }
+
+    #[track_caller]
+    fn assert_body_contains(&self, needle: &str) -> &Self {
+        if !self.text().contains(needle) {
+            fail(self, &format!("body does not contain {:?}", needle));
+        }
+        self
+    }
+}
+
+#[track_caller]
+fn fail(response: &Response, what: &str) {
+    panic!(
+        "{}\n  url: {}\n  status: {}\n  body: {}",
+        what,
+        response.url,
+        response.status_code,
+        snippet(&response.text())
+    );
+}
+
+#[cfg(feature = "json")]
+fn subset_mismatches(expected: &Value, actual: &Value, path: String, out: &mut Vec<String>) {
+    let at = if path.is_empty() { "/" } else { &path };
+    match (expected, actual) {
+        (Value::Object(expected), Value::Object(actual)) => {
+            for (key, expected) in expected {
+                let path = format!("{}/{}", path, key);
+                match actual.get(key) {
+                    Some(actual) => subset_mismatches(expected, actual, path, out),
+                    None => out.push(format!("{}: missing, expected {}", path, expected)),
+                }
+            }
+        }
+        (Value::Array(expected), Value::Array(actual)) => {
+            if expected.len() != actual.len() {
+                out.push(format!(
+                    "{}: expected {} elements, got {}",
+                    at,
+                    expected.len(),
+                    actual.len()
+                ));
+                return;
+            }
+            for (index, (expected, actual)) in expected.iter().zip(actual).enumerate() {
+                subset_mismatches(expected, actual, format!("{}/{}", path, index), out);
+            }
+        }
+        (expected, actual) if expected != actual => {
+            out.push(format!("{}: expected {}, got {}", at, expected, actual))
+        }
+        _ => {}
+    }
+}
@@ -0,0 +1,86 @@
+/*
+ * MIT License
+ *
+ * Copyright (c) 2025 Squirrel
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in
+ * all copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+use crate::{PeakError, PeakRequests};
+use reqwest::blocking::ClientBuilder;
+use std::error::Error;
+
+impl PeakRequests {
+    // native-tls (openssl) still falls back to the subject CN when a cert has no
+    // SAN, rustls never does, so requiring a SAN means switching backends
+    pub fn require_san(mut self, require: bool) -> Self {
+        self.require_san = require;
+        self
+    }
+
+    pub fn check_revocation(mut self, check: bool) -> Self {
+        self.check_revocation = check;
+        self
+    }
+
+    pub(crate) fn apply_tls_options(
+        &self,
+        client_builder: ClientBuilder,
+    ) -> Result<ClientBuilder, PeakError> {
+        if self.require_san {
+            return Ok(client_builder.use_rustls_tls());
+        }
+        Ok(client_builder)
+    }
+}
+
+// openssl and rustls word these differently, this maps both onto the check that failed
+pub(crate) fn certificate_failure(error: &reqwest::Error) -> Option<PeakError> {
+    let mut source = error.source();
+    while let Some(cause) = source {
+        let message = cause.to_string();
+        let lower = message.to_ascii_lowercase();
+        if lower.contains("certificate") {
+            let check = if lower.contains("notvalidforname")
+                || lower.contains("hostname mismatch")
+                || lower.contains("not valid for name")
+            {
+                "hostname"
+            } else if lower.contains("expired") || lower.contains("notvalidyet") {
+                "validity period"
+            } else if lower.contains("revoked") {
+                "revocation"
+            } else if lower.contains("unknownissuer")
+                || lower.contains("self-signed")
+                || lower.contains("self signed")
+                || lower.contains("issuer")
+            {
+                "trust chain"
+            } else {
+                "certificate"
+            };
+            return Some(PeakError::Tls {
+                check: check.to_string(),
+                message,
+            });
+        }
+        source = cause.source();
+    }
+    None
+}
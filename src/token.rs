@@ -0,0 +1,125 @@
+/*
+ * MIT License
+ *
+ * Copyright (c) 2025 Squirrel
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in
+ * all copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+use crate::{contain, Callback, PeakError, PeakRequests, PreparedRequest, Response};
+use std::fmt;
+use std::sync::{Arc, Mutex};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TokenRequestReason {
+    Initial,
+    Unauthorized,
+}
+
+type FetchToken = Callback<dyn Fn(TokenRequestReason) -> Result<String, String> + Send + Sync>;
+
+// cheap to clone, clones share the cached token. hand the same provider to clients
+// on different threads and a burst of 401s still only triggers one refresh.
+#[derive(Clone)]
+pub struct TokenProvider {
+    fetch: FetchToken,
+    token: Arc<Mutex<Option<String>>>,
+}
+
+impl<F> From<F> for TokenProvider
+where
+    F: Fn(TokenRequestReason) -> Result<String, String> + Send + Sync + 'static,
+{
+    fn from(f: F) -> Self {
+        TokenProvider {
+            fetch: Callback(Arc::new(f)),
+            token: Arc::new(Mutex::new(None)),
+        }
+    }
+}
+
+// never print the token itself
+impl fmt::Debug for TokenProvider {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("TokenProvider")
+    }
+}
+
+impl TokenProvider {
+    pub fn new(
+        f: impl Fn(TokenRequestReason) -> Result<String, String> + Send + Sync + 'static,
+    ) -> Self {
+        TokenProvider::from(f)
+    }
+
+    // the callback runs with the lock held, that's what makes it single-flight
+    fn current(&self) -> Result<String, PeakError> {
+        let mut token = self.token.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(token) = token.as_ref() {
+            return Ok(token.clone());
+        }
+        // caught inside the lock, so a panicking provider doesn't poison it either
+        let fresh = contain("token_provider", || {
+            (self.fetch)(TokenRequestReason::Initial)
+        })?
+        .map_err(PeakError::TokenProvider)?;
+        *token = Some(fresh.clone());
+        Ok(fresh)
+    }
+
+    // if another thread already swapped out the rejected token, just use theirs
+    fn refresh(&self, rejected: &str) -> Result<String, PeakError> {
+        let mut token = self.token.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(token) = token.as_ref().filter(|token| token.as_str() != rejected) {
+            return Ok(token.clone());
+        }
+        let fresh = contain("token_provider", || {
+            (self.fetch)(TokenRequestReason::Unauthorized)
+        })?
+        .map_err(PeakError::TokenProvider)?;
+        *token = Some(fresh.clone());
+        Ok(fresh)
+    }
+}
+
+impl PeakRequests {
+    pub fn token_provider(mut self, provider: impl Into<TokenProvider>) -> Self {
+        self.token_provider = Some(provider.into());
+        self
+    }
+
+    pub(crate) fn execute_with_token(
+        &mut self,
+        provider: &TokenProvider,
+        request: &PreparedRequest,
+    ) -> Result<Response, PeakError> {
+        let token = provider.current()?;
+        let mut attempt = request.clone();
+        attempt.set_header("Authorization", format!("Bearer {}", token));
+        let response = self.fetch(&attempt)?;
+        if response.status_code != 401 {
+            return Ok(response);
+        }
+
+        // exactly one retry, a second 401 goes back to the caller as is
+        let token = provider.refresh(&token)?;
+        attempt.set_header("Authorization", format!("Bearer {}", token));
+        self.fetch(&attempt)
+    }
+}
@@ -0,0 +1,355 @@
+/*
+ * MIT License
+ *
+ * Copyright (c) 2025 Squirrel
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in
+ * all copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+use crate::PeakRequests;
+#[cfg(unix)]
+use crate::{PeakError, PreparedRequest};
+#[cfg(unix)]
+use reqwest::{blocking::Client, ResponseBuilderExt};
+#[cfg(unix)]
+use std::io::{self, BufRead, BufReader, Read, Write};
+#[cfg(unix)]
+use std::os::unix::net::UnixStream;
+use std::path::{Path, PathBuf};
+#[cfg(unix)]
+use std::time::Duration;
+
+pub(crate) const DEFAULT_MAX_BODY: u64 = 64 * 1024 * 1024;
+
+// settable everywhere so check_capabilities can refuse it off unix
+#[derive(Debug, Clone)]
+#[cfg_attr(not(unix), allow(dead_code))]
+pub(crate) struct UnixSocket {
+    path: PathBuf,
+    // None sends everything over the socket
+    host: Option<String>,
+}
+
+impl PeakRequests {
+    // every request from this client goes over the socket, the url still says
+    // where on the server (http://localhost/containers/json). plain http only.
+    pub fn unix_socket(mut self, path: &Path) -> Self {
+        self.unix_socket = Some(UnixSocket {
+            path: path.to_path_buf(),
+            host: None,
+        });
+        self
+    }
+
+    // only requests for http://host/... go over the socket, the rest as usual
+    pub fn unix_socket_for_host(mut self, host: &str, path: &Path) -> Self {
+        self.unix_socket = Some(UnixSocket {
+            path: path.to_path_buf(),
+            host: Some(host.to_ascii_lowercase()),
+        });
+        self
+    }
+
+    // nothing between us and the server caps a body read off the socket, so this
+    // does. over it the request fails with BodyTooLarge. 64 MiB unless set.
+    pub fn unix_socket_max_body(mut self, bytes: u64) -> Self {
+        self.unix_socket_max_body = bytes;
+        self
+    }
+
+    #[cfg(unix)]
+    pub(crate) fn unix_socket_route(&self, request: &PreparedRequest) -> Option<&UnixSocket> {
+        let socket = self.unix_socket.as_ref()?;
+        let Some(host) = &socket.host else {
+            return Some(socket);
+        };
+        let url = reqwest::Url::parse(&request.url).ok()?;
+        url.host_str()
+            .is_some_and(|url_host| url_host.eq_ignore_ascii_case(host))
+            .then_some(socket)
+    }
+
+    // one request per connection, Connection: close and then read the answer off
+    #[cfg(unix)]
+    pub(crate) fn send_unix(
+        &self,
+        socket: &UnixSocket,
+        client: &Client,
+        request: &PreparedRequest,
+    ) -> Result<reqwest::blocking::Response, PeakError> {
+        let built = self.request_builder(client, request)?.build()?;
+        if built.url().scheme() != "http" {
+            return Err(PeakError::Unsupported(
+                "only plain http is spoken over a unix socket".to_string(),
+            ));
+        }
+
+        let mut stream = UnixStream::connect(&socket.path).map_err(|e| socket_error(socket, e))?;
+        let timeout = request.timeout.or(self.timeout.map(Duration::from_secs));
+        stream.set_read_timeout(timeout)?;
+        stream.set_write_timeout(timeout)?;
+
+        let url = built.url();
+        let mut target = url.path().to_string();
+        if let Some(query) = url.query() {
+            target.push('?');
+            target.push_str(query);
+        }
+        let body = built
+            .body()
+            .map(|body| {
+                body.as_bytes().map(<[u8]>::to_vec).ok_or_else(|| {
+                    PeakError::Unsupported("streamed bodies over a unix socket".to_string())
+                })
+            })
+            .transpose()?
+            .unwrap_or_default();
+
+        // request_builder already put the client's headers on, minus the credentials
+        // once a redirect left the origin
+        let headers = built.headers();
+        let mut head = format!("{} {} HTTP/1.1\r\n", built.method(), target).into_bytes();
+        if !headers.contains_key("host") {
+            let host = format!("Host: {}\r\n", url.host_str().unwrap_or("localhost"));
+            head.extend_from_slice(host.as_bytes());
+        }
+        for (name, value) in headers {
+            if name == "connection" || name == "content-length" {
+                continue;
+            }
+            // values go out as the bytes they are, not only the ascii ones
+            head.extend_from_slice(name.as_str().as_bytes());
+            head.extend_from_slice(b": ");
+            head.extend_from_slice(value.as_bytes());
+            head.extend_from_slice(b"\r\n");
+        }
+        if !body.is_empty() || !matches!(built.method().as_str(), "GET" | "HEAD" | "DELETE") {
+            head.extend_from_slice(format!("Content-Length: {}\r\n", body.len()).as_bytes());
+        }
+        head.extend_from_slice(b"Connection: close\r\n\r\n");
+        stream.write_all(&head)?;
+        stream.write_all(&body)?;
+        stream.flush()?;
+
+        let head_only = built.method() == "HEAD";
+        let limits = Limits {
+            head_size: self.max_response_header_size,
+            header_count: self.max_header_count,
+            body: self.unix_socket_max_body,
+        };
+        read_response(BufReader::new(stream), url.clone(), head_only, &limits)
+    }
+}
+
+#[cfg(unix)]
+fn socket_error(socket: &UnixSocket, error: io::Error) -> PeakError {
+    let reason = match error.kind() {
+        io::ErrorKind::NotFound => "no such socket".to_string(),
+        io::ErrorKind::PermissionDenied => "permission denied".to_string(),
+        io::ErrorKind::ConnectionRefused => "connection refused, nothing is listening".to_string(),
+        _ => error.to_string(),
+    };
+    PeakError::UnixSocket {
+        path: socket.path.clone(),
+        reason,
+        source: error,
+    }
+}
+
+#[cfg(unix)]
+fn malformed(reason: &str) -> PeakError {
+    PeakError::MalformedResponse {
+        reason: reason.to_string(),
+    }
+}
+
+// nothing in front of the socket enforces these, so the reader does
+#[cfg(unix)]
+struct Limits {
+    head_size: usize,
+    header_count: usize,
+    body: u64,
+}
+
+// longest chunk-size line taken, extensions and all
+#[cfg(unix)]
+const MAX_CHUNK_LINE: u64 = 4096;
+
+#[cfg(unix)]
+struct Head {
+    status: u16,
+    builder: http::response::Builder,
+    length: Option<u64>,
+    chunked: bool,
+}
+
+#[cfg(unix)]
+fn read_response(
+    mut reader: BufReader<UnixStream>,
+    url: reqwest::Url,
+    head_only: bool,
+    limits: &Limits,
+) -> Result<reqwest::blocking::Response, PeakError> {
+    let max_body = limits.body;
+    // 100 Continue and 103 Early Hints come ahead of the real answer, skip them.
+    // 101 would be the last thing said in http, so it counts as final.
+    let head = loop {
+        let head = read_head(&mut reader, &url, limits)?;
+        if head.status == 101 || !(100..200).contains(&head.status) {
+            break head;
+        }
+    };
+
+    let mut body = Vec::new();
+    if !head_only && !matches!(head.status, 101 | 204 | 304) {
+        if head.chunked {
+            read_chunked(&mut reader, &mut body, max_body)?;
+        } else if let Some(length) = head.length {
+            if length > max_body {
+                return Err(PeakError::BodyTooLarge { limit: max_body });
+            }
+            read_exactly(&mut reader, length, &mut body)?;
+        } else {
+            // read one past the cap to tell a full body from an oversized one
+            reader
+                .by_ref()
+                .take(max_body.saturating_add(1))
+                .read_to_end(&mut body)?;
+            if body.len() as u64 > max_body {
+                return Err(PeakError::BodyTooLarge { limit: max_body });
+            }
+        }
+    }
+
+    let response = head
+        .builder
+        .body(body)
+        .map_err(|e| malformed(&e.to_string()))?;
+    Ok(response.into())
+}
+
+#[cfg(unix)]
+fn read_head(
+    reader: &mut BufReader<UnixStream>,
+    url: &reqwest::Url,
+    limits: &Limits,
+) -> Result<Head, PeakError> {
+    let mut size = 0;
+    let mut count = 0;
+    let mut line = String::new();
+    read_head_line(reader, &mut line, &mut size, count, limits)?;
+    let status: u16 = line
+        .split_whitespace()
+        .nth(1)
+        .and_then(|status| status.parse().ok())
+        .ok_or_else(|| malformed("bad status line from unix socket"))?;
+
+    let mut head = Head {
+        status,
+        builder: http::Response::builder().status(status).url(url.clone()),
+        length: None,
+        chunked: false,
+    };
+    loop {
+        line.clear();
+        if read_head_line(reader, &mut line, &mut size, count, limits)? == 0 {
+            return Err(malformed("unix socket closed inside the response head"));
+        }
+        let header = line.trim_end_matches(['\r', '\n']);
+        if header.is_empty() {
+            return Ok(head);
+        }
+        let Some((name, value)) = header.split_once(':') else {
+            return Err(malformed("bad header line from unix socket"));
+        };
+        count += 1;
+        if count > limits.header_count {
+            return Err(PeakError::HeadersTooLarge { size, count });
+        }
+        let value = value.trim();
+        if name.eq_ignore_ascii_case("content-length") {
+            head.length = value.parse().ok();
+        }
+        if name.eq_ignore_ascii_case("transfer-encoding") {
+            head.chunked = value.to_ascii_lowercase().contains("chunked");
+        }
+        head.builder = head.builder.header(name.trim(), value);
+    }
+}
+
+// the peer decides how long a line is, so every read stops at what's left of the
+// head limit (plus one, to tell a head that fills it from one past it)
+#[cfg(unix)]
+fn read_head_line(
+    reader: &mut BufReader<UnixStream>,
+    line: &mut String,
+    size: &mut usize,
+    count: usize,
+    limits: &Limits,
+) -> Result<usize, PeakError> {
+    let left = limits.head_size.saturating_sub(*size).saturating_add(1);
+    let read = reader.by_ref().take(left as u64).read_line(line)?;
+    *size += read;
+    if *size > limits.head_size {
+        return Err(PeakError::HeadersTooLarge { size: *size, count });
+    }
+    Ok(read)
+}
+
+// grows the body as bytes arrive instead of trusting the announced size up front
+#[cfg(unix)]
+fn read_exactly(reader: &mut impl Read, size: u64, body: &mut Vec<u8>) -> Result<(), PeakError> {
+    let read = reader.take(size).read_to_end(body)?;
+    if (read as u64) < size {
+        return Err(malformed("unix socket closed inside the response body"));
+    }
+    Ok(())
+}
+
+#[cfg(unix)]
+fn read_chunked(
+    reader: &mut BufReader<UnixStream>,
+    body: &mut Vec<u8>,
+    max_body: u64,
+) -> Result<(), PeakError> {
+    let mut line = String::new();
+    loop {
+        line.clear();
+        reader.by_ref().take(MAX_CHUNK_LINE).read_line(&mut line)?;
+        let size = line.trim().split(';').next().unwrap_or_default();
+        let size = u64::from_str_radix(size, 16).map_err(|_| malformed("bad chunk size"))?;
+        if size == 0 {
+            // trailers, up to the blank line
+            loop {
+                line.clear();
+                let read = reader.by_ref().take(MAX_CHUNK_LINE).read_line(&mut line)?;
+                if read == 0 || line.trim().is_empty() {
+                    return Ok(());
+                }
+            }
+        }
+        let total = (body.len() as u64).checked_add(size);
+        if total.is_none_or(|total| total > max_body) {
+            return Err(PeakError::BodyTooLarge { limit: max_body });
+        }
+        read_exactly(reader, size, body)?;
+        line.clear();
+        reader.by_ref().take(MAX_CHUNK_LINE).read_line(&mut line)?;
+    }
+}
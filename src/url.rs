@@ -0,0 +1,131 @@
+/*
+ * MIT License
+ *
+ * Copyright (c) 2025 Squirrel
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in
+ * all copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+use crate::{PeakError, Response};
+use percent_encoding::percent_decode;
+use reqwest::Url;
+
+// application/x-www-form-urlencoded the way browsers read it: '+' is a space,
+// duplicate keys and their order are kept, a bad escape is left as it is
+pub fn parse_query(query: &str) -> Vec<(String, String)> {
+    pairs(query)
+        .map(|(name, value)| (decode(name), decode(value)))
+        .collect()
+}
+
+// same, but a '%' without two hex digits after it or bytes that aren't utf-8 fail
+pub fn parse_query_strict(query: &str) -> Result<Vec<(String, String)>, PeakError> {
+    pairs(query)
+        .map(|(name, value)| Ok((decode_strict(name)?, decode_strict(value)?)))
+        .collect()
+}
+
+fn pairs(query: &str) -> impl Iterator<Item = (&str, &str)> {
+    query
+        .strip_prefix('?')
+        .unwrap_or(query)
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| pair.split_once('=').unwrap_or((pair, "")))
+}
+
+fn decode(input: &str) -> String {
+    let input = input.replace('+', " ");
+    percent_decode(input.as_bytes())
+        .decode_utf8_lossy()
+        .into_owned()
+}
+
+fn decode_strict(input: &str) -> Result<String, PeakError> {
+    let bytes = input.as_bytes();
+    for (at, _) in input.match_indices('%') {
+        let valid = bytes.len() > at + 2
+            && bytes[at + 1].is_ascii_hexdigit()
+            && bytes[at + 2].is_ascii_hexdigit();
+        if !valid {
+            return Err(PeakError::InvalidUrl(format!(
+                "bad percent escape at byte {} of {:?}",
+                at, input
+            )));
+        }
+    }
+    let input = input.replace('+', " ");
+    percent_decode(input.as_bytes())
+        .decode_utf8()
+        .map(|decoded| decoded.into_owned())
+        .map_err(|_| PeakError::InvalidUrl(format!("{:?} doesn't decode to utf-8", input)))
+}
+
+impl Response {
+    // absolute, resolved against this response's url when the header is relative
+    pub fn location(&self) -> Option<String> {
+        let location = self.headers.get("location")?;
+        match Url::parse(&self.url) {
+            Ok(base) => base.join(location).ok().map(String::from),
+            Err(_) => Some(location.clone()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pairs(list: &[(&str, &str)]) -> Vec<(String, String)> {
+        list.iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn decodes_like_a_form() {
+        assert_eq!(
+            parse_query("?a=1&b=x+y%20z&&a=2&flag&%C3%A9=%E2%9C%93"),
+            pairs(&[
+                ("a", "1"),
+                ("b", "x y z"),
+                ("a", "2"),
+                ("flag", ""),
+                ("é", "✓")
+            ])
+        );
+        assert_eq!(parse_query("k=a=b"), pairs(&[("k", "a=b")]));
+        assert!(parse_query("").is_empty());
+    }
+
+    #[test]
+    fn lenient_keeps_bad_escapes_and_strict_refuses_them() {
+        assert_eq!(
+            parse_query("a=100%&b=%zz"),
+            pairs(&[("a", "100%"), ("b", "%zz")])
+        );
+        assert!(parse_query_strict("a=100%").is_err());
+        assert!(parse_query_strict("a=%4").is_err());
+        assert!(parse_query_strict("a=%ff").is_err());
+        assert_eq!(
+            parse_query_strict("a=%41+b").unwrap(),
+            pairs(&[("a", "A b")])
+        );
+    }
+}
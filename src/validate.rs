@@ -0,0 +1,157 @@
+/*
+ * MIT License
+ *
+ * Copyright (c) 2025 Squirrel
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in
+ * all copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+use crate::PeakRequests;
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigWarning {
+    pub setting: &'static str,
+    pub conflicts_with: &'static str,
+    // what the client will actually do
+    pub effect: &'static str,
+}
+
+impl fmt::Display for ConfigWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} conflicts with {}: {}",
+            self.setting, self.conflicts_with, self.effect
+        )
+    }
+}
+
+struct Rule {
+    setting: &'static str,
+    conflicts_with: &'static str,
+    effect: &'static str,
+    applies: fn(&PeakRequests) -> bool,
+}
+
+// every pair of settings where one quietly cancels the other out
+const RULES: &[Rule] = &[
+    Rule {
+        setting: "http2_initial_stream_window_size",
+        conflicts_with: "http1_only",
+        effect: "the client never speaks http2, the window size is ignored",
+        applies: |c| c.http2.http1_only && c.http2.stream_window.is_some(),
+    },
+    Rule {
+        setting: "http2_initial_connection_window_size",
+        conflicts_with: "http1_only",
+        effect: "the client never speaks http2, the window size is ignored",
+        applies: |c| c.http2.http1_only && c.http2.connection_window.is_some(),
+    },
+    Rule {
+        setting: "http2_adaptive_window",
+        conflicts_with: "http1_only",
+        effect: "the client never speaks http2, flow control stays off",
+        applies: |c| c.http2.http1_only && c.http2.adaptive_window,
+    },
+    Rule {
+        setting: "http2_initial_stream_window_size",
+        conflicts_with: "http2_adaptive_window",
+        effect: "the adaptive window replaces the fixed size",
+        applies: |c| c.http2.adaptive_window && c.http2.stream_window.is_some(),
+    },
+    Rule {
+        setting: "http2_initial_connection_window_size",
+        conflicts_with: "http2_adaptive_window",
+        effect: "the adaptive window replaces the fixed size",
+        applies: |c| c.http2.adaptive_window && c.http2.connection_window.is_some(),
+    },
+    Rule {
+        setting: "on_redirect",
+        conflicts_with: "allow_redirects(false)",
+        effect: "redirects aren't followed, so the hook is never called",
+        applies: |c| c.on_redirect.is_some() && !c.allow_redirects,
+    },
+    Rule {
+        setting: "persist_session_cookies",
+        conflicts_with: "cookies(false)",
+        effect: "there's no cookie jar, save_cookies writes an empty list",
+        applies: |c| c.persist_session_cookies && c.cookie_jar.is_none(),
+    },
+    Rule {
+        setting: "auth",
+        conflicts_with: "token_provider",
+        effect: "the token provider is used and auth is ignored",
+        applies: |c| c.auth.is_some() && c.token_provider.is_some(),
+    },
+    Rule {
+        setting: "headers(Cookie)",
+        conflicts_with: "cookies(true)",
+        effect: "the fixed Cookie header is sent and the jar's cookies never are",
+        applies: |c| {
+            c.cookie_jar.is_some()
+                && c.headers
+                    .keys()
+                    .any(|key| key.eq_ignore_ascii_case("cookie"))
+        },
+    },
+    Rule {
+        setting: "normalize_outgoing",
+        conflicts_with: "url_normalization",
+        effect: "every normalization step is off, urls go out unchanged",
+        applies: |c| {
+            let n = &c.url_normalization;
+            c.normalize_outgoing
+                && !(n.lowercase_scheme_and_host
+                    || n.strip_default_port
+                    || n.uppercase_percent_encoding
+                    || n.decode_unreserved
+                    || n.sort_query
+                    || n.remove_trailing_slash)
+        },
+    },
+    Rule {
+        setting: "keepalive_ping",
+        conflicts_with: "disable_keep_alive",
+        effect: "nothing stays in the pool, every ping opens a new connection",
+        applies: |c| c.keepalive.is_some() && c.disable_keep_alive,
+    },
+    Rule {
+        setting: "convert_long_get_to_post",
+        conflicts_with: "max_url_length",
+        effect: "GETs longer than the limit but under the threshold fail instead of converting",
+        applies: |c| matches!((c.max_url_length, &c.long_get), (Some(limit), Some(long_get)) if limit < long_get.threshold),
+    },
+];
+
+impl PeakRequests {
+    // also runs when the client is first built, with each warning going to log::warn
+    pub fn validate(&self) -> Vec<ConfigWarning> {
+        RULES
+            .iter()
+            .filter(|rule| (rule.applies)(self))
+            .map(|rule| ConfigWarning {
+                setting: rule.setting,
+                conflicts_with: rule.conflicts_with,
+                effect: rule.effect,
+            })
+            .chain(self.capability_warnings())
+            .collect()
+    }
+}
@@ -0,0 +1,167 @@
+/*
+ * MIT License
+ *
+ * Copyright (c) 2025 Squirrel
+ *
+ * Permission is hereby granted, free of charge, to any person obtaining a copy
+ * of this software and associated documentation files (the "Software"), to deal
+ * in the Software without restriction, including without limitation the rights
+ * to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+ * copies of the Software, and to permit persons to whom the Software is
+ * furnished to do so, subject to the following conditions:
+ *
+ * The above copyright notice and this permission notice shall be included in
+ * all copies or substantial portions of the Software.
+ *
+ * THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+ * IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+ * FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+ * AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+ * LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+ * OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+ * SOFTWARE.
+ */
+
+use crate::{unique_token, PeakError, PeakRequests, Response};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ViaEntry {
+    pub protocol: Option<String>,
+    pub received_by: String,
+    pub comment: Option<String>,
+}
+
+impl PeakRequests {
+    // sends a via hop naming this client, so a proxy that routes the request back
+    // through itself shows up as our token in the response's via chain
+    pub fn detect_proxy_loops(mut self, detect: bool) -> Self {
+        self.proxy_loop_token = if detect {
+            Some(format!("peakrequests-{}", unique_token()))
+        } else {
+            None
+        };
+        self
+    }
+
+    pub(crate) fn check_proxy_loop(&self, response: &Response) -> Result<(), PeakError> {
+        let Some(token) = &self.proxy_loop_token else {
+            return Ok(());
+        };
+        if response
+            .via()
+            .iter()
+            .any(|entry| entry.received_by.eq_ignore_ascii_case(token))
+        {
+            return Err(PeakError::ProxyLoop {
+                via: response.headers.get("via").cloned().unwrap_or_default(),
+            });
+        }
+        Ok(())
+    }
+}
+
+impl Response {
+    pub fn via(&self) -> Vec<ViaEntry> {
+        self.headers
+            .get("via")
+            .map(|header| parse_via(header))
+            .unwrap_or_default()
+    }
+}
+
+fn parse_via(header: &str) -> Vec<ViaEntry> {
+    split_hops(header)
+        .iter()
+        .filter_map(|hop| parse_hop(hop))
+        .collect()
+}
+
+// commas inside a parenthesised comment don't separate hops
+fn split_hops(header: &str) -> Vec<String> {
+    let mut hops = Vec::new();
+    let mut current = String::new();
+    let mut depth = 0usize;
+    for c in header.chars() {
+        match c {
+            '(' => depth += 1,
+            ')' => depth = depth.saturating_sub(1),
+            ',' if depth == 0 => {
+                hops.push(std::mem::take(&mut current));
+                continue;
+            }
+            _ => {}
+        }
+        current.push(c);
+    }
+    hops.push(current);
+    hops
+}
+
+fn parse_hop(hop: &str) -> Option<ViaEntry> {
+    let (head, comment) = match hop.find('(') {
+        Some(open) => {
+            let close = hop
+                .rfind(')')
+                .filter(|close| *close > open)
+                .unwrap_or(hop.len());
+            let comment = hop[open + 1..close].trim();
+            (
+                &hop[..open],
+                (!comment.is_empty()).then(|| comment.to_string()),
+            )
+        }
+        None => (hop, None),
+    };
+
+    let mut words = head.split_whitespace();
+    let first = words.next()?;
+    // real proxies sometimes leave the protocol off entirely ("Via: squid")
+    let (protocol, received_by) = match words.next() {
+        Some(second) => (Some(first.to_string()), second.to_string()),
+        None => (None, first.to_string()),
+    };
+
+    Some(ViaEntry {
+        protocol,
+        received_by,
+        comment,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_hops_with_comments() {
+        assert_eq!(
+            parse_via("1.0 fred, 1.1 p.example.net (Apache/1.1, with a comma), squid"),
+            [
+                ViaEntry {
+                    protocol: Some("1.0".to_string()),
+                    received_by: "fred".to_string(),
+                    comment: None,
+                },
+                ViaEntry {
+                    protocol: Some("1.1".to_string()),
+                    received_by: "p.example.net".to_string(),
+                    comment: Some("Apache/1.1, with a comma".to_string()),
+                },
+                ViaEntry {
+                    protocol: None,
+                    received_by: "squid".to_string(),
+                    comment: None,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn skips_empty_hops_and_survives_unclosed_comments() {
+        let entries = parse_via(" , HTTP/2 edge:443 (unclosed");
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].protocol.as_deref(), Some("HTTP/2"));
+        assert_eq!(entries[0].received_by, "edge:443");
+        assert_eq!(entries[0].comment.as_deref(), Some("unclosed"));
+    }
+}
@@ -0,0 +1,37 @@
+mod common;
+
+use common::{response, Server};
+use peakrequests::PeakRequests;
+
+#[test]
+fn services_are_keyed_by_the_normalized_origin() {
+    let server = Server::start(|_| response("200 OK", &[("Alt-Svc", "h3=\":443\"; ma=60")], ""));
+    let mut client = PeakRequests::new();
+    client.get(&server.url("/a")).unwrap();
+    client
+        .get(&server.url("/%7Eb").replacen("http", "HTTP", 1))
+        .unwrap();
+
+    let known = client.known_alt_services();
+    assert_eq!(known.len(), 1);
+    let services = &known[&server.url("")];
+    assert_eq!(services[0].protocol_id, "h3");
+    assert_eq!(services[0].port, 443);
+}
+
+#[test]
+fn clear_forgets_the_origin() {
+    let server = Server::start(|request| {
+        let header = if request.path == "/clear" {
+            "clear"
+        } else {
+            "h2=\":8443\""
+        };
+        response("200 OK", &[("Alt-Svc", header)], "")
+    });
+    let mut client = PeakRequests::new();
+    client.get(&server.url("/")).unwrap();
+    assert_eq!(client.known_alt_services().len(), 1);
+    client.get(&server.url("/clear")).unwrap();
+    assert!(client.known_alt_services().is_empty());
+}
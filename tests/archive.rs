@@ -0,0 +1,99 @@
+#![cfg(feature = "json")]
+
+mod common;
+
+use common::{response_bytes, Server};
+use peakrequests::{ArchiveConfig, PeakRequests};
+use serde_json::Value;
+use sha2::Digest;
+use std::fs;
+use std::path::Path;
+use std::thread;
+use std::time::{Duration, Instant};
+
+fn records(dir: &Path, expected: u64, client: &PeakRequests) -> Vec<Value> {
+    let started = Instant::now();
+    while client.archive_stats().written < expected {
+        assert!(
+            started.elapsed() < Duration::from_secs(5),
+            "archive writer stalled"
+        );
+        thread::sleep(Duration::from_millis(10));
+    }
+    fs::read_dir(dir)
+        .unwrap()
+        .map(|entry| serde_json::from_slice(&fs::read(entry.unwrap().path()).unwrap()).unwrap())
+        .collect()
+}
+
+fn record_for<'a>(records: &'a [Value], path: &str) -> &'a Value {
+    records
+        .iter()
+        .find(|record| record["request"]["url"].as_str().unwrap().ends_with(path))
+        .unwrap()
+}
+
+#[test]
+fn bodies_are_stored_exactly() {
+    let server = Server::start(|request| match request.path.as_str() {
+        "/text" => response_bytes("200 OK", &[], "héllo".as_bytes()),
+        "/binary" => response_bytes("200 OK", &[], &[0xff, 0x00, 0xfe]),
+        _ => response_bytes("200 OK", &[], "aéééé".as_bytes()),
+    });
+    let dir = std::env::temp_dir().join(format!("peak-archive-{}", std::process::id()));
+    let mut client = PeakRequests::new().archive(ArchiveConfig {
+        dir: dir.clone(),
+        max_body: 6,
+        ..Default::default()
+    });
+
+    for path in ["/text", "/binary", "/long"] {
+        client.get(&server.url(path)).unwrap();
+    }
+    let records = records(&dir, 3, &client);
+    fs::remove_dir_all(&dir).unwrap();
+
+    let text = &record_for(&records, "/text")["response"];
+    assert_eq!(text["body"], "héllo");
+    assert_eq!(text["body_encoding"], "utf8");
+    assert_eq!(text["body_truncated"], false);
+
+    let binary = record_for(&records, "/binary");
+    assert_eq!(binary["response"]["body"], "/wD+");
+    assert_eq!(binary["response"]["body_encoding"], "base64");
+    assert_eq!(
+        binary["body_sha256"],
+        "af9ceddc9d8b08ac09e1994bfd20459b5e377425df7354dfce3501992828a5b7"
+    );
+
+    // nine bytes cut to six lands inside the third é
+    let long = &record_for(&records, "/long")["response"];
+    assert_eq!(long["body"], "aéé");
+    assert_eq!(long["body_encoding"], "utf8");
+    assert_eq!(long["body_truncated"], true);
+}
+
+#[test]
+fn spilled_bodies_are_archived_from_disk() {
+    let body: Vec<u8> = (0..256 * 1024).map(|n| (n % 7) as u8 + b'a').collect();
+    let expected_sha = format!("{:x}", sha2::Sha256::digest(&body));
+    let served = body.clone();
+    let server = Server::start(move |_| response_bytes("200 OK", &[], &served));
+    let dir = std::env::temp_dir().join(format!("peak-archive-spilled-{}", std::process::id()));
+    let mut client = PeakRequests::new()
+        .spill_to_disk_over(1024, None)
+        .archive(ArchiveConfig {
+            dir: dir.clone(),
+            max_body: 10,
+            ..Default::default()
+        });
+
+    assert!(client.get(&server.url("/big")).unwrap().is_spilled());
+    let records = records(&dir, 1, &client);
+    fs::remove_dir_all(&dir).unwrap();
+
+    let record = record_for(&records, "/big");
+    assert_eq!(record["response"]["body"], "abcdefgabc");
+    assert_eq!(record["response"]["body_truncated"], true);
+    assert_eq!(record["body_sha256"], expected_sha.as_str());
+}
@@ -0,0 +1,121 @@
+#![cfg(feature = "testing")]
+
+mod common;
+
+use common::{ok, response, Server};
+use peakrequests::testing::assertions::*;
+use peakrequests::{get, Response};
+use std::panic::{self, AssertUnwindSafe};
+
+fn json_server() -> Server {
+    Server::start(|_| {
+        response(
+            "200 OK",
+            &[
+                ("Content-Type", "application/json"),
+                ("X-Request-Id", "r-1"),
+            ],
+            r#"{"ok": true, "user": {"id": 7, "name": "sq"}, "tags": ["a", "b"]}"#,
+        )
+    })
+}
+
+fn panic_message(f: impl FnOnce()) -> String {
+    let payload = panic::catch_unwind(AssertUnwindSafe(f)).unwrap_err();
+    payload
+        .downcast_ref::<String>()
+        .cloned()
+        .or_else(|| payload.downcast_ref::<&str>().map(|s| s.to_string()))
+        .unwrap()
+}
+
+#[test]
+fn passing_assertions_chain() {
+    let server = json_server();
+    let resp = get(&server.url("/user")).unwrap();
+    resp.assert_status(200)
+        .assert_header("content-type", "application/json")
+        .assert_header("Content-Type", contains("json"))
+        .assert_header("x-request-id", present())
+        .assert_body_contains("\"id\": 7");
+}
+
+#[cfg(feature = "json")]
+#[test]
+fn json_subset_match() {
+    let server = json_server();
+    let resp = get(&server.url("/user")).unwrap();
+    resp.assert_json_matches(serde_json::json!({"ok": true, "user": {"id": 7}}));
+    resp.assert_json_matches(serde_json::json!({"tags": ["a", "b"]}));
+
+    let message = panic_message(|| {
+        resp.assert_json_matches(serde_json::json!({"user": {"id": 8}, "tags": ["a"], "gone": 1}));
+    });
+    assert!(
+        message.contains("/user/id: expected 8, got 7"),
+        "{}",
+        message
+    );
+    assert!(
+        message.contains("/tags: expected 1 elements, got 2"),
+        "{}",
+        message
+    );
+    assert!(message.contains("/gone: missing"), "{}", message);
+}
+
+#[test]
+fn failure_message_has_url_status_and_truncated_body() {
+    let body = "x".repeat(500);
+    let long = body.clone();
+    let server = Server::start(move |_| response("404 Not Found", &[], &long));
+    let url = server.url("/missing");
+    let resp: Response = get(&url).unwrap();
+
+    let message = panic_message(|| {
+        resp.assert_status(200);
+    });
+    assert!(message.starts_with("expected status 200"), "{}", message);
+    assert!(message.contains(&format!("url: {}", url)), "{}", message);
+    assert!(message.contains("status: 404"), "{}", message);
+    assert!(
+        message.contains(&format!("body: {}...", "x".repeat(200))),
+        "{}",
+        message
+    );
+    assert!(!message.contains(&body), "body should be truncated");
+}
+
+#[test]
+fn header_and_body_failures() {
+    let server = Server::start(|_| ok("hello"));
+    let resp = get(&server.url("/")).unwrap();
+
+    let message = panic_message(|| {
+        resp.assert_header("x-missing", present());
+    });
+    assert!(
+        message.starts_with("header x-missing is missing"),
+        "{}",
+        message
+    );
+
+    let message = panic_message(|| {
+        resp.assert_header("content-length", equals("6"));
+    });
+    assert!(
+        message.contains("does not match Equals(\"6\")"),
+        "{}",
+        message
+    );
+
+    let message = panic_message(|| {
+        resp.assert_body_contains("bye");
+    });
+    assert!(
+        message.starts_with("body does not contain \"bye\""),
+        "{}",
+        message
+    );
+    assert!(message.contains("body: hello"), "{}", message);
+}
@@ -0,0 +1,171 @@
+mod common;
+
+use common::{ok, response, Server};
+use peakrequests::{AuthScheme, PeakRequests, TokenProvider, TokenRequestReason};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Barrier};
+use std::thread;
+use std::time::Duration;
+
+fn digest_param<'a>(header: &'a str, name: &str) -> Option<&'a str> {
+    header
+        .strip_prefix("Digest ")?
+        .split(", ")
+        .filter_map(|param| param.split_once('='))
+        .find(|(key, _)| *key == name)
+        .map(|(_, value)| value.trim_matches('"'))
+}
+
+#[test]
+fn basic_is_sent_up_front() {
+    let server = Server::start(|_| ok("in"));
+
+    let resp = PeakRequests::new()
+        .auth(AuthScheme::basic("aladdin", "opensesame"))
+        .get(&server.url("/"))
+        .unwrap();
+    assert_eq!(resp.status_code, 200);
+
+    let requests = server.requests();
+    assert_eq!(requests.len(), 1);
+    assert_eq!(
+        requests[0].header("authorization"),
+        Some("Basic YWxhZGRpbjpvcGVuc2VzYW1l")
+    );
+}
+
+#[test]
+fn basic_waits_for_a_challenge_when_not_preemptive() {
+    let server = Server::start(|request| match request.header("authorization") {
+        Some(_) => ok("in"),
+        None => response(
+            "401 Unauthorized",
+            &[("WWW-Authenticate", "Basic realm=\"site\"")],
+            "",
+        ),
+    });
+
+    let resp = PeakRequests::new()
+        .auth(AuthScheme::Basic {
+            username: "aladdin".to_string(),
+            password: "opensesame".to_string(),
+            preemptive: false,
+        })
+        .get(&server.url("/"))
+        .unwrap();
+    assert_eq!(resp.status_code, 200);
+
+    let requests = server.requests();
+    assert_eq!(requests.len(), 2);
+    assert_eq!(requests[0].header("authorization"), None);
+    assert_eq!(
+        requests[1].header("authorization"),
+        Some("Basic YWxhZGRpbjpvcGVuc2VzYW1l")
+    );
+}
+
+#[test]
+fn digest_answers_the_challenge_and_a_stale_nonce() {
+    let server = Server::start(|request| {
+        let nonce = request
+            .header("authorization")
+            .and_then(|header| digest_param(header, "nonce"));
+        match nonce {
+            Some("second") => ok("in"),
+            Some(_) => response(
+                "401 Unauthorized",
+                &[(
+                    "WWW-Authenticate",
+                    "Digest realm=\"api\", nonce=\"second\", qop=\"auth\", stale=true",
+                )],
+                "",
+            ),
+            None => response(
+                "401 Unauthorized",
+                &[(
+                    "WWW-Authenticate",
+                    "Digest realm=\"api\", nonce=\"first\", qop=\"auth\", opaque=\"o\"",
+                )],
+                "",
+            ),
+        }
+    });
+
+    let resp = PeakRequests::new()
+        .auth(AuthScheme::digest("user", "pass"))
+        .get(&server.url("/items?page=2"))
+        .unwrap();
+    assert_eq!(resp.status_code, 200);
+
+    let requests = server.requests();
+    assert_eq!(requests.len(), 3);
+    let answer = requests[1].header("authorization").unwrap();
+    assert_eq!(digest_param(answer, "username"), Some("user"));
+    assert_eq!(digest_param(answer, "realm"), Some("api"));
+    assert_eq!(digest_param(answer, "nonce"), Some("first"));
+    assert_eq!(digest_param(answer, "uri"), Some("/items?page=2"));
+    assert_eq!(digest_param(answer, "opaque"), Some("o"));
+    assert_eq!(digest_param(answer, "nc"), Some("00000001"));
+    let retry = requests[2].header("authorization").unwrap();
+    assert_eq!(digest_param(retry, "nonce"), Some("second"));
+}
+
+#[test]
+fn a_rejected_answer_comes_back_as_is() {
+    let server = Server::start(|_| {
+        response(
+            "401 Unauthorized",
+            &[("WWW-Authenticate", "Digest realm=\"api\", nonce=\"n\"")],
+            "no",
+        )
+    });
+
+    let resp = PeakRequests::new()
+        .auth(AuthScheme::digest("user", "wrong"))
+        .get(&server.url("/"))
+        .unwrap();
+    assert_eq!(resp.status_code, 401);
+    assert_eq!(resp.www_authenticate()[0].params["nonce"], "n");
+    assert_eq!(server.requests().len(), 2);
+}
+
+#[test]
+fn a_burst_of_401s_refreshes_the_token_once() {
+    let server = Server::start(|request| match request.header("authorization") {
+        Some("Bearer fresh") => ok("in"),
+        _ => response("401 Unauthorized", &[], ""),
+    });
+    let initial = Arc::new(AtomicUsize::new(0));
+    let refreshed = Arc::new(AtomicUsize::new(0));
+    let (counted_initial, counted_refreshed) = (Arc::clone(&initial), Arc::clone(&refreshed));
+    let provider = TokenProvider::new(move |reason| match reason {
+        TokenRequestReason::Initial => {
+            counted_initial.fetch_add(1, Ordering::SeqCst);
+            Ok("stale".to_string())
+        }
+        TokenRequestReason::Unauthorized => {
+            counted_refreshed.fetch_add(1, Ordering::SeqCst);
+            // long enough for every other thread to pile up behind it
+            thread::sleep(Duration::from_millis(200));
+            Ok("fresh".to_string())
+        }
+    });
+
+    let barrier = Arc::new(Barrier::new(10));
+    let url = server.url("/private");
+    let threads: Vec<_> = (0..10)
+        .map(|_| {
+            let (provider, barrier, url) = (provider.clone(), Arc::clone(&barrier), url.clone());
+            thread::spawn(move || {
+                let mut client = PeakRequests::new().token_provider(provider);
+                barrier.wait();
+                client.get(&url).unwrap().text().to_string()
+            })
+        })
+        .collect();
+    for thread in threads {
+        assert_eq!(thread.join().unwrap(), "in");
+    }
+    assert_eq!(initial.load(Ordering::SeqCst), 1);
+    assert_eq!(refreshed.load(Ordering::SeqCst), 1);
+}
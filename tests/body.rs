@@ -0,0 +1,82 @@
+mod common;
+
+use common::{ok, Server};
+use peakrequests::PeakRequests;
+use std::io::Read;
+
+#[test]
+fn text_decodes_by_charset_and_bytes_stay_raw() {
+    let server = Server::start(|_| {
+        let mut out = b"HTTP/1.1 200 OK\r\nConnection: close\r\n\
+            Content-Type: text/plain; charset=\"ISO-8859-1\"\r\nContent-Length: 4\r\n\r\ncaf"
+            .to_vec();
+        out.push(0xe9);
+        out
+    });
+
+    let resp = PeakRequests::new().get(&server.url("/")).unwrap();
+    assert_eq!(&resp.bytes_ref()[..], b"caf\xe9");
+    assert_eq!(resp.text(), "café");
+}
+
+#[test]
+fn bytes_share_the_body() {
+    let server = Server::start(|_| ok("shared body"));
+
+    let resp = PeakRequests::new().get(&server.url("/")).unwrap();
+    let forwarded = resp.bytes();
+    assert_eq!(forwarded.as_ptr(), resp.bytes_ref().as_ptr());
+    assert_eq!(forwarded.slice(0..6).as_ptr(), resp.bytes_ref().as_ptr());
+    assert_eq!(resp.text(), "shared body");
+}
+
+#[test]
+fn stream_chunks_add_up_to_the_body() {
+    let body: String = (0..200_000)
+        .map(|i| (b'a' + (i % 26) as u8) as char)
+        .collect();
+    let expected = body.clone();
+    let server = Server::start(move |_| ok(&body));
+
+    let stream = PeakRequests::new().get_stream(&server.url("/")).unwrap();
+    let mut received = Vec::new();
+    let mut chunks = 0;
+    for chunk in stream {
+        let chunk = chunk.unwrap();
+        assert!(!chunk.is_empty());
+        received.extend_from_slice(&chunk);
+        chunks += 1;
+    }
+    assert!(chunks > 1);
+    assert_eq!(received, expected.as_bytes());
+}
+
+#[test]
+fn stream_reads_count_against_the_length() {
+    let server = Server::start(|_| ok("0123456789"));
+
+    let mut stream = PeakRequests::new().get_stream(&server.url("/")).unwrap();
+    assert_eq!(stream.status_code, 200);
+    assert_eq!(stream.content_length(), Some(10));
+
+    let mut head = [0u8; 4];
+    stream.read_exact(&mut head).unwrap();
+    assert_eq!(&head, b"0123");
+    assert_eq!(stream.bytes_read(), 4);
+
+    let mut rest = String::new();
+    stream.read_to_string(&mut rest).unwrap();
+    assert_eq!(rest, "456789");
+    assert_eq!(stream.bytes_read(), 10);
+    assert!(stream.next().is_none());
+}
+
+#[test]
+fn closing_a_stream_leaves_the_client_usable() {
+    let server = Server::start(|_| ok("unread"));
+    let mut client = PeakRequests::new();
+
+    client.get_stream(&server.url("/")).unwrap().close();
+    let resp = client.get(&server.url("/")).unwrap();
+    assert_eq!(resp.text(), "unread");
+}
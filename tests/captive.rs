@@ -0,0 +1,77 @@
+#![cfg(feature = "json")]
+
+mod common;
+
+use common::{ok, response, Server};
+use peakrequests::{PeakError, PeakRequests};
+
+const PORTAL: &str = r#"<!DOCTYPE html><html><head>
+<meta http-equiv="refresh" content="0; url=/login?next=%2F">
+</head><body><form action="https://portal.example/form"></form></body></html>"#;
+
+fn fetch_json(client: &mut PeakRequests, url: &str) -> Result<serde_json::Value, PeakError> {
+    client
+        .request("GET", url)
+        .header("Accept", "application/json")
+        .send()?
+        .json()
+}
+
+#[test]
+fn an_html_answer_to_a_json_request_is_flagged() {
+    let server = Server::start(|_| ok(PORTAL));
+    let mut client = PeakRequests::new().detect_captive_portal(true);
+    let error = fetch_json(&mut client, &server.url("/api/items")).unwrap_err();
+    let PeakError::LikelyCaptivePortal { portal_url_hint } = error else {
+        panic!("expected LikelyCaptivePortal, got {:?}", error);
+    };
+    assert_eq!(portal_url_hint, Some(server.url("/login?next=%2F")));
+}
+
+#[test]
+fn the_form_action_is_the_fallback_hint() {
+    let server = Server::start(|_| {
+        response(
+            "200 OK",
+            &[("Content-Type", "text/html")],
+            r#"<html><form method="post" action="/accept-terms"></form></html>"#,
+        )
+    });
+    let mut client = PeakRequests::new().detect_captive_portal(true);
+    let error = fetch_json(&mut client, &server.url("/api")).unwrap_err();
+    assert!(
+        matches!(&error, PeakError::LikelyCaptivePortal { portal_url_hint: Some(hint) } if *hint == server.url("/accept-terms")),
+        "{:?}",
+        error
+    );
+}
+
+#[test]
+fn only_requests_asking_for_json_are_checked() {
+    let server = Server::start(|_| ok(PORTAL));
+    let mut client = PeakRequests::new().detect_captive_portal(true);
+    let error = client
+        .get(&server.url("/page"))
+        .unwrap()
+        .json()
+        .unwrap_err();
+    assert!(matches!(error, PeakError::Json(_)), "{:?}", error);
+}
+
+#[test]
+fn off_unless_asked_for() {
+    let server = Server::start(|_| ok(PORTAL));
+    let mut client = PeakRequests::new();
+    let error = fetch_json(&mut client, &server.url("/api")).unwrap_err();
+    assert!(matches!(error, PeakError::Json(_)), "{:?}", error);
+}
+
+#[test]
+fn real_json_passes() {
+    let server = Server::start(|_| ok(r#"{"ok": true}"#));
+    let mut client = PeakRequests::new().detect_captive_portal(true);
+    assert_eq!(
+        fetch_json(&mut client, &server.url("/api")).unwrap()["ok"],
+        true
+    );
+}
@@ -0,0 +1,123 @@
+mod common;
+
+use common::{response, Server};
+use peakrequests::{CaptureRequirements, PeakError, PeakRequests};
+use std::sync::{Arc, Mutex};
+
+fn site() -> Server {
+    Server::start(|_| {
+        response(
+            "201 Created",
+            &[("X-Request-Id", "r-1")],
+            "0123456789abcdef",
+        )
+    })
+}
+
+#[derive(Debug, Default, Clone, PartialEq)]
+struct Seen {
+    status: u16,
+    method: String,
+    url: String,
+    request_id: Option<String>,
+    body: Option<Vec<u8>>,
+}
+
+fn recorder(
+    client: PeakRequests,
+    requirements: CaptureRequirements,
+) -> (PeakRequests, Arc<Mutex<Vec<Seen>>>) {
+    let seen = Arc::new(Mutex::new(Vec::new()));
+    let log = Arc::clone(&seen);
+    let client = client.on_capture(requirements, move |capture| {
+        log.lock().unwrap().push(Seen {
+            status: capture.status(),
+            method: capture.method().to_string(),
+            url: capture.display_url(),
+            request_id: capture
+                .headers()
+                .and_then(|headers| headers.get("x-request-id").cloned()),
+            body: capture.body().map(|body| body.to_vec()),
+        });
+    });
+    (client, seen)
+}
+
+#[test]
+fn nothing_is_captured_without_hooks() {
+    let server = site();
+    let mut client = PeakRequests::new();
+    client.get(&server.url("/")).unwrap();
+    assert_eq!(client.capture_stats().captures, 0);
+}
+
+#[test]
+fn a_status_hook_sees_only_the_basics() {
+    let server = site();
+    let (mut client, seen) = recorder(PeakRequests::new(), CaptureRequirements::STATUS);
+    client.get(&server.url("/items?token=abc")).unwrap();
+
+    let seen = seen.lock().unwrap();
+    assert_eq!(seen.len(), 1);
+    assert_eq!(seen[0].status, 201);
+    assert_eq!(seen[0].method, "GET");
+    assert!(seen[0].url.ends_with("/items?token=abc"));
+    assert_eq!(seen[0].request_id, None);
+    assert_eq!(seen[0].body, None);
+
+    let stats = client.capture_stats();
+    assert_eq!(stats.captures, 1);
+    assert_eq!(stats.header_bytes + stats.body_bytes, 0);
+}
+
+#[test]
+fn each_hook_gets_what_it_asked_for() {
+    let server = site();
+    let (client, headers) = recorder(PeakRequests::new(), CaptureRequirements::HEADERS);
+    let (client, short) = recorder(client, CaptureRequirements::body(4));
+    let (mut client, long) = recorder(
+        client,
+        CaptureRequirements::HEADERS | CaptureRequirements::body(10),
+    );
+    client.get(&server.url("/")).unwrap();
+
+    let headers = &headers.lock().unwrap()[0];
+    assert_eq!(headers.request_id.as_deref(), Some("r-1"));
+    assert_eq!(headers.body, None);
+    let short = &short.lock().unwrap()[0];
+    assert_eq!(short.request_id, None);
+    assert_eq!(short.body.as_deref(), Some(&b"0123"[..]));
+    let long = &long.lock().unwrap()[0];
+    assert_eq!(long.request_id.as_deref(), Some("r-1"));
+    assert_eq!(long.body.as_deref(), Some(&b"0123456789"[..]));
+
+    // the body is read once, for the largest limit
+    let stats = client.capture_stats();
+    assert_eq!(stats.body_bytes, 10);
+    assert!(stats.header_bytes > 0);
+}
+
+#[test]
+fn a_spilled_body_is_read_only_as_far_as_asked() {
+    let server = Server::start(|_| common::ok(&"x".repeat(64 * 1024)));
+    let (mut client, seen) = recorder(
+        PeakRequests::new().spill_to_disk_over(1024, None),
+        CaptureRequirements::body(3),
+    );
+    assert!(client.get(&server.url("/")).unwrap().is_spilled());
+    assert_eq!(seen.lock().unwrap()[0].body.as_deref(), Some(&b"xxx"[..]));
+    assert_eq!(client.capture_stats().body_bytes, 3);
+}
+
+#[test]
+fn a_panicking_hook_becomes_an_error() {
+    let server = site();
+    let mut client =
+        PeakRequests::new().on_capture(CaptureRequirements::STATUS, |_| panic!("metrics down"));
+    let error = client.get(&server.url("/")).unwrap_err();
+    assert!(
+        matches!(&error, PeakError::HookPanicked { hook: "on_capture", message } if message == "metrics down"),
+        "{:?}",
+        error
+    );
+}
@@ -0,0 +1,78 @@
+// one test on its own: the counting allocator sees every thread in the process
+mod common;
+
+use common::{response_bytes, Server};
+use peakrequests::{CaptureRequirements, PeakRequests};
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+struct Counting;
+
+static ALLOCATED: AtomicU64 = AtomicU64::new(0);
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATED.fetch_add(layout.size() as u64, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: Counting = Counting;
+
+const BODY: usize = 1024 * 1024;
+const ROUNDS: u64 = 8;
+// buffer growth varies run to run, a copy of the body would still be twice this
+const SLACK: u64 = BODY as u64 / 2;
+
+fn allocated_per_request(mut client: PeakRequests, server: &Server) -> u64 {
+    client.get(&server.url("/")).unwrap();
+    let before = ALLOCATED.load(Ordering::Relaxed);
+    for _ in 0..ROUNDS {
+        client.get(&server.url("/")).unwrap();
+    }
+    (ALLOCATED.load(Ordering::Relaxed) - before) / ROUNDS
+}
+
+#[test]
+fn capturing_does_not_copy_the_body() {
+    let body = vec![b'x'; BODY];
+    let server = Server::start(move |_| response_bytes("200 OK", &[], &body));
+
+    let none = allocated_per_request(PeakRequests::new(), &server);
+    let status = allocated_per_request(
+        PeakRequests::new().on_capture(CaptureRequirements::STATUS, |capture| {
+            assert_eq!(capture.status(), 200);
+        }),
+        &server,
+    );
+    let full = allocated_per_request(
+        PeakRequests::new().on_capture(CaptureRequirements::body(usize::MAX), |capture| {
+            assert_eq!(capture.body().unwrap().len(), BODY);
+        }),
+        &server,
+    );
+    assert!(status < none + SLACK, "{} vs {}", status, none);
+    // an in-memory body is handed out as a slice, not a copy
+    assert!(full < none + SLACK, "{} vs {}", full, none);
+
+    // a spilled one has to be read back, which is what the budget saves
+    let spilled = |requirements| {
+        PeakRequests::new()
+            .spill_to_disk_over(1024, None)
+            .on_capture(requirements, |_| {})
+    };
+    let spilled_status = allocated_per_request(spilled(CaptureRequirements::STATUS), &server);
+    let spilled_full =
+        allocated_per_request(spilled(CaptureRequirements::body(usize::MAX)), &server);
+    assert!(
+        spilled_full >= spilled_status + BODY as u64,
+        "{} vs {}",
+        spilled_full,
+        spilled_status
+    );
+}
@@ -0,0 +1,51 @@
+#![cfg(feature = "testing")]
+
+mod common;
+
+use common::{ok, Server};
+use peakrequests::testing::clock::FakeClock;
+use peakrequests::{MirrorConfig, PeakRequests};
+use std::time::{Duration, Instant};
+
+#[test]
+fn probes_wait_on_the_fake_clock() {
+    let server = Server::start(|_| ok("pong"));
+    let clock = FakeClock::new();
+    let mut client = PeakRequests::new().with_clock(clock.clone());
+
+    let started = Instant::now();
+    let results = client
+        .probe(&server.url("/"), 3, Duration::from_secs(60))
+        .unwrap();
+    assert!(started.elapsed() < Duration::from_secs(5));
+    assert_eq!(results.len(), 3);
+    assert!(results.iter().all(|result| result.status == Some(200)));
+    assert_eq!(clock.elapsed(), Duration::from_secs(120));
+}
+
+// which of 20 requests the mirror picked, with the client seeded before mirror()
+fn mirrored_paths(seed: u64) -> Vec<String> {
+    let primary = Server::start(|_| ok("primary"));
+    let target = Server::start(|_| ok("mirror"));
+    let mut client = PeakRequests::new().with_seed(seed).mirror(MirrorConfig {
+        target_base_url: target.url(""),
+        sample_rate: 0.5,
+        ..MirrorConfig::default()
+    });
+    for i in 0..20 {
+        client.get(&primary.url(&format!("/{}", i))).unwrap();
+    }
+    client.shutdown(Duration::from_secs(5));
+
+    let mut paths: Vec<String> = target.requests().into_iter().map(|r| r.path).collect();
+    paths.sort();
+    paths
+}
+
+#[test]
+fn a_seeded_client_samples_the_same_requests() {
+    let first = mirrored_paths(7);
+    assert!(!first.is_empty() && first.len() < 20, "{:?}", first);
+    assert_eq!(mirrored_paths(7), first);
+    assert_ne!(mirrored_paths(8), first);
+}
@@ -0,0 +1,125 @@
+// a tiny http/1.1 server on 127.0.0.1 for the integration tests. one request per
+// connection, the handler's answer is written back verbatim.
+#![allow(dead_code)]
+
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+#[derive(Debug, Clone)]
+pub struct Request {
+    pub method: String,
+    pub path: String,
+    // names lowercased, in the order they came
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+impl Request {
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+pub struct Server {
+    pub addr: SocketAddr,
+    seen: Arc<Mutex<Vec<Request>>>,
+}
+
+impl Server {
+    // the handler can answer with a String or raw bytes
+    pub fn start<R: AsRef<[u8]>>(
+        handler: impl Fn(&Request) -> R + Send + Sync + 'static,
+    ) -> Server {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let log = Arc::clone(&seen);
+        let handler = Arc::new(handler);
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                let Ok(stream) = stream else { continue };
+                let log = Arc::clone(&log);
+                let handler = Arc::clone(&handler);
+                thread::spawn(move || serve(stream, &*handler, &log));
+            }
+        });
+        Server { addr, seen }
+    }
+
+    pub fn url(&self, path: &str) -> String {
+        format!("http://{}{}", self.addr, path)
+    }
+
+    pub fn requests(&self) -> Vec<Request> {
+        self.seen.lock().unwrap().clone()
+    }
+}
+
+fn serve<R: AsRef<[u8]>>(
+    stream: TcpStream,
+    handler: &dyn Fn(&Request) -> R,
+    log: &Mutex<Vec<Request>>,
+) {
+    let mut reader = BufReader::new(stream.try_clone().unwrap());
+    let mut line = String::new();
+    if reader.read_line(&mut line).unwrap_or(0) == 0 {
+        return;
+    }
+    let mut parts = line.split_whitespace();
+    let method = parts.next().unwrap_or_default().to_string();
+    let path = parts.next().unwrap_or_default().to_string();
+
+    let mut headers = Vec::new();
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line).unwrap_or(0) == 0 || line == "\r\n" {
+            break;
+        }
+        if let Some((key, value)) = line.trim_end().split_once(':') {
+            headers.push((key.trim().to_ascii_lowercase(), value.trim().to_string()));
+        }
+    }
+    let length = headers
+        .iter()
+        .find(|(key, _)| key == "content-length")
+        .and_then(|(_, value)| value.parse().ok())
+        .unwrap_or(0);
+    let mut body = vec![0; length];
+    let _ = reader.read_exact(&mut body);
+
+    let request = Request {
+        method,
+        path,
+        headers,
+        body,
+    };
+    log.lock().unwrap().push(request.clone());
+    let response = handler(&request);
+    let mut stream = stream;
+    let _ = stream.write_all(response.as_ref());
+}
+
+pub fn response(status: &str, headers: &[(&str, &str)], body: &str) -> String {
+    String::from_utf8(response_bytes(status, headers, body.as_bytes())).unwrap()
+}
+
+// same, for bodies that aren't utf-8
+pub fn response_bytes(status: &str, headers: &[(&str, &str)], body: &[u8]) -> Vec<u8> {
+    let mut out = format!("HTTP/1.1 {}\r\nConnection: close\r\n", status);
+    for (key, value) in headers {
+        out.push_str(&format!("{}: {}\r\n", key, value));
+    }
+    out.push_str(&format!("Content-Length: {}\r\n\r\n", body.len()));
+    let mut out = out.into_bytes();
+    out.extend_from_slice(body);
+    out
+}
+
+pub fn ok(body: &str) -> String {
+    response("200 OK", &[], body)
+}
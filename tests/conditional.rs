@@ -0,0 +1,101 @@
+mod common;
+
+use common::{ok, response, Server};
+use peakrequests::{FetchResult, PeakRequests, Validators};
+
+// answers 304 while the client still has version 1, the current version otherwise
+fn versioned(current: &'static str) -> Server {
+    Server::start(move |request| {
+        let etag = format!("\"{}\"", current);
+        if request.header("if-none-match") == Some(etag.as_str()) {
+            return response("304 Not Modified", &[("ETag", &etag)], "");
+        }
+        response(
+            "200 OK",
+            &[("ETag", &etag)],
+            &format!("version {}", current),
+        )
+    })
+}
+
+fn known_etag(etag: &str) -> Validators {
+    Validators {
+        etag: Some(etag.to_string()),
+        ..Validators::default()
+    }
+}
+
+#[test]
+fn a_304_is_not_modified() {
+    let server = versioned("1");
+    let mut client = PeakRequests::new();
+    let result = client
+        .fetch_if_changed(&server.url("/doc"), known_etag("\"1\""))
+        .unwrap();
+    assert!(matches!(result, FetchResult::NotModified), "{:?}", result);
+    assert_eq!(server.requests()[0].header("if-none-match"), Some("\"1\""));
+}
+
+#[test]
+fn a_changed_resource_is_fetched_with_its_new_validators() {
+    let server = versioned("2");
+    let mut client = PeakRequests::new();
+    let result = client
+        .fetch_if_changed(&server.url("/doc"), known_etag("\"1\""))
+        .unwrap();
+    let FetchResult::Fetched(response) = result else {
+        panic!("expected the new version, got {:?}", result);
+    };
+    assert_eq!(response.text(), "version 2");
+    assert_eq!(
+        Validators::from_response(&response).etag.as_deref(),
+        Some("\"2\"")
+    );
+}
+
+#[test]
+fn a_server_ignoring_conditionals_is_still_not_modified() {
+    let server = Server::start(|_| {
+        response(
+            "200 OK",
+            &[("Last-Modified", "Tue, 01 Oct 2024 00:00:00 GMT")],
+            "same",
+        )
+    });
+    let known = Validators {
+        last_modified: Some("Tue, 01 Oct 2024 00:00:00 GMT".to_string()),
+        ..Validators::default()
+    };
+    let mut client = PeakRequests::new();
+    let result = client.fetch_if_changed(&server.url("/doc"), known).unwrap();
+    assert!(matches!(result, FetchResult::NotModified), "{:?}", result);
+    assert_eq!(
+        server.requests()[0].header("if-modified-since"),
+        Some("Tue, 01 Oct 2024 00:00:00 GMT")
+    );
+}
+
+#[test]
+fn only_a_length_probes_with_head_first() {
+    let server = Server::start(|_| ok("12345"));
+    let mut client = PeakRequests::new();
+
+    let same = Validators {
+        content_length: Some(5),
+        ..Validators::default()
+    };
+    let result = client.fetch_if_changed(&server.url("/doc"), same).unwrap();
+    assert!(matches!(result, FetchResult::NotModified), "{:?}", result);
+
+    let different = Validators {
+        content_length: Some(4),
+        ..Validators::default()
+    };
+    let result = client
+        .fetch_if_changed(&server.url("/doc"), different)
+        .unwrap();
+    assert!(matches!(result, FetchResult::Fetched(_)), "{:?}", result);
+
+    let methods: Vec<String> = server.requests().into_iter().map(|r| r.method).collect();
+    assert_eq!(methods, ["HEAD", "HEAD", "GET"]);
+}
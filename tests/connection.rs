@@ -0,0 +1,30 @@
+mod common;
+
+use common::{ok, Server};
+use peakrequests::PeakRequests;
+
+// its own test binary: the proxy comes from the environment, which is process-wide
+#[test]
+fn one_off_connection_keeps_the_environment_proxy() {
+    let proxy = Server::start(|req| ok(&format!("proxied {}", req.path)));
+    for name in ["NO_PROXY", "no_proxy", "HTTP_PROXY"] {
+        std::env::remove_var(name);
+    }
+    std::env::set_var("http_proxy", proxy.url(""));
+
+    let mut client = PeakRequests::new();
+    let resp = client
+        .request("GET", "http://origin.invalid/once")
+        .connection_close()
+        .send()
+        .unwrap();
+    assert_eq!(resp.text(), "proxied http://origin.invalid/once");
+
+    let resp = client
+        .request("GET", &proxy.url("/direct"))
+        .proxy(None)
+        .connection_close()
+        .send()
+        .unwrap();
+    assert_eq!(resp.text(), "proxied /direct");
+}
@@ -0,0 +1,79 @@
+#![cfg(feature = "json")]
+
+mod common;
+
+use common::{response, Server};
+use peakrequests::{PeakError, PeakRequests};
+
+fn serving(content_type: Option<&'static str>) -> Server {
+    Server::start(move |_| match content_type {
+        Some(content_type) => response("200 OK", &[("Content-Type", content_type)], r#"{"a": 1}"#),
+        None => response("200 OK", &[], r#"{"a": 1}"#),
+    })
+}
+
+#[test]
+fn lenient_by_default() {
+    let server = serving(Some("text/html"));
+    let resp = PeakRequests::new().get(&server.url("/")).unwrap();
+    assert_eq!(resp.json().unwrap()["a"], 1);
+}
+
+#[test]
+fn strict_refuses_a_mismatched_type() {
+    let server = serving(Some("text/html; charset=utf-8"));
+    let resp = PeakRequests::new()
+        .strict_content_type(true)
+        .get(&server.url("/"))
+        .unwrap();
+    let error = resp.json().unwrap_err();
+    let PeakError::UnexpectedContentType {
+        expected,
+        got,
+        body_snippet,
+    } = &error
+    else {
+        panic!("expected UnexpectedContentType, got {:?}", error);
+    };
+    assert_eq!(expected, "application/json");
+    assert_eq!(got.as_deref(), Some("text/html"));
+    assert_eq!(body_snippet, r#"{"a": 1}"#);
+}
+
+#[test]
+fn strict_refuses_a_missing_type() {
+    let server = serving(None);
+    let resp = PeakRequests::new()
+        .strict_content_type(true)
+        .get(&server.url("/"))
+        .unwrap();
+    assert!(matches!(
+        resp.json(),
+        Err(PeakError::UnexpectedContentType { got: None, .. })
+    ));
+}
+
+#[test]
+fn strict_takes_json_and_its_suffixes() {
+    for content_type in [
+        "application/json",
+        "application/vnd.api+json; charset=utf-8",
+    ] {
+        let server = serving(Some(content_type));
+        let resp = PeakRequests::new()
+            .strict_content_type(true)
+            .get(&server.url("/"))
+            .unwrap();
+        assert_eq!(resp.json().unwrap()["a"], 1, "{}", content_type);
+    }
+}
+
+#[test]
+fn json_strict_checks_without_the_client_setting() {
+    let server = serving(Some("text/plain"));
+    let resp = PeakRequests::new().get(&server.url("/")).unwrap();
+    assert!(matches!(
+        resp.json_strict(),
+        Err(PeakError::UnexpectedContentType { .. })
+    ));
+}
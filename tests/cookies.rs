@@ -0,0 +1,49 @@
+mod common;
+
+use common::{response, Server};
+use peakrequests::PeakRequests;
+
+// stands in as a proxy so the cookies can come from made-up hostnames
+fn cookies_for(host: &str, set_cookies: &'static [&'static str]) -> Vec<(String, String, bool)> {
+    let proxy = Server::start(move |_| {
+        let headers: Vec<(&str, &str)> = set_cookies.iter().map(|c| ("Set-Cookie", *c)).collect();
+        response("200 OK", &headers, "ok")
+    });
+    let mut client = PeakRequests::new().proxy(&proxy.url(""));
+    let resp = client.get(&format!("http://{}/", host)).unwrap();
+    resp.cookies
+        .into_iter()
+        .map(|cookie| (cookie.name, cookie.domain, cookie.host_only))
+        .collect()
+}
+
+#[test]
+fn public_suffix_domains_are_refused() {
+    let cookies = cookies_for(
+        "www.example.com",
+        &[
+            "tld=1; Domain=com",
+            "dot=1; Domain=.com",
+            "site=1; Domain=example.com",
+        ],
+    );
+    assert_eq!(
+        cookies,
+        [("site".to_string(), "example.com".to_string(), false)]
+    );
+
+    let cookies = cookies_for(
+        "shop.example.co.uk",
+        &["cc=1; Domain=co.uk", "site=1; Domain=example.co.uk"],
+    );
+    assert_eq!(
+        cookies,
+        [("site".to_string(), "example.co.uk".to_string(), false)]
+    );
+}
+
+#[test]
+fn suffix_naming_the_host_itself_stays_host_only() {
+    let cookies = cookies_for("intranet", &["a=1; Domain=intranet"]);
+    assert_eq!(cookies, [("a".to_string(), "intranet".to_string(), true)]);
+}
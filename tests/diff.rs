@@ -0,0 +1,79 @@
+mod common;
+
+use common::{response_bytes, Server};
+use peakrequests::{BodyComparison, BodyDiff, Change, DiffOptions, PeakRequests, Response};
+
+fn fetch(answer: Vec<u8>) -> Response {
+    let server = Server::start(move |_| answer.clone());
+    PeakRequests::new().get(&server.url("/")).unwrap()
+}
+
+#[test]
+fn reports_status_and_header_changes() {
+    let old = fetch(response_bytes(
+        "200 OK",
+        &[("Date", "Mon"), ("X-Version", "1"), ("X-Old", "gone")],
+        b"same",
+    ));
+    let new = fetch(response_bytes(
+        "503 Service Unavailable",
+        &[("Date", "Tue"), ("X-Version", "2"), ("X-New", "here")],
+        b"same",
+    ));
+
+    let diff = old.diff(&new, DiffOptions::new());
+    assert!(!diff.is_same());
+    assert_eq!(diff.status, Some(Change { from: 200, to: 503 }));
+    assert_eq!(diff.headers_removed["x-old"], "gone");
+    assert_eq!(diff.headers_added["x-new"], "here");
+    assert_eq!(
+        diff.headers_changed["x-version"],
+        Change {
+            from: "1".to_string(),
+            to: "2".to_string()
+        }
+    );
+    assert!(!diff.headers_changed.contains_key("date"));
+    assert_eq!(diff.body, BodyDiff::Same);
+
+    let diff = old.diff(&old, DiffOptions::new());
+    assert!(diff.is_same());
+}
+
+#[test]
+fn bodies_compare_as_bytes() {
+    let old = fetch(response_bytes("200 OK", &[], b"\xff\xfe"));
+    let new = fetch(response_bytes("200 OK", &[], b"\xfe\xff"));
+    assert_eq!(old.text(), new.text());
+
+    assert_eq!(
+        old.diff(&new, DiffOptions::new()).body,
+        BodyDiff::Changed {
+            from_len: 2,
+            to_len: 2
+        }
+    );
+    assert!(matches!(
+        old.diff(&new, DiffOptions::new().body(BodyComparison::HashOnly))
+            .body,
+        BodyDiff::HashChanged { .. }
+    ));
+    assert_eq!(
+        old.diff(&old, DiffOptions::new().body(BodyComparison::HashOnly))
+            .body,
+        BodyDiff::Same
+    );
+}
+
+#[cfg(feature = "json")]
+#[test]
+fn normalized_json_ignores_key_order_and_spacing() {
+    let old = fetch(response_bytes("200 OK", &[], br#"{"a": 1, "b": [1, 2]}"#));
+    let new = fetch(response_bytes("200 OK", &[], br#"{"b":[1,2],"a":1}"#));
+    let changed = fetch(response_bytes("200 OK", &[], br#"{"b":[2,1],"a":1}"#));
+    let options = || DiffOptions::new().body(BodyComparison::NormalizedJson);
+
+    assert_eq!(old.diff(&new, options()).body, BodyDiff::Same);
+    assert_ne!(old.diff(&changed, options()).body, BodyDiff::Same);
+    assert_ne!(old.diff(&new, DiffOptions::new()).body, BodyDiff::Same);
+}
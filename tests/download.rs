@@ -0,0 +1,43 @@
+use peakrequests::{BulkOptions, DownloadJob, DownloadOutcome, PeakError, PeakRequests};
+
+fn destination(name: &str) -> std::path::PathBuf {
+    std::env::temp_dir().join(format!("peak-download-{}-{}", name, std::process::id()))
+}
+
+#[test]
+fn failures_keep_their_type() {
+    let outcomes = PeakRequests::new().download_all(
+        vec![DownloadJob::new(
+            "http://127.0.0.1:1/file",
+            destination("connect"),
+        )],
+        BulkOptions::new().concurrency(1),
+    );
+    assert!(
+        matches!(&outcomes[..], [DownloadOutcome::Failed { error: PeakError::Http(e), .. }] if e.is_connect()),
+        "{:?}",
+        outcomes
+    );
+
+    let outcomes = PeakRequests::new().proxy("http://[::1").download_all(
+        vec![
+            DownloadJob::new("http://a.example/", destination("a")),
+            DownloadJob::new("http://b.example/", destination("b")),
+        ],
+        BulkOptions::new(),
+    );
+    assert_eq!(outcomes.len(), 2);
+    for outcome in &outcomes {
+        assert!(
+            matches!(
+                outcome,
+                DownloadOutcome::Failed {
+                    error: PeakError::Http(_),
+                    ..
+                }
+            ),
+            "{:?}",
+            outcome
+        );
+    }
+}
@@ -0,0 +1,115 @@
+use peakrequests::PeakRequests;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+// keeps connections open between requests, so counting connections shows whether
+// a dropped stream went back to the pool. /N answers N bytes, /stall sends one
+// chunk of a chunked body and then nothing.
+struct Server {
+    addr: SocketAddr,
+    connections: Arc<AtomicUsize>,
+}
+
+impl Server {
+    fn start() -> Server {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let connections = Arc::new(AtomicUsize::new(0));
+        let counted = Arc::clone(&connections);
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                let Ok(stream) = stream else { continue };
+                counted.fetch_add(1, Ordering::SeqCst);
+                thread::spawn(move || serve(stream));
+            }
+        });
+        Server { addr, connections }
+    }
+
+    fn url(&self, path: &str) -> String {
+        format!("http://{}{}", self.addr, path)
+    }
+
+    fn connections(&self) -> usize {
+        self.connections.load(Ordering::SeqCst)
+    }
+}
+
+fn serve(stream: TcpStream) {
+    let mut reader = BufReader::new(stream.try_clone().unwrap());
+    let mut stream = stream;
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line).unwrap_or(0) == 0 {
+            return;
+        }
+        let path = line.split_whitespace().nth(1).unwrap_or("/").to_string();
+        while line != "\r\n" {
+            line.clear();
+            if reader.read_line(&mut line).unwrap_or(0) == 0 {
+                return;
+            }
+        }
+
+        if path == "/stall" {
+            let _ = stream
+                .write_all(b"HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n5\r\nhello\r\n");
+            thread::sleep(Duration::from_secs(30));
+            return;
+        }
+        let length: usize = path[1..].parse().unwrap();
+        let head = format!("HTTP/1.1 200 OK\r\nContent-Length: {}\r\n\r\n", length);
+        if stream.write_all(head.as_bytes()).is_err() {
+            return;
+        }
+        let block = [b'x'; 8192];
+        let mut left = length;
+        while left > 0 {
+            let n = left.min(block.len());
+            if stream.write_all(&block[..n]).is_err() {
+                return;
+            }
+            left -= n;
+        }
+    }
+}
+
+fn read_then_drop(client: &mut PeakRequests, url: &str, amount: usize) {
+    let mut stream = client.get_stream(url).unwrap();
+    let mut buf = vec![0; amount];
+    stream.read_exact(&mut buf).unwrap();
+}
+
+#[test]
+fn a_mostly_read_stream_goes_back_to_the_pool() {
+    let server = Server::start();
+    let mut client = PeakRequests::new();
+
+    read_then_drop(&mut client, &server.url("/200000"), 190_000);
+    assert_eq!(client.get(&server.url("/2")).unwrap().text(), "xx");
+    assert_eq!(server.connections(), 1);
+}
+
+#[test]
+fn a_barely_started_huge_stream_is_closed() {
+    let server = Server::start();
+    let mut client = PeakRequests::new();
+
+    read_then_drop(&mut client, &server.url("/50000000"), 1000);
+    assert_eq!(client.get(&server.url("/2")).unwrap().text(), "xx");
+    assert_eq!(server.connections(), 2);
+}
+
+#[test]
+fn a_stalled_chunked_body_does_not_hang_the_drop() {
+    let server = Server::start();
+    let mut client = PeakRequests::new();
+
+    let started = Instant::now();
+    read_then_drop(&mut client, &server.url("/stall"), 5);
+    assert!(started.elapsed() < Duration::from_secs(5));
+}
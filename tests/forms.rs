@@ -0,0 +1,84 @@
+// form bodies through post/put, with and without the json feature (the
+// signatures differ: without it there's no json argument)
+mod common;
+
+use common::{ok, Server};
+use peakrequests::PeakRequests;
+use std::collections::HashMap;
+
+fn echo() -> Server {
+    Server::start(|request| {
+        ok(&format!(
+            "{} {} {}",
+            request.method,
+            request.header("content-type").unwrap_or("-"),
+            String::from_utf8_lossy(&request.body)
+        ))
+    })
+}
+
+fn form() -> HashMap<&'static str, &'static str> {
+    let mut data = HashMap::new();
+    data.insert("name", "peak requests");
+    data
+}
+
+#[test]
+fn free_functions_send_forms() {
+    let server = echo();
+    let resp = peakrequests::post(&server.url("/"), form()).unwrap();
+    assert_eq!(
+        resp.text(),
+        "POST application/x-www-form-urlencoded name=peak+requests"
+    );
+    let resp = peakrequests::put(&server.url("/"), form()).unwrap();
+    assert_eq!(
+        resp.text(),
+        "PUT application/x-www-form-urlencoded name=peak+requests"
+    );
+}
+
+#[cfg(not(feature = "json"))]
+#[test]
+fn client_post_and_put_take_only_form_data() {
+    let server = echo();
+    let mut client = PeakRequests::new();
+    let resp = client.post(&server.url("/"), Some(form())).unwrap();
+    assert_eq!(
+        resp.text(),
+        "POST application/x-www-form-urlencoded name=peak+requests"
+    );
+    let resp = client.put(&server.url("/"), None).unwrap();
+    assert_eq!(resp.text(), "PUT - ");
+}
+
+#[cfg(feature = "json")]
+#[test]
+fn client_post_and_put_take_form_or_json() {
+    let server = echo();
+    let mut client = PeakRequests::new();
+    let resp = client.post(&server.url("/"), Some(form()), None).unwrap();
+    assert_eq!(
+        resp.text(),
+        "POST application/x-www-form-urlencoded name=peak+requests"
+    );
+    let resp = client
+        .put(&server.url("/"), None, Some(serde_json::json!({"a": 1})))
+        .unwrap();
+    assert_eq!(resp.text(), r#"PUT application/json {"a":1}"#);
+}
+
+#[test]
+fn builder_form_body() {
+    let server = echo();
+    let mut client = PeakRequests::new();
+    let resp = client
+        .request("POST", &server.url("/"))
+        .form(form())
+        .send()
+        .unwrap();
+    assert_eq!(
+        resp.text(),
+        "POST application/x-www-form-urlencoded name=peak+requests"
+    );
+}
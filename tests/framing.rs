@@ -0,0 +1,59 @@
+mod common;
+
+use common::{ok, Server};
+use peakrequests::{PeakError, PeakRequests};
+
+const SMUGGLED: &str = "HTTP/1.1 200 OK\r\nContent-Length: 3\r\nTransfer-Encoding: chunked\r\nConnection: close\r\n\r\n5\r\nhello\r\n0\r\n\r\n";
+
+fn malformed_reason(error: PeakError) -> String {
+    match error {
+        PeakError::MalformedResponse { reason } => reason,
+        other => panic!("expected MalformedResponse, got {:?}", other),
+    }
+}
+
+#[test]
+fn both_framing_headers_are_refused() {
+    let server = Server::start(|_| SMUGGLED);
+    let mut client = PeakRequests::new();
+    let error = client.get(&server.url("/")).unwrap_err();
+    assert_eq!(
+        malformed_reason(error),
+        "both Content-Length and Transfer-Encoding present"
+    );
+}
+
+#[test]
+fn lenient_framing_goes_with_transfer_encoding() {
+    let server = Server::start(|_| SMUGGLED);
+    let mut client = PeakRequests::new().lenient_framing(true);
+    let response = client.get(&server.url("/")).unwrap();
+    assert_eq!(response.text(), "hello");
+}
+
+#[test]
+fn conflicting_lengths_are_refused() {
+    let server = Server::start(|_| {
+        "HTTP/1.1 200 OK\r\nContent-Length: 5\r\nContent-Length: 6\r\nConnection: close\r\n\r\nhello!"
+    });
+    let mut client = PeakRequests::new();
+    let error = client.get(&server.url("/")).unwrap_err();
+    assert!(malformed_reason(error).starts_with("conflicting Content-Length values"),);
+}
+
+#[test]
+fn lenient_framing_does_not_excuse_conflicting_lengths() {
+    let server = Server::start(|_| {
+        "HTTP/1.1 200 OK\r\nContent-Length: 5, 6\r\nConnection: close\r\n\r\nhello!"
+    });
+    let mut client = PeakRequests::new().lenient_framing(true);
+    let error = client.get(&server.url("/")).unwrap_err();
+    assert!(malformed_reason(error).starts_with("conflicting Content-Length values"),);
+}
+
+#[test]
+fn ordinary_responses_pass() {
+    let server = Server::start(|_| ok("fine"));
+    let mut client = PeakRequests::new();
+    assert_eq!(client.get(&server.url("/")).unwrap().text(), "fine");
+}
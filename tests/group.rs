@@ -0,0 +1,87 @@
+mod common;
+
+use common::{ok, Server};
+use peakrequests::{GroupResult, PeakError, PeakRequests, RequestGroup};
+use std::thread;
+use std::time::{Duration, Instant};
+
+// /slow answers after two seconds, anything else straight away
+fn server() -> Server {
+    Server::start(|request| {
+        if request.path == "/slow" {
+            thread::sleep(Duration::from_secs(2));
+        }
+        ok(&request.path)
+    })
+}
+
+fn group(client: &mut PeakRequests, deadline: Duration, urls: &[String]) -> RequestGroup {
+    let mut group = RequestGroup::new(deadline);
+    for url in urls {
+        group.add(client.request("GET", url).prepare());
+    }
+    group
+}
+
+#[test]
+fn the_deadline_cuts_off_what_is_still_running() {
+    let server = server();
+    let mut client = PeakRequests::new();
+    let urls = [server.url("/slow"), server.url("/fast")];
+
+    let started = Instant::now();
+    let results = group(&mut client, Duration::from_millis(500), &urls).execute(&client, 2);
+    assert!(started.elapsed() < Duration::from_millis(1500));
+
+    assert!(matches!(results[0], GroupResult::Cancelled));
+    let GroupResult::Completed(fast) = &results[1] else {
+        panic!("fast request did not complete: {:?}", results[1]);
+    };
+    assert_eq!(fast.text(), "/fast");
+}
+
+#[test]
+fn cancelling_returns_early() {
+    let server = server();
+    let mut client = PeakRequests::new();
+    let urls = [server.url("/slow"), server.url("/slow")];
+    let group = group(&mut client, Duration::from_secs(10), &urls);
+    let cancel = group.cancel_handle();
+
+    let started = Instant::now();
+    thread::spawn(move || {
+        thread::sleep(Duration::from_millis(200));
+        cancel.cancel();
+    });
+    let results = group.execute(&client, 2);
+    assert!(started.elapsed() < Duration::from_secs(1));
+    assert!(results
+        .iter()
+        .all(|result| matches!(result, GroupResult::Cancelled)));
+}
+
+#[test]
+fn failures_keep_their_type() {
+    let mut client = PeakRequests::new();
+    let urls = ["http://127.0.0.1:1/".to_string()];
+    let results = group(&mut client, Duration::from_secs(5), &urls).execute(&client, 1);
+    assert!(
+        matches!(&results[0], GroupResult::Failed(PeakError::Http(e)) if e.is_connect()),
+        "{:?}",
+        results[0]
+    );
+
+    let mut client = PeakRequests::new().proxy("http://[::1");
+    let urls = [
+        "http://a.example/".to_string(),
+        "http://b.example/".to_string(),
+    ];
+    let results = group(&mut client, Duration::from_secs(5), &urls).execute(&client, 1);
+    for result in &results {
+        assert!(
+            matches!(result, GroupResult::Failed(PeakError::Http(_))),
+            "{:?}",
+            result
+        );
+    }
+}
@@ -0,0 +1,48 @@
+mod common;
+
+use common::{response, Server};
+use peakrequests::{PeakError, PeakRequests};
+
+// sha-256 of "hello" is LPJNul+wow4m6DsqxbninhsWHlwfp0JecwQzYpOLmCQ=, this isn't it
+fn wrong_digest() -> Server {
+    Server::start(|_| {
+        response(
+            "200 OK",
+            &[(
+                "Repr-Digest",
+                "sha-256=:AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA=:",
+            )],
+            "hello",
+        )
+    })
+}
+
+#[test]
+fn not_requiring_a_digest_does_not_compute_one() {
+    let server = wrong_digest();
+
+    let resp = PeakRequests::new()
+        .require_valid_digest(false)
+        .get(&server.url("/"))
+        .unwrap();
+    assert_eq!(resp.sha256(), None);
+    assert_eq!(resp.digest_verified(), None);
+}
+
+#[test]
+fn requiring_then_relaxing_keeps_the_hash() {
+    let server = wrong_digest();
+
+    let result = PeakRequests::new()
+        .require_valid_digest(true)
+        .get(&server.url("/"));
+    assert!(matches!(result, Err(PeakError::DigestMismatch { .. })));
+
+    let resp = PeakRequests::new()
+        .require_valid_digest(true)
+        .require_valid_digest(false)
+        .get(&server.url("/"))
+        .unwrap();
+    assert!(resp.sha256().is_some());
+    assert_eq!(resp.digest_verified(), Some(false));
+}
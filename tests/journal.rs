@@ -0,0 +1,59 @@
+#![cfg(feature = "json")]
+
+mod common;
+
+use common::{response, Server};
+use peakrequests::{JournalConfig, JournalFlush, PeakRequests};
+use std::fs;
+
+#[test]
+fn credentials_stay_out_of_the_journal() {
+    let server = Server::start(|_| response("503 Service Unavailable", &[], ""));
+    let dir = std::env::temp_dir().join(format!("peak-journal-{}", std::process::id()));
+    let mut client = PeakRequests::new().journal(JournalConfig {
+        dir: dir.clone(),
+        max_bytes: 1 << 20,
+        flush: JournalFlush::Always,
+    });
+
+    let resp = client
+        .request("POST", &server.url("/orders"))
+        .header("Authorization", "Bearer secret-token")
+        .header("Cookie", "session=secret-cookie")
+        .header("Proxy-Authorization", "Basic secret-proxy")
+        .header("X-Trace", "kept")
+        .durable()
+        .send()
+        .unwrap();
+    assert_eq!(resp.status_code, 503);
+    assert_eq!(
+        server.requests()[0].header("authorization"),
+        Some("Bearer secret-token")
+    );
+
+    let journal = fs::read_to_string(dir.join("journal.log")).unwrap();
+    fs::remove_dir_all(&dir).unwrap();
+    assert!(!journal.contains("secret"), "{}", journal);
+    assert!(journal.contains("X-Trace"), "{}", journal);
+}
+
+#[test]
+fn a_prepared_request_stays_durable() {
+    let server = Server::start(|_| response("200 OK", &[], "done"));
+    let dir = std::env::temp_dir().join(format!("peak-journal-prepared-{}", std::process::id()));
+    let mut client = PeakRequests::new().journal(JournalConfig {
+        dir: dir.clone(),
+        max_bytes: 1 << 20,
+        flush: JournalFlush::Always,
+    });
+
+    let prepared = client
+        .request("POST", &server.url("/orders"))
+        .durable()
+        .prepare();
+    prepared.send(&mut client).unwrap();
+    fs::remove_dir_all(&dir).unwrap();
+
+    // durable sends carry an idempotency key, plain ones don't
+    assert!(server.requests()[0].header("idempotency-key").is_some());
+}
@@ -0,0 +1,70 @@
+#![cfg(feature = "json")]
+
+mod common;
+
+use common::{ok, Server};
+use peakrequests::{JsonEncodeOptions, PeakRequests};
+use serde_json::json;
+
+fn sent_body(mut client: PeakRequests) -> String {
+    let server = Server::start(|_| ok(""));
+    client
+        .request("POST", &server.url("/"))
+        .json(json!({"name": "café", "emoji": "🐿", "n": [1, 2]}))
+        .send()
+        .unwrap();
+    let requests = server.requests();
+    assert_eq!(requests[0].header("content-type"), Some("application/json"));
+    String::from_utf8(requests[0].body.clone()).unwrap()
+}
+
+#[test]
+fn compact_utf8_by_default() {
+    let body = sent_body(PeakRequests::new());
+    assert_eq!(body, r#"{"emoji":"🐿","n":[1,2],"name":"café"}"#);
+}
+
+#[test]
+fn non_ascii_is_escaped_on_request() {
+    let body = sent_body(PeakRequests::new().json_encoding(JsonEncodeOptions {
+        pretty: false,
+        escape_non_ascii: true,
+    }));
+    // the squirrel is past the BMP, so it goes out as a surrogate pair
+    assert_eq!(
+        body,
+        r#"{"emoji":"\ud83d\udc3f","n":[1,2],"name":"caf\u00e9"}"#
+    );
+}
+
+#[test]
+fn pretty_keeps_the_layout() {
+    let body = sent_body(PeakRequests::new().json_encoding(JsonEncodeOptions {
+        pretty: true,
+        escape_non_ascii: true,
+    }));
+    assert!(body.contains("\n  \"name\": \"caf\\u00e9\""), "{}", body);
+    let decoded: serde_json::Value = serde_json::from_str(&body).unwrap();
+    assert_eq!(decoded["emoji"], "🐿");
+    assert_eq!(decoded["name"], "café");
+}
+
+#[test]
+fn an_explicit_content_type_is_kept() {
+    let server = Server::start(|_| ok(""));
+    let mut client = PeakRequests::new();
+    client
+        .request("POST", &server.url("/"))
+        .header("Content-Type", "application/merge-patch+json")
+        .json(json!({"a": 1}))
+        .send()
+        .unwrap();
+    let requests = server.requests();
+    let types: Vec<_> = requests[0]
+        .headers
+        .iter()
+        .filter(|(key, _)| key == "content-type")
+        .collect();
+    assert_eq!(types.len(), 1);
+    assert_eq!(types[0].1, "application/merge-patch+json");
+}
@@ -0,0 +1,102 @@
+use peakrequests::PeakRequests;
+use std::io::{BufRead, BufReader, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+// keeps connections open between requests and drops one after IDLE without a
+// request, like a nat table would. counting connections counts handshakes.
+const IDLE: Duration = Duration::from_millis(1500);
+
+struct Server {
+    addr: SocketAddr,
+    connections: Arc<AtomicUsize>,
+    requests: Arc<AtomicUsize>,
+}
+
+impl Server {
+    fn start() -> Server {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let connections = Arc::new(AtomicUsize::new(0));
+        let requests = Arc::new(AtomicUsize::new(0));
+        let (counted, served) = (Arc::clone(&connections), Arc::clone(&requests));
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                let Ok(stream) = stream else { continue };
+                counted.fetch_add(1, Ordering::SeqCst);
+                let served = Arc::clone(&served);
+                thread::spawn(move || serve(stream, &served));
+            }
+        });
+        Server {
+            addr,
+            connections,
+            requests,
+        }
+    }
+
+    fn url(&self, path: &str) -> String {
+        format!("http://{}{}", self.addr, path)
+    }
+}
+
+fn serve(stream: TcpStream, served: &AtomicUsize) {
+    stream.set_read_timeout(Some(IDLE)).unwrap();
+    let mut reader = BufReader::new(stream.try_clone().unwrap());
+    let mut stream = stream;
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line).unwrap_or(0) == 0 {
+            return;
+        }
+        let head_only = line.starts_with("HEAD ");
+        while line != "\r\n" {
+            line.clear();
+            if reader.read_line(&mut line).unwrap_or(0) == 0 {
+                return;
+            }
+        }
+        served.fetch_add(1, Ordering::SeqCst);
+        let body = if head_only { "" } else { "ok" };
+        let answer = format!("HTTP/1.1 200 OK\r\nContent-Length: 2\r\n\r\n{}", body);
+        if stream.write_all(answer.as_bytes()).is_err() {
+            return;
+        }
+    }
+}
+
+fn request_after_idle(client: &mut PeakRequests, server: &Server) {
+    assert_eq!(client.get(&server.url("/")).unwrap().text(), "ok");
+    thread::sleep(IDLE * 2 + Duration::from_millis(500));
+    assert_eq!(client.get(&server.url("/")).unwrap().text(), "ok");
+}
+
+#[test]
+fn pings_keep_the_connection_warm_across_idle() {
+    let server = Server::start();
+    let mut client =
+        PeakRequests::new().keepalive_ping(Duration::from_secs(1), &server.url("/ping"));
+
+    request_after_idle(&mut client, &server);
+    assert_eq!(server.connections.load(Ordering::SeqCst), 1);
+    assert!(server.requests.load(Ordering::SeqCst) >= 4);
+}
+
+#[test]
+fn without_pings_the_idle_connection_is_replaced() {
+    let server = Server::start();
+    let mut client = PeakRequests::new();
+
+    request_after_idle(&mut client, &server);
+    assert_eq!(server.connections.load(Ordering::SeqCst), 2);
+    assert_eq!(server.requests.load(Ordering::SeqCst), 2);
+}
+
+#[test]
+fn sub_second_intervals_are_raised() {
+    let client = PeakRequests::new().keepalive_ping(Duration::ZERO, "http://127.0.0.1:9/");
+    assert_eq!(client.effective_config().pool.keepalive_ping_secs, Some(1));
+}
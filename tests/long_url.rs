@@ -0,0 +1,74 @@
+mod common;
+
+use common::{ok, Server};
+use peakrequests::{PeakError, PeakRequests};
+
+fn long_query(server: &Server, values: usize) -> String {
+    let mut url = server.url("/search?");
+    for n in 0..values {
+        url.push_str(&format!("k{}=v{}&", n, n));
+    }
+    url.pop();
+    url
+}
+
+#[test]
+fn urls_over_the_limit_are_refused_before_sending() {
+    let server = Server::start(|_| ok(""));
+    let mut client = PeakRequests::new().max_url_length(100);
+    let url = long_query(&server, 30);
+    let error = client.get(&url).unwrap_err();
+    assert!(
+        matches!(error, PeakError::UrlTooLong { length, limit: 100 } if length == url.len()),
+        "{:?}",
+        error
+    );
+    assert!(server.requests().is_empty());
+
+    client.get(&server.url("/short")).unwrap();
+    assert_eq!(server.requests().len(), 1);
+}
+
+#[test]
+fn a_long_get_goes_out_as_a_post() {
+    let server = Server::start(|_| ok(""));
+    let mut client = PeakRequests::new().convert_long_get_to_post(100, "X-HTTP-Method-Override");
+    let url = format!("{}#section", long_query(&server, 30));
+    client.get(&url).unwrap();
+
+    let requests = server.requests();
+    assert_eq!(requests[0].method, "POST");
+    assert_eq!(requests[0].path, "/search");
+    assert_eq!(requests[0].header("x-http-method-override"), Some("GET"));
+    assert_eq!(
+        requests[0].header("content-type"),
+        Some("application/x-www-form-urlencoded")
+    );
+    let query = url.split_once('?').unwrap().1.split_once('#').unwrap().0;
+    assert_eq!(requests[0].body, query.as_bytes());
+}
+
+#[test]
+fn short_gets_and_other_methods_are_left_alone() {
+    let server = Server::start(|_| ok(""));
+    let mut client = PeakRequests::new().convert_long_get_to_post(100, "X-HTTP-Method-Override");
+    client.get(&server.url("/search?q=1")).unwrap();
+    client.delete(&long_query(&server, 30)).unwrap();
+
+    let requests = server.requests();
+    assert_eq!(requests[0].method, "GET");
+    assert_eq!(requests[0].path, "/search?q=1");
+    assert_eq!(requests[1].method, "DELETE");
+    assert!(requests[1].path.starts_with("/search?k0=v0"));
+    assert_eq!(requests[1].header("x-http-method-override"), None);
+}
+
+#[test]
+fn the_limit_applies_after_conversion() {
+    let server = Server::start(|_| ok(""));
+    let mut client = PeakRequests::new()
+        .convert_long_get_to_post(100, "X-HTTP-Method-Override")
+        .max_url_length(200);
+    client.get(&long_query(&server, 60)).unwrap();
+    assert_eq!(server.requests()[0].method, "POST");
+}
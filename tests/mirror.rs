@@ -0,0 +1,173 @@
+mod common;
+
+use common::{ok, response, Server};
+use peakrequests::{MirrorConfig, PeakRequests};
+use std::collections::HashMap;
+use std::thread;
+use std::time::{Duration, Instant};
+
+#[test]
+fn replays_past_the_queue_are_dropped_and_counted() {
+    let primary = Server::start(|_| ok("primary"));
+    let target = Server::start(|_| {
+        thread::sleep(Duration::from_millis(200));
+        ok("mirror")
+    });
+    let mut client = PeakRequests::new().mirror(MirrorConfig {
+        target_base_url: target.url(""),
+        sample_rate: 1.0,
+        queue: 1,
+        ..MirrorConfig::default()
+    });
+
+    for i in 0..8 {
+        client.get(&primary.url(&format!("/{}", i))).unwrap();
+    }
+    // one replay running, one queued, the rest had nowhere to go
+    assert!(client.mirror_stats().dropped >= 4);
+
+    let deadline = Instant::now() + Duration::from_secs(5);
+    let stats = loop {
+        let stats = client.mirror_stats();
+        if stats.mirrored + stats.dropped == 8 || Instant::now() > deadline {
+            break stats;
+        }
+        thread::sleep(Duration::from_millis(20));
+    };
+    assert_eq!(stats.mirrored + stats.dropped, 8);
+    assert_eq!(target.requests().len() as u64, stats.mirrored);
+}
+
+// sends `count` GETs through a mirroring client and returns the paths the shadow
+// backend saw, sorted
+fn mirrored(count: usize, config: MirrorConfig) -> Vec<String> {
+    let primary = Server::start(|_| ok("primary"));
+    let target = Server::start(|_| ok("mirror"));
+    let mut client = PeakRequests::new().mirror(MirrorConfig {
+        target_base_url: target.url(""),
+        queue: count,
+        ..config
+    });
+    for i in 0..count {
+        client.get(&primary.url(&format!("/{}", i))).unwrap();
+    }
+    client.shutdown(Duration::from_secs(5));
+
+    let mut paths: Vec<String> = target.requests().into_iter().map(|r| r.path).collect();
+    paths.sort();
+    paths
+}
+
+#[test]
+fn the_sample_rate_decides_how_much_is_copied() {
+    let none = mirrored(
+        50,
+        MirrorConfig {
+            sample_rate: 0.0,
+            ..MirrorConfig::default()
+        },
+    );
+    assert!(none.is_empty());
+
+    let all = mirrored(
+        50,
+        MirrorConfig {
+            sample_rate: 1.0,
+            ..MirrorConfig::default()
+        },
+    );
+    assert_eq!(all.len(), 50);
+
+    let some = mirrored(
+        200,
+        MirrorConfig {
+            sample_rate: 0.25,
+            seed: Some(1),
+            ..MirrorConfig::default()
+        },
+    );
+    assert!((25..=75).contains(&some.len()), "{}", some.len());
+}
+
+#[test]
+fn the_seed_fixes_which_requests_are_copied() {
+    let sampled = |seed| {
+        mirrored(
+            30,
+            MirrorConfig {
+                sample_rate: 0.5,
+                seed: Some(seed),
+                ..MirrorConfig::default()
+            },
+        )
+    };
+    let first = sampled(42);
+    assert!(!first.is_empty() && first.len() < 30, "{:?}", first);
+    assert_eq!(sampled(42), first);
+    assert_ne!(sampled(43), first);
+}
+
+#[test]
+fn posts_are_only_copied_when_asked() {
+    let primary = Server::start(|_| ok("primary"));
+    let target = Server::start(|_| ok("mirror"));
+    for (mirror_non_idempotent, expected) in [(false, 0), (true, 1)] {
+        let mut client = PeakRequests::new().mirror(MirrorConfig {
+            target_base_url: target.url(""),
+            sample_rate: 1.0,
+            mirror_non_idempotent,
+            ..MirrorConfig::default()
+        });
+        client
+            .request("POST", &primary.url("/orders"))
+            .form(HashMap::from([("item", "1")]))
+            .send()
+            .unwrap();
+        client.shutdown(Duration::from_secs(5));
+        assert_eq!(target.requests().len(), expected);
+    }
+}
+
+#[test]
+fn credentials_are_not_copied_to_the_shadow_backend() {
+    let primary = Server::start(|_| {
+        response(
+            "200 OK",
+            &[("Set-Cookie", "sid=from-primary; Path=/")],
+            "primary",
+        )
+    });
+    let target = Server::start(|_| ok("mirror"));
+    let mut headers = HashMap::new();
+    headers.insert("Authorization".to_string(), "Bearer secret".to_string());
+    headers.insert("X-Trace".to_string(), "abc".to_string());
+    let mut client = PeakRequests::new()
+        .headers(headers)
+        .cookies(true)
+        .mirror(MirrorConfig {
+            target_base_url: target.url(""),
+            sample_rate: 1.0,
+            ..MirrorConfig::default()
+        });
+
+    client.get(&primary.url("/first")).unwrap();
+    client
+        .request("GET", &primary.url("/second"))
+        .header("Proxy-Authorization", "Basic c2VjcmV0")
+        .send()
+        .unwrap();
+    client.shutdown(Duration::from_secs(5));
+
+    assert_eq!(
+        primary.requests()[1].header("cookie"),
+        Some("sid=from-primary")
+    );
+    let replays = target.requests();
+    assert_eq!(replays.len(), 2);
+    for replay in &replays {
+        assert_eq!(replay.header("authorization"), None, "{:?}", replay);
+        assert_eq!(replay.header("proxy-authorization"), None, "{:?}", replay);
+        assert_eq!(replay.header("cookie"), None, "{:?}", replay);
+        assert_eq!(replay.header("x-trace"), Some("abc"));
+    }
+}
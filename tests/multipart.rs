@@ -0,0 +1,100 @@
+mod common;
+
+use common::{ok, Server};
+use peakrequests::{Multipart, PeakRequests, RelatedPart};
+
+fn boundary(content_type: &str) -> String {
+    let start = content_type.find("boundary=\"").unwrap() + "boundary=\"".len();
+    let end = content_type[start..].find('"').unwrap();
+    content_type[start..start + end].to_string()
+}
+
+#[test]
+fn parts_are_laid_out_in_order() {
+    let multipart = Multipart::related()
+        .part(RelatedPart::new("text/xml; charset=utf-8", "<doc/>").content_id("<root@x>"))
+        .part(
+            RelatedPart::new("image/png", vec![0u8, 1, 2])
+                .content_id("img@x")
+                .header("Content-Transfer-Encoding", "binary"),
+        );
+    let boundary = boundary(&multipart.content_type());
+
+    let mut expected = format!(
+        "--{b}\r\nContent-Type: text/xml; charset=utf-8\r\nContent-ID: <root@x>\r\n\r\n<doc/>\r\n\
+         --{b}\r\nContent-Type: image/png\r\nContent-ID: <img@x>\r\nContent-Transfer-Encoding: binary\r\n\r\n",
+        b = boundary
+    )
+    .into_bytes();
+    expected.extend_from_slice(&[0, 1, 2]);
+    expected.extend_from_slice(format!("\r\n--{}--\r\n", boundary).as_bytes());
+    assert_eq!(multipart.to_bytes(), expected);
+}
+
+#[test]
+fn the_first_part_is_the_root_unless_started_elsewhere() {
+    let first = RelatedPart::new("application/json", "{}");
+    let second = RelatedPart::new("text/xml", "<a/>").content_id("second@x");
+
+    let plain = Multipart::related()
+        .part(first.clone())
+        .part(second.clone());
+    let content_type = plain.content_type();
+    assert!(content_type.starts_with("multipart/related; boundary=\""));
+    assert!(
+        content_type.ends_with("; type=\"application/json\""),
+        "{}",
+        content_type
+    );
+
+    let started = Multipart::related()
+        .part(first)
+        .part(second)
+        .start("<second@x>");
+    assert!(
+        started
+            .content_type()
+            .ends_with("; type=\"text/xml\"; start=\"<second@x>\""),
+        "{}",
+        started.content_type()
+    );
+}
+
+#[test]
+fn boundaries_and_ids_are_unique() {
+    let a = Multipart::related().content_type();
+    let b = Multipart::related().content_type();
+    assert_ne!(boundary(&a), boundary(&b));
+
+    let part = RelatedPart::new("text/plain", "x");
+    assert_ne!(part.id(), RelatedPart::new("text/plain", "x").id());
+    assert_eq!(part.cid_url(), format!("cid:{}", part.id()));
+}
+
+#[test]
+fn the_builder_sends_the_body_with_its_content_type() {
+    let server = Server::start(|_| ok(""));
+    let multipart = Multipart::related()
+        .part(RelatedPart::new("text/plain", "root"))
+        .part(RelatedPart::new("text/plain", "attachment"));
+    let content_type = multipart.content_type();
+    let body = multipart.to_bytes();
+
+    let mut client = PeakRequests::new();
+    client
+        .request("POST", &server.url("/upload"))
+        .header("Content-Type", "text/plain")
+        .multipart(multipart)
+        .send()
+        .unwrap();
+
+    let requests = server.requests();
+    let types: Vec<_> = requests[0]
+        .headers
+        .iter()
+        .filter(|(key, _)| key == "content-type")
+        .map(|(_, value)| value.as_str())
+        .collect();
+    assert_eq!(types, [content_type.as_str()]);
+    assert_eq!(requests[0].body, body);
+}
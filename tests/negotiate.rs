@@ -0,0 +1,71 @@
+mod common;
+
+use common::{ok, response, Server};
+use peakrequests::{PeakError, PeakRequests};
+
+// only speaks xml, anything asking for something else gets a 406
+fn xml_only() -> Server {
+    Server::start(|request| match request.header("accept") {
+        Some("application/xml") => ok("<ok/>"),
+        _ => response("406 Not Acceptable", &[], ""),
+    })
+}
+
+fn accepts(server: &Server) -> Vec<String> {
+    server
+        .requests()
+        .iter()
+        .map(|r| r.header("accept").unwrap_or_default().to_string())
+        .collect()
+}
+
+#[test]
+fn falls_back_one_type_at_a_time() {
+    let server = xml_only();
+    let mut client = PeakRequests::new();
+    let resp = client
+        .request("GET", &server.url("/doc"))
+        .accept_fallback(&["application/json", "application/xml"])
+        .send()
+        .unwrap();
+    assert_eq!(resp.text(), "<ok/>");
+    assert_eq!(resp.negotiated_accept.as_deref(), Some("application/xml"));
+    assert_eq!(
+        accepts(&server),
+        [
+            "application/json, application/xml;q=0.9",
+            "application/json",
+            "application/xml"
+        ]
+    );
+}
+
+#[test]
+fn nothing_acceptable_lists_every_attempt() {
+    let server = xml_only();
+    let mut client = PeakRequests::new();
+    let error = client
+        .request("GET", &server.url("/doc"))
+        .accept_fallback(&["application/json", "text/csv"])
+        .send()
+        .unwrap_err();
+    let PeakError::NotAcceptable { attempted } = error else {
+        panic!("expected NotAcceptable, got {:?}", error);
+    };
+    assert_eq!(attempted, accepts(&server));
+    assert_eq!(attempted.len(), 3);
+}
+
+#[test]
+fn a_prepared_request_keeps_its_fallbacks() {
+    let server = xml_only();
+    let mut client = PeakRequests::new();
+    let prepared = client
+        .request("GET", &server.url("/doc"))
+        .accept_fallback(&["application/json", "application/xml"])
+        .prepare();
+
+    let resp = prepared.send(&mut client).unwrap();
+    assert_eq!(resp.negotiated_accept.as_deref(), Some("application/xml"));
+    assert_eq!(server.requests().len(), 3);
+}
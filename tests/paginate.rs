@@ -0,0 +1,148 @@
+#![cfg(feature = "json")]
+
+mod common;
+
+use common::{ok, response, Server};
+use peakrequests::{CursorSpec, Offset, PeakError, PeakRequests};
+
+fn paths(server: &Server) -> Vec<String> {
+    server.requests().into_iter().map(|r| r.path).collect()
+}
+
+#[test]
+fn a_plain_url_is_one_page() {
+    let server = Server::start(|_| ok("[1, 2]"));
+    let mut client = PeakRequests::new();
+    let pages: Vec<_> = client.paginate(&server.url("/items")).collect();
+    assert_eq!(pages.len(), 1);
+    assert_eq!(pages[0].as_ref().unwrap().text(), "[1, 2]");
+    assert_eq!(paths(&server), ["/items"]);
+}
+
+#[test]
+fn offsets_step_by_the_limit_until_an_empty_page() {
+    let server = Server::start(|request| match request.path.as_str() {
+        "/items?from=0&size=2" => ok("[1, 2]"),
+        "/items?from=2&size=2" => ok("[3, 4]"),
+        _ => ok("[]"),
+    });
+    let mut client = PeakRequests::new();
+    let pages: Vec<String> = client
+        .paginate(&server.url("/items"))
+        .query_style(Offset {
+            param: "from",
+            limit: 2,
+        })
+        .limit_param("size")
+        .map(|page| page.unwrap().text().to_string())
+        .collect();
+    assert_eq!(pages, ["[1, 2]", "[3, 4]", "[]"]);
+    assert_eq!(
+        paths(&server),
+        [
+            "/items?from=0&size=2",
+            "/items?from=2&size=2",
+            "/items?from=4&size=2"
+        ]
+    );
+}
+
+#[test]
+fn cursors_are_followed_until_there_is_none() {
+    let server = Server::start(|request| match request.path.as_str() {
+        "/feed" => ok(r#"{"data": [1], "meta": {"next": "b"}}"#),
+        "/feed?after=b" => ok(r#"{"data": [2], "meta": {"next": 3}}"#),
+        "/feed?after=3" => ok(r#"{"data": [4], "meta": {"next": null}}"#),
+        _ => response("404 Not Found", &[], ""),
+    });
+    let mut client = PeakRequests::new();
+    let pages = client
+        .paginate(&server.url("/feed"))
+        .cursor_style(CursorSpec {
+            request_param: "after",
+            response_pointer: "/meta/next",
+        })
+        .items_pointer("/data")
+        .count();
+    assert_eq!(pages, 3);
+    assert_eq!(paths(&server), ["/feed", "/feed?after=b", "/feed?after=3"]);
+}
+
+#[test]
+fn an_empty_items_array_ends_cursor_paging() {
+    let server = Server::start(|request| match request.path.as_str() {
+        "/feed" => ok(r#"{"data": [1], "next": "b"}"#),
+        _ => ok(r#"{"data": [], "next": "c"}"#),
+    });
+    let mut client = PeakRequests::new();
+    let pages = client
+        .paginate(&server.url("/feed"))
+        .cursor_style(CursorSpec {
+            request_param: "after",
+            response_pointer: "/next",
+        })
+        .items_pointer("/data")
+        .count();
+    assert_eq!(pages, 2);
+}
+
+#[test]
+fn a_repeated_cursor_is_a_loop() {
+    // a -> b -> a, following the third page's cursor would start the circle again
+    let server = Server::start(|request| match request.path.as_str() {
+        "/feed" => ok(r#"{"data": [1], "next": "a"}"#),
+        "/feed?after=a" => ok(r#"{"data": [2], "next": "b"}"#),
+        _ => ok(r#"{"data": [3], "next": "a"}"#),
+    });
+    let mut client = PeakRequests::new();
+    let pages: Vec<_> = client
+        .paginate(&server.url("/feed"))
+        .cursor_style(CursorSpec {
+            request_param: "after",
+            response_pointer: "/next",
+        })
+        .items_pointer("/data")
+        .collect();
+    assert_eq!(pages.len(), 3);
+    assert!(pages[..2].iter().all(Result::is_ok));
+    assert!(
+        matches!(&pages[2], Err(PeakError::PaginationLoop { cursor }) if cursor == "a"),
+        "{:?}",
+        pages[2]
+    );
+    assert_eq!(server.requests().len(), 3);
+}
+
+#[test]
+fn max_pages_stops_a_server_that_never_runs_dry() {
+    let server = Server::start(|_| ok("[1]"));
+    let mut client = PeakRequests::new();
+    let pages = client
+        .paginate(&server.url("/items"))
+        .query_style(Offset {
+            param: "offset",
+            limit: 1,
+        })
+        .max_pages(5)
+        .count();
+    assert_eq!(pages, 5);
+    assert_eq!(server.requests().len(), 5);
+}
+
+#[test]
+fn an_error_status_is_the_last_page() {
+    let server = Server::start(|request| match request.path.as_str() {
+        "/items?offset=0&limit=1" => ok("[1]"),
+        _ => response("500 Internal Server Error", &[], "down"),
+    });
+    let mut client = PeakRequests::new();
+    let pages: Vec<_> = client
+        .paginate(&server.url("/items"))
+        .query_style(Offset {
+            param: "offset",
+            limit: 1,
+        })
+        .collect();
+    assert_eq!(pages.len(), 2);
+    assert_eq!(pages[1].as_ref().unwrap().status_code, 500);
+}
@@ -0,0 +1,70 @@
+#![cfg(feature = "json")]
+
+mod common;
+
+use common::{response, Server};
+use peakrequests::{PeakError, PeakRequests};
+
+fn failing(content_type: &'static str, body: &'static str) -> Server {
+    Server::start(move |_| response("403 Forbidden", &[("Content-Type", content_type)], body))
+}
+
+#[test]
+fn problem_details_ride_along_on_status_errors() {
+    let server = failing(
+        "application/problem+json",
+        r#"{"type": "https://example.com/probs/credit", "title": "Not enough credit",
+            "status": 403, "detail": "Balance is 30, cost is 50", "balance": 30}"#,
+    );
+    let resp = PeakRequests::new().get(&server.url("/buy")).unwrap();
+    let error = resp.error_for_status().unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        format!(
+            "HTTP status 403 for {}: Not enough credit: Balance is 30, cost is 50",
+            server.url("/buy")
+        )
+    );
+
+    let PeakError::Status {
+        problem: Some(problem),
+        ..
+    } = &error
+    else {
+        panic!("expected problem details, got {:?}", error);
+    };
+    assert_eq!(
+        problem.problem_type.as_deref(),
+        Some("https://example.com/probs/credit")
+    );
+    assert_eq!(problem.status, Some(403));
+    assert_eq!(problem.extensions["balance"], 30);
+}
+
+#[test]
+fn members_of_the_wrong_type_are_left_out() {
+    let server = failing(
+        "application/problem+json; charset=utf-8",
+        r#"{"title": 7, "status": "403", "detail": "still here"}"#,
+    );
+    let resp = PeakRequests::new().get(&server.url("/")).unwrap();
+    let problem = resp.problem().unwrap();
+    assert_eq!(problem.title, None);
+    assert_eq!(problem.status, None);
+    assert_eq!(problem.detail.as_deref(), Some("still here"));
+    assert!(problem.extensions.is_empty());
+}
+
+#[test]
+fn other_content_types_have_no_problem() {
+    let server = failing("application/json", r#"{"title": "nope"}"#);
+    let resp = PeakRequests::new().get(&server.url("/")).unwrap();
+    assert_eq!(resp.problem(), None);
+
+    let error = resp.error_for_status().unwrap_err();
+    assert!(matches!(error, PeakError::Status { problem: None, .. }));
+    assert_eq!(
+        error.to_string(),
+        format!("HTTP status 403 for {}", server.url("/"))
+    );
+}
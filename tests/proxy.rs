@@ -0,0 +1,54 @@
+mod common;
+
+use common::{ok, Server};
+use peakrequests::{GroupResult, PeakRequests, RequestGroup};
+use std::time::Duration;
+
+// the mock sees the absolute-form target a proxy gets, so it stands in for one
+fn proxy() -> Server {
+    Server::start(|req| ok(&format!("proxied {}", req.path)))
+}
+
+#[test]
+fn background_and_group_requests_use_the_client_proxy() {
+    let proxy = proxy();
+    let mut client = PeakRequests::new().proxy(&proxy.url(""));
+
+    let request = client.request("GET", "http://origin.invalid/bg").prepare();
+    let background = client
+        .send_background(request)
+        .wait(Duration::from_secs(5))
+        .unwrap()
+        .unwrap();
+    assert_eq!(background.text(), "proxied http://origin.invalid/bg");
+
+    let mut group = RequestGroup::new(Duration::from_secs(5));
+    group.add(
+        client
+            .request("GET", "http://origin.invalid/group")
+            .prepare(),
+    );
+    let GroupResult::Completed(grouped) = group.execute(&client, 1).remove(0) else {
+        panic!("group request did not complete");
+    };
+    assert_eq!(grouped.text(), "proxied http://origin.invalid/group");
+}
+
+#[test]
+fn request_override_replaces_the_client_proxy() {
+    let client_proxy = proxy();
+    let override_proxy = proxy();
+    let mut client = PeakRequests::new().proxy(&client_proxy.url(""));
+
+    let resp = client
+        .request("GET", "http://origin.invalid/swap")
+        .proxy(Some(&override_proxy.url("")))
+        .send()
+        .unwrap();
+    assert_eq!(resp.text(), "proxied http://origin.invalid/swap");
+    assert!(client_proxy.requests().is_empty());
+
+    let resp = client.get("http://origin.invalid/main").unwrap();
+    assert_eq!(resp.text(), "proxied http://origin.invalid/main");
+    assert_eq!(client_proxy.requests().len(), 1);
+}
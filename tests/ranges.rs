@@ -0,0 +1,52 @@
+mod common;
+
+use common::Server;
+use peakrequests::PeakRequests;
+
+const FIRST: &[u8] = &[0xff, 0x00, 0x80, 0xfe];
+const SECOND: &[u8] = &[0xc3, 0x28, 0x0a];
+
+fn byteranges() -> Vec<u8> {
+    let mut body = Vec::new();
+    body.extend_from_slice(b"--sep\r\nContent-Range: bytes 0-3/100\r\n\r\n");
+    body.extend_from_slice(FIRST);
+    body.extend_from_slice(b"\r\n--sep\r\nContent-Range: bytes 50-52/100\r\n\r\n");
+    body.extend_from_slice(SECOND);
+    body.extend_from_slice(b"\r\n--sep--\r\n");
+
+    let mut out = format!(
+        "HTTP/1.1 206 Partial Content\r\nConnection: close\r\n\
+         Content-Type: multipart/byteranges; boundary=sep\r\nContent-Length: {}\r\n\r\n",
+        body.len()
+    )
+    .into_bytes();
+    out.extend_from_slice(&body);
+    out
+}
+
+#[test]
+fn binary_ranges_survive_the_buffered_response() {
+    let server = Server::start(|_| byteranges());
+    let mut client = PeakRequests::new();
+    let url = server.url("/blob");
+
+    let buffered = client
+        .request("GET", &url)
+        .ranges(&[(0, Some(3)), (50, Some(52))])
+        .send()
+        .unwrap()
+        .byte_ranges()
+        .unwrap();
+    let streamed = client.get_stream(&url).unwrap().byte_ranges().unwrap();
+
+    assert_eq!(buffered, streamed);
+    assert_eq!(buffered.len(), 2);
+    assert_eq!((buffered[0].start, buffered[0].end), (0, 3));
+    assert_eq!(buffered[0].bytes, FIRST);
+    assert_eq!((buffered[1].start, buffered[1].end), (50, 52));
+    assert_eq!(buffered[1].bytes, SECOND);
+    assert_eq!(
+        server.requests()[0].header("range"),
+        Some("bytes=0-3,50-52")
+    );
+}
@@ -0,0 +1,212 @@
+mod common;
+
+use common::{ok, response, Server};
+use peakrequests::{PeakError, PeakRequests};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+fn client_with_credentials() -> PeakRequests {
+    let mut headers = HashMap::new();
+    headers.insert("Authorization".to_string(), "Bearer secret".to_string());
+    headers.insert("Cookie".to_string(), "sid=secret".to_string());
+    headers.insert("X-Trace".to_string(), "abc".to_string());
+    PeakRequests::new().headers(headers)
+}
+
+#[test]
+fn client_credentials_stay_behind_on_cross_origin_redirect() {
+    let other = Server::start(|_| ok("landed"));
+    let target = other.url("/landing");
+    let origin = Server::start(move |_| response("302 Found", &[("Location", &target)], ""));
+
+    let mut client = client_with_credentials();
+    let resp = client.get(&origin.url("/start")).unwrap();
+    assert_eq!(resp.text(), "landed");
+
+    let first = &origin.requests()[0];
+    assert_eq!(first.header("authorization"), Some("Bearer secret"));
+    assert_eq!(first.header("cookie"), Some("sid=secret"));
+
+    let hop = &other.requests()[0];
+    assert_eq!(hop.header("authorization"), None);
+    assert_eq!(hop.header("cookie"), None);
+    assert_eq!(hop.header("x-trace"), Some("abc"));
+}
+
+#[test]
+fn credentials_do_not_come_back_after_leaving_the_origin() {
+    // a -> b -> a: once the chain left a, a doesn't get them back either
+    let a_url = std::sync::Arc::new(std::sync::Mutex::new(String::new()));
+    let back = std::sync::Arc::clone(&a_url);
+    let b = Server::start(move |_| {
+        let location = format!("{}/home", back.lock().unwrap());
+        response("302 Found", &[("Location", &location)], "")
+    });
+    let b_url = b.url("/bounce");
+    let a = Server::start(move |request| match request.path.as_str() {
+        "/start" => response("302 Found", &[("Location", &b_url)], ""),
+        _ => ok("home"),
+    });
+    *a_url.lock().unwrap() = format!("http://{}", a.addr);
+
+    let resp = client_with_credentials().get(&a.url("/start")).unwrap();
+    assert_eq!(resp.text(), "home");
+    let home = a
+        .requests()
+        .into_iter()
+        .find(|r| r.path == "/home")
+        .unwrap();
+    assert_eq!(home.header("authorization"), None);
+}
+
+#[test]
+fn same_origin_redirect_keeps_client_headers() {
+    let server = Server::start(|request| match request.path.as_str() {
+        "/old" => response("301 Moved Permanently", &[("Location", "/new")], ""),
+        _ => ok("new"),
+    });
+    let resp = client_with_credentials().get(&server.url("/old")).unwrap();
+    assert_eq!(resp.text(), "new");
+    let hop = &server.requests()[1];
+    assert_eq!(hop.header("authorization"), Some("Bearer secret"));
+}
+
+#[test]
+fn request_headers_override_client_headers() {
+    let server = Server::start(|_| ok(""));
+    let mut client = client_with_credentials();
+    client
+        .request("GET", &server.url("/"))
+        .header("X-Trace", "mine")
+        .send()
+        .unwrap();
+    let seen = &server.requests()[0];
+    let traces: Vec<_> = seen
+        .headers
+        .iter()
+        .filter(|(k, _)| k == "x-trace")
+        .collect();
+    assert_eq!(traces.len(), 1);
+    assert_eq!(seen.header("x-trace"), Some("mine"));
+}
+
+#[test]
+fn a_panicking_redirect_hook_becomes_an_error() {
+    let other = Server::start(|_| ok("landed"));
+    let target = other.url("/landing");
+    let origin = Server::start(move |_| response("302 Found", &[("Location", &target)], ""));
+
+    let mut client = PeakRequests::new().on_redirect(|_| panic!("hook blew up"));
+    let error = client.get(&origin.url("/start")).unwrap_err();
+    assert!(
+        matches!(&error, PeakError::HookPanicked { hook: "on_redirect", message } if message == "hook blew up"),
+        "{:?}",
+        error
+    );
+    assert!(other.requests().is_empty());
+}
+
+#[test]
+fn the_redirect_hook_sees_every_hop_in_order() {
+    let server = Server::start(|request| match request.path.as_str() {
+        "/a" => response("301 Moved Permanently", &[("Location", "/b")], ""),
+        "/b" => response("302 Found", &[("Location", "/c")], ""),
+        "/c" => response("307 Temporary Redirect", &[("Location", "/d")], ""),
+        _ => ok("end"),
+    });
+    let events = Arc::new(Mutex::new(Vec::new()));
+    let seen = Arc::clone(&events);
+    let mut client = PeakRequests::new().on_redirect(move |event| {
+        seen.lock().unwrap().push((
+            event.hop,
+            event.status,
+            event.previous_url.to_string(),
+            event.next_url.to_string(),
+        ));
+    });
+
+    let resp = client.get(&server.url("/a")).unwrap();
+    assert_eq!(resp.text(), "end");
+    assert_eq!(
+        *events.lock().unwrap(),
+        vec![
+            (1, 301, server.url("/a"), server.url("/b")),
+            (2, 302, server.url("/b"), server.url("/c")),
+            (3, 307, server.url("/c"), server.url("/d")),
+        ]
+    );
+}
+
+fn unfollowed(location: Option<&str>, status: &str) -> peakrequests::Response {
+    let status = status.to_string();
+    let location = location.map(str::to_string);
+    let server = Server::start(move |_| match &location {
+        Some(location) => response(&status, &[("Location", location)], ""),
+        None => response(&status, &[], ""),
+    });
+    let mut client = PeakRequests::new().allow_redirects(false);
+    client.get(&server.url("/from")).unwrap()
+}
+
+#[test]
+fn redirect_info_resolves_the_target() {
+    let response = unfollowed(Some("/to?x=1"), "302 Found");
+    let info = response.redirect_info().unwrap();
+    assert_eq!(info.status, 302);
+    assert_eq!(
+        info.target.as_deref(),
+        Some(response.url.replace("/from", "/to?x=1").as_str())
+    );
+    assert!(!info.cross_origin);
+    assert!(!info.permanent);
+}
+
+#[test]
+fn redirect_info_flags_cross_origin_and_permanent() {
+    let info = unfollowed(Some("https://elsewhere.example/"), "308 Permanent Redirect")
+        .redirect_info()
+        .unwrap();
+    assert_eq!(info.target.as_deref(), Some("https://elsewhere.example/"));
+    assert!(info.cross_origin);
+    assert!(info.permanent);
+    assert!(
+        unfollowed(Some("/moved"), "301 Moved Permanently")
+            .redirect_info()
+            .unwrap()
+            .permanent
+    );
+}
+
+#[test]
+fn redirect_info_without_a_usable_location() {
+    let info = unfollowed(None, "307 Temporary Redirect")
+        .redirect_info()
+        .unwrap();
+    assert_eq!(info.target, None);
+    assert!(!info.cross_origin);
+
+    assert!(unfollowed(None, "304 Not Modified")
+        .redirect_info()
+        .is_none());
+    assert!(unfollowed(Some("/x"), "300 Multiple Choices")
+        .redirect_info()
+        .is_none());
+}
+
+#[test]
+fn redirect_info_method_changes_match_the_loop() {
+    let see_other = unfollowed(Some("/x"), "303 See Other")
+        .redirect_info()
+        .unwrap();
+    assert!(see_other.changes_method("PUT"));
+    assert!(!see_other.changes_method("HEAD"));
+
+    let found = unfollowed(Some("/x"), "302 Found").redirect_info().unwrap();
+    assert!(found.changes_method("post"));
+    assert!(!found.changes_method("PUT"));
+
+    let temporary = unfollowed(Some("/x"), "307 Temporary Redirect")
+        .redirect_info()
+        .unwrap();
+    assert!(!temporary.changes_method("POST"));
+}
@@ -0,0 +1,55 @@
+mod common;
+
+use common::{ok, response, Server};
+use peakrequests::{PeakError, PeakRequests};
+
+#[test]
+fn relays_the_body_and_its_type() {
+    let body = "x".repeat(100_000);
+    let source_body = body.clone();
+    let source = Server::start(move |_| {
+        response(
+            "200 OK",
+            &[("Content-Type", "application/octet-stream")],
+            &source_body,
+        )
+    });
+    let destination = Server::start(|_| response("201 Created", &[], "stored"));
+    let mut client = PeakRequests::new();
+
+    let from = client.get_stream(&source.url("/blob")).unwrap();
+    let resp = client
+        .relay(from, "PUT", &destination.url("/copy"))
+        .unwrap();
+    assert_eq!(resp.status_code, 201);
+    assert_eq!(resp.text(), "stored");
+
+    let requests = destination.requests();
+    assert_eq!(requests[0].method, "PUT");
+    assert_eq!(requests[0].path, "/copy");
+    assert_eq!(
+        requests[0].header("content-type"),
+        Some("application/octet-stream")
+    );
+    assert_eq!(requests[0].header("content-length"), Some("100000"));
+    assert_eq!(requests[0].body, body.as_bytes());
+}
+
+#[test]
+fn a_source_cut_short_is_blamed_on_the_source() {
+    let source = Server::start(|_| {
+        "HTTP/1.1 200 OK\r\nConnection: close\r\nContent-Length: 100\r\n\r\nonly ten b"
+    });
+    let destination = Server::start(|_| ok(""));
+    let mut client = PeakRequests::new();
+
+    let from = client.get_stream(&source.url("/blob")).unwrap();
+    let error = client
+        .relay(from, "PUT", &destination.url("/copy"))
+        .unwrap_err();
+    assert!(
+        matches!(&error, PeakError::RelaySource { url, .. } if url.ends_with("/blob")),
+        "{:?}",
+        error
+    );
+}
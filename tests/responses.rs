@@ -0,0 +1,59 @@
+mod common;
+
+use common::{ok, response, Server};
+use peakrequests::{GroupResult, PeakError, PeakRequests, PreparedRequest, RequestGroup};
+use std::time::Duration;
+
+const HELLO_SHA256: &str = "2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824";
+
+fn get(client: &mut PeakRequests, url: &str) -> PreparedRequest {
+    client.request("GET", url).prepare()
+}
+
+fn group(client: &mut PeakRequests, url: &str) -> GroupResult {
+    let mut group = RequestGroup::new(Duration::from_secs(5));
+    group.add(get(client, url));
+    group.execute(client, 1).remove(0)
+}
+
+#[test]
+fn every_path_digests_and_records_the_attempt() {
+    let server = Server::start(|_| ok("hello"));
+    let mut client = PeakRequests::new().compute_digest(true);
+    let url = server.url("/");
+
+    let direct = client.get(&url).unwrap();
+    let request = get(&mut client, &url);
+    let background = client
+        .send_background(request)
+        .wait(Duration::from_secs(5))
+        .unwrap()
+        .unwrap();
+    let GroupResult::Completed(grouped) = group(&mut client, &url) else {
+        panic!("group request did not complete");
+    };
+
+    for response in [direct, background, grouped] {
+        assert_eq!(response.sha256(), Some(HELLO_SHA256));
+        assert_eq!(response.attempts().len(), 1);
+        assert_eq!(response.text(), "hello");
+    }
+}
+
+#[test]
+fn header_limits_apply_off_the_main_path() {
+    let server = Server::start(|_| response("200 OK", &[("X-One", "1"), ("X-Two", "2")], "ok"));
+    let mut client = PeakRequests::new().max_header_count(2);
+    let url = server.url("/");
+
+    let request = get(&mut client, &url);
+    let background = client
+        .send_background(request)
+        .wait(Duration::from_secs(5))
+        .unwrap();
+    assert!(matches!(background, Err(PeakError::HeadersTooLarge { .. })));
+    assert!(matches!(
+        group(&mut client, &url),
+        GroupResult::Failed(PeakError::HeadersTooLarge { .. })
+    ));
+}
@@ -0,0 +1,92 @@
+mod common;
+
+use common::{ok, response, Server};
+use peakrequests::{
+    AttemptOutcome, BulkOptions, DownloadJob, DownloadOutcome, PeakError, PeakRequests,
+};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+
+// answers 503 until it has been asked `failures` times
+fn flaky(failures: usize) -> Server {
+    let seen = AtomicUsize::new(0);
+    Server::start(move |_| {
+        if seen.fetch_add(1, Ordering::SeqCst) < failures {
+            response("503 Service Unavailable", &[], "later")
+        } else {
+            ok("up")
+        }
+    })
+}
+
+fn statuses(attempts: &[peakrequests::AttemptRecord]) -> Vec<AttemptOutcome> {
+    attempts
+        .iter()
+        .map(|attempt| attempt.outcome.clone())
+        .collect()
+}
+
+#[test]
+fn expectation_retries_until_it_matches() {
+    let server = flaky(2);
+    let mut client = PeakRequests::new();
+
+    let resp = client
+        .request("GET", &server.url("/health"))
+        .expectation_retries(3, Duration::ZERO)
+        .send_expecting_body(200, "up")
+        .unwrap();
+    assert_eq!(server.requests().len(), 3);
+    assert_eq!(
+        statuses(resp.attempts()),
+        [
+            AttemptOutcome::Status(503),
+            AttemptOutcome::Status(503),
+            AttemptOutcome::Status(200),
+        ]
+    );
+}
+
+#[test]
+fn exhausted_retries_keep_every_attempt() {
+    let server = flaky(usize::MAX);
+    let mut client = PeakRequests::new();
+
+    let error = client
+        .request("GET", &server.url("/health"))
+        .expectation_retries(1, Duration::ZERO)
+        .send_expecting(200)
+        .unwrap_err();
+    assert!(matches!(error, PeakError::RetriesExhausted { .. }));
+    assert_eq!(
+        statuses(error.attempts()),
+        [AttemptOutcome::Status(503), AttemptOutcome::Status(503)]
+    );
+    assert_eq!(server.requests().len(), 2);
+}
+
+#[test]
+fn downloads_retry_and_say_so() {
+    let server = flaky(1);
+    let destination = std::env::temp_dir().join(format!("peak-retry-{}", std::process::id()));
+
+    let outcomes = PeakRequests::new().emit_retry_headers(true).download_all(
+        vec![DownloadJob::new(&server.url("/file"), &destination)],
+        BulkOptions::new().concurrency(1).retries(1),
+    );
+    let body = std::fs::read_to_string(&destination).unwrap();
+    std::fs::remove_file(&destination).unwrap();
+    assert!(
+        matches!(outcomes[..], [DownloadOutcome::Downloaded { bytes: 2, .. }]),
+        "{:?}",
+        outcomes
+    );
+    assert_eq!(body, "up");
+
+    let requests = server.requests();
+    assert_eq!(requests.len(), 2);
+    assert_eq!(requests[0].header("x-retry-attempt"), None);
+    assert_eq!(requests[1].header("x-retry-attempt"), Some("2"));
+    assert_eq!(requests[1].header("x-retry-reason"), Some("status-503"));
+    assert!(requests[1].header("x-retry-first-attempt").is_some());
+}
@@ -0,0 +1,130 @@
+mod common;
+
+use common::{ok, response, Server};
+use peakrequests::{PeakError, PeakRequests};
+
+fn site(robots: &'static str) -> Server {
+    Server::start(move |request| match request.path.as_str() {
+        "/robots.txt" => ok(robots),
+        _ => ok("page"),
+    })
+}
+
+fn allowed(client: &mut PeakRequests, server: &Server, path: &str) -> bool {
+    match client.get(&server.url(path)) {
+        Ok(_) => true,
+        Err(PeakError::DisallowedByRobots { .. }) => false,
+        Err(e) => panic!("{} failed: {}", path, e),
+    }
+}
+
+#[test]
+fn disallowed_paths_are_refused_before_sending() {
+    let server = site("User-agent: *\nDisallow: /private\n");
+    let mut client = PeakRequests::new().respect_robots_txt("examplebot");
+
+    let error = client.get(&server.url("/private/page")).unwrap_err();
+    assert!(
+        matches!(&error, PeakError::DisallowedByRobots { rule } if rule == "Disallow: /private"),
+        "{:?}",
+        error
+    );
+    assert!(allowed(&mut client, &server, "/public"));
+
+    let paths: Vec<String> = server.requests().into_iter().map(|r| r.path).collect();
+    assert_eq!(paths, ["/robots.txt", "/public"]);
+}
+
+#[test]
+fn the_longest_rule_wins_and_wildcards_match() {
+    let server = site(
+        "User-agent: *\n\
+         Disallow: /docs\n\
+         Allow: /docs/public\n\
+         Disallow: /*.pdf$\n",
+    );
+    let mut client = PeakRequests::new().respect_robots_txt("examplebot");
+    assert!(!allowed(&mut client, &server, "/docs/secret"));
+    assert!(allowed(&mut client, &server, "/docs/public/readme"));
+    assert!(!allowed(&mut client, &server, "/files/report.pdf"));
+    assert!(allowed(&mut client, &server, "/files/report.pdf.html"));
+}
+
+#[test]
+fn groups_match_by_product_token_ignoring_case() {
+    let server = site(
+        "User-agent: *\n\
+         Disallow: /\n\
+         \n\
+         User-agent: ExampleBot\n\
+         Disallow: /drafts\n",
+    );
+    let mut client =
+        PeakRequests::new().respect_robots_txt("examplebot/2.1 (+https://example.com/bot)");
+    assert!(allowed(&mut client, &server, "/articles"));
+    assert!(!allowed(&mut client, &server, "/drafts/1"));
+
+    // a different product that merely starts the same way falls back to *
+    let mut client = PeakRequests::new().respect_robots_txt("ExampleBotExtra");
+    assert!(!allowed(&mut client, &server, "/articles"));
+}
+
+#[test]
+fn a_missing_robots_txt_allows_everything() {
+    let server = Server::start(|request| match request.path.as_str() {
+        "/robots.txt" => response("404 Not Found", &[], ""),
+        _ => ok("page"),
+    });
+    let mut client = PeakRequests::new().respect_robots_txt("examplebot");
+    assert!(allowed(&mut client, &server, "/anything"));
+}
+
+#[cfg(feature = "testing")]
+#[test]
+fn the_crawl_delay_spaces_out_requests() {
+    use peakrequests::testing::clock::FakeClock;
+    use std::time::Duration;
+
+    let server = site("User-agent: *\nCrawl-delay: 5\n");
+    let clock = FakeClock::new();
+    let mut client = PeakRequests::new()
+        .respect_robots_txt("examplebot")
+        .with_clock(clock.clone());
+    for page in 0..3 {
+        client.get(&server.url(&format!("/{}", page))).unwrap();
+    }
+    assert_eq!(clock.elapsed(), Duration::from_secs(10));
+}
+
+#[test]
+fn rules_and_the_cache_see_the_normalized_url() {
+    let server = site("User-agent: *\nDisallow: /~private\n");
+    let mut client = PeakRequests::new().respect_robots_txt("examplebot");
+
+    assert!(!allowed(&mut client, &server, "/%7Eprivate/page"));
+    assert!(!allowed(&mut client, &server, "/%7eprivate"));
+    let shouted = server.url("/public").replacen("http", "HTTP", 1);
+    client.get(&shouted).unwrap();
+
+    let robots = server
+        .requests()
+        .into_iter()
+        .filter(|r| r.path == "/robots.txt")
+        .count();
+    assert_eq!(robots, 1);
+}
+
+#[test]
+fn opt_in_normalization_applies_to_rules_too() {
+    use peakrequests::UrlNormalization;
+
+    let server = site("User-agent: *\nDisallow: /search?a=\n");
+    let mut client = PeakRequests::new()
+        .respect_robots_txt("examplebot")
+        .url_normalization(UrlNormalization {
+            sort_query: true,
+            ..Default::default()
+        });
+    assert!(!allowed(&mut client, &server, "/search?b=2&a=1"));
+    assert!(allowed(&mut client, &server, "/search?b=2"));
+}
@@ -0,0 +1,100 @@
+mod common;
+
+use common::{ok, response, Server};
+use peakrequests::{Overrides, PeakError, PeakRequests};
+use std::thread;
+use std::time::Duration;
+
+fn slow_server() -> Server {
+    Server::start(|request| {
+        if request.path == "/slow" {
+            thread::sleep(Duration::from_millis(400));
+        }
+        ok("done")
+    })
+}
+
+fn is_timeout(result: Result<peakrequests::Response, PeakError>) -> bool {
+    matches!(result, Err(PeakError::Http(error)) if error.is_timeout())
+}
+
+#[test]
+fn scoped_headers_fill_in_without_overriding() {
+    let server = Server::start(|_| ok(""));
+    let mut client = PeakRequests::new();
+    {
+        let mut scoped = client.scoped(
+            Overrides::new()
+                .header("X-Tenant", "blue")
+                .header("X-Trace", "scoped"),
+        );
+        scoped.get(&server.url("/one")).unwrap();
+        scoped
+            .request("GET", &server.url("/two"))
+            .header("X-Trace", "mine")
+            .send()
+            .unwrap();
+    }
+    client.get(&server.url("/after")).unwrap();
+
+    let requests = server.requests();
+    assert_eq!(requests[0].header("x-tenant"), Some("blue"));
+    assert_eq!(requests[0].header("x-trace"), Some("scoped"));
+    assert_eq!(requests[1].header("x-tenant"), Some("blue"));
+    let traces: Vec<_> = requests[1]
+        .headers
+        .iter()
+        .filter(|(key, _)| key == "x-trace")
+        .map(|(_, value)| value.as_str())
+        .collect();
+    assert_eq!(traces, ["mine"]);
+    assert_eq!(requests[2].header("x-tenant"), None);
+}
+
+#[test]
+fn the_scoped_timeout_ends_with_the_scope() {
+    let server = slow_server();
+    let mut client = PeakRequests::new();
+    let mut scoped = client.scoped(Overrides::new().timeout(Duration::from_millis(100)));
+    assert!(is_timeout(scoped.get(&server.url("/slow"))));
+    assert!(scoped.get(&server.url("/fast")).is_ok());
+    drop(scoped);
+
+    assert_eq!(client.get(&server.url("/slow")).unwrap().text(), "done");
+}
+
+#[test]
+fn a_request_timeout_outranks_the_scope() {
+    let server = slow_server();
+    let mut client = PeakRequests::new();
+    let mut scoped = client.scoped(Overrides::new().timeout(Duration::from_millis(100)));
+    let response = scoped
+        .request("GET", &server.url("/slow"))
+        .timeout(Duration::from_secs(5))
+        .send()
+        .unwrap();
+    assert_eq!(response.text(), "done");
+}
+
+#[test]
+fn redirect_settings_are_scoped() {
+    let server = Server::start(|request| match request.path.as_str() {
+        "/a" => response("302 Found", &[("Location", "/b")], ""),
+        "/b" => response("302 Found", &[("Location", "/c")], ""),
+        _ => ok("landed"),
+    });
+    let mut client = PeakRequests::new();
+
+    let mut scoped = client.scoped(Overrides::new().allow_redirects(false));
+    assert_eq!(scoped.get(&server.url("/a")).unwrap().status_code, 302);
+
+    let mut scoped = client.scoped(Overrides::new().max_redirects(1));
+    let error = scoped.get(&server.url("/a")).unwrap_err();
+    assert!(
+        matches!(error, PeakError::TooManyRedirects { max: 1 }),
+        "{:?}",
+        error
+    );
+
+    assert_eq!(client.get(&server.url("/a")).unwrap().text(), "landed");
+}
@@ -0,0 +1,54 @@
+#![cfg(feature = "scrape")]
+
+mod common;
+
+use common::{ok, Server};
+use peakrequests::{PeakError, PeakRequests, Response};
+
+const PAGE: &str = r#"<html><head><title>Nuts &amp; Bolts</title></head>
+<body><a href="/a">A</a> <a href="/b">B</a> <span>price: 42</span></body></html>"#;
+
+fn page() -> Response {
+    let server = Server::start(|_| ok(PAGE));
+    PeakRequests::new().get(&server.url("/")).unwrap()
+}
+
+#[test]
+fn contains_looks_at_the_decoded_text() {
+    let response = page();
+    assert!(response.contains("price: 42"));
+    assert!(!response.contains("price: 43"));
+}
+
+#[test]
+fn extract_prefers_the_first_group() {
+    let response = page();
+    assert_eq!(
+        response.extract("<title>(.*?)</title>").unwrap().as_deref(),
+        Some("Nuts &amp; Bolts")
+    );
+    assert_eq!(
+        response.extract(r"price: \d+").unwrap().as_deref(),
+        Some("price: 42")
+    );
+    assert_eq!(response.extract("<table>").unwrap(), None);
+}
+
+#[test]
+fn extract_all_returns_every_match_in_order() {
+    let response = page();
+    assert_eq!(
+        response.extract_all(r#"href="([^"]+)""#).unwrap(),
+        ["/a", "/b"]
+    );
+    assert!(response.extract_all("<table>").unwrap().is_empty());
+}
+
+#[test]
+fn a_bad_pattern_is_an_error() {
+    let response = page();
+    let error = response.extract("(unclosed").unwrap_err();
+    assert!(matches!(error, PeakError::InvalidPattern(_)), "{:?}", error);
+    // and stays one the second time, nothing bad got cached
+    assert!(response.extract_all("(unclosed").is_err());
+}
@@ -0,0 +1,116 @@
+mod common;
+
+use common::{ok, Server};
+use peakrequests::{MirrorConfig, PeakError, PeakRequests};
+use std::thread;
+use std::time::{Duration, Instant};
+
+fn slow(millis: u64) -> Server {
+    Server::start(move |_| {
+        thread::sleep(Duration::from_millis(millis));
+        ok("late")
+    })
+}
+
+#[test]
+fn finished_background_work_counts_as_completed() {
+    let server = slow(50);
+    let client = PeakRequests::new().background_workers(2);
+    let handles: Vec<_> = (0..2)
+        .map(|_| client.send_background(client_get(&server)))
+        .collect();
+
+    let report = client.shutdown(Duration::from_secs(5));
+    assert_eq!(report.completed, 2);
+    assert_eq!(report.abandoned, 0);
+    assert!(report.errors.is_empty());
+    for handle in handles {
+        assert!(handle.try_result().unwrap().is_ok());
+    }
+}
+
+#[test]
+fn queued_background_work_is_closed_after_the_grace_period() {
+    let server = slow(400);
+    let client = PeakRequests::new().background_workers(1);
+    let running = client.send_background(client_get(&server));
+    let queued = client.send_background(client_get(&server));
+
+    let started = Instant::now();
+    let report = client.shutdown(Duration::from_millis(100));
+    assert!(started.elapsed() < Duration::from_millis(350));
+    assert_eq!(report.completed, 0);
+    assert_eq!(report.abandoned, 2);
+
+    assert!(running.wait(Duration::from_secs(5)).unwrap().is_ok());
+    let error = queued.wait(Duration::from_secs(5)).unwrap().unwrap_err();
+    assert!(matches!(error, PeakError::ClientClosed), "{:?}", error);
+    assert_eq!(server.requests().len(), 1);
+}
+
+#[test]
+fn queued_mirror_replays_are_skipped() {
+    let primary = Server::start(|_| ok(""));
+    let mirror = slow(300);
+    let mut client = PeakRequests::new().mirror(MirrorConfig {
+        target_base_url: mirror.url(""),
+        sample_rate: 1.0,
+        queue: 8,
+        ..Default::default()
+    });
+    for n in 0..3 {
+        client.get(&primary.url(&format!("/{}", n))).unwrap();
+    }
+
+    let report = client.shutdown(Duration::from_millis(100));
+    assert_eq!(report.abandoned, 3);
+    thread::sleep(Duration::from_millis(800));
+    assert_eq!(mirror.requests().len(), 1);
+}
+
+#[cfg(feature = "json")]
+#[test]
+fn archive_records_are_written_before_shutdown_returns() {
+    use peakrequests::ArchiveConfig;
+
+    let server = Server::start(|_| ok("archived"));
+    let dir = std::env::temp_dir().join(format!("peak-shutdown-archive-{}", std::process::id()));
+    let mut client = PeakRequests::new().archive(ArchiveConfig {
+        dir: dir.clone(),
+        ..Default::default()
+    });
+    for n in 0..5 {
+        client.get(&server.url(&format!("/{}", n))).unwrap();
+    }
+
+    let report = client.shutdown(Duration::from_secs(5));
+    let written = std::fs::read_dir(&dir).unwrap().count();
+    std::fs::remove_dir_all(&dir).unwrap();
+    assert_eq!(written, 5);
+    assert_eq!(report.abandoned, 0);
+}
+
+#[cfg(feature = "json")]
+#[test]
+fn cookies_go_back_to_the_file_they_came_from() {
+    let server = Server::start(|_| {
+        common::response("200 OK", &[("Set-Cookie", "fresh=1; Max-Age=3600")], "")
+    });
+    let path = std::env::temp_dir().join(format!("peak-shutdown-cookies-{}", std::process::id()));
+    std::fs::write(&path, "[]").unwrap();
+
+    let mut client = PeakRequests::new().load_cookies(&path).unwrap();
+    client.get(&server.url("/")).unwrap();
+    let report = client.shutdown(Duration::from_secs(1));
+    assert!(report.errors.is_empty(), "{:?}", report.errors);
+
+    let saved = std::fs::read_to_string(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert!(saved.contains("fresh"), "{}", saved);
+}
+
+fn client_get(server: &Server) -> peakrequests::PreparedRequest {
+    PeakRequests::new()
+        .request("GET", &server.url("/"))
+        .prepare()
+}
@@ -0,0 +1,79 @@
+#![cfg(feature = "json")]
+
+mod common;
+
+use common::{ok, Server};
+use peakrequests::{PeakRequests, RequestSpec, SpecBody, SpecProxy};
+use serde_json::json;
+use std::time::Duration;
+
+fn round_trip(spec: &RequestSpec) -> RequestSpec {
+    serde_json::from_str(&serde_json::to_string(spec).unwrap()).unwrap()
+}
+
+#[test]
+fn a_prepared_request_survives_serialization() {
+    let server = Server::start(|_| ok(""));
+    let mut client = PeakRequests::new();
+    let prepared = client
+        .request("post", &server.url("/items?x=1"))
+        .header("X-Tag", "nightly")
+        .json(json!({"name": "acorn"}))
+        .timeout(Duration::from_millis(1500))
+        .proxy(None)
+        .prepare();
+    let spec = RequestSpec::from_prepared(&prepared);
+    assert_eq!(spec.method, "POST");
+    assert_eq!(spec.body, SpecBody::Json(json!({"name": "acorn"})));
+    assert_eq!(spec.options.timeout, Some(Duration::from_millis(1500)));
+    assert_eq!(spec.options.proxy, Some(SpecProxy::Direct));
+
+    let restored = round_trip(&spec);
+    assert_eq!(restored, spec);
+
+    client.execute(&restored).unwrap();
+    let requests = server.requests();
+    assert_eq!(requests[0].method, "POST");
+    assert_eq!(requests[0].path, "/items?x=1");
+    assert_eq!(requests[0].header("x-tag"), Some("nightly"));
+    assert_eq!(requests[0].body, br#"{"name":"acorn"}"#);
+}
+
+#[test]
+fn bytes_go_over_the_wire_as_base64() {
+    let mut spec = RequestSpec::new("put", "http://example.invalid/blob");
+    spec.body = SpecBody::Bytes(vec![0, 159, 146, 150]);
+    let encoded = serde_json::to_value(&spec).unwrap();
+    assert_eq!(encoded["body"], json!({"bytes": "AJ+Slg=="}));
+    assert_eq!(round_trip(&spec), spec);
+}
+
+#[test]
+fn a_form_body_round_trips_and_is_sent() {
+    let server = Server::start(|_| ok(""));
+    let mut spec = RequestSpec::new("POST", &server.url("/login"));
+    spec.body = SpecBody::Form(vec![("user".to_string(), "squirrel".to_string())]);
+    let restored = round_trip(&spec);
+    assert_eq!(restored, spec);
+
+    PeakRequests::new().execute(&restored).unwrap();
+    let requests = server.requests();
+    assert_eq!(
+        requests[0].header("content-type"),
+        Some("application/x-www-form-urlencoded")
+    );
+    assert_eq!(requests[0].body, b"user=squirrel");
+}
+
+#[test]
+fn a_minimal_spec_fills_in_defaults() {
+    let server = Server::start(|_| ok(""));
+    let spec: RequestSpec =
+        serde_json::from_value(json!({"method": "get", "url": server.url("/plain")})).unwrap();
+    assert_eq!(spec.body, SpecBody::Empty);
+    assert!(spec.headers.is_empty());
+    assert_eq!(spec.options.timeout, None);
+
+    PeakRequests::new().execute(&spec).unwrap();
+    assert_eq!(server.requests()[0].method, "GET");
+}
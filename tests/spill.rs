@@ -0,0 +1,138 @@
+mod common;
+
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use common::{ok, response_bytes, Server};
+use peakrequests::PeakRequests;
+use sha2::{Digest, Sha256, Sha512};
+use std::fs;
+use std::io::Read;
+use std::path::PathBuf;
+
+fn scratch(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("peak-spill-{}-{}", name, std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+fn files_in(dir: &PathBuf) -> usize {
+    fs::read_dir(dir).unwrap().count()
+}
+
+fn big_body() -> Vec<u8> {
+    (0..4 * 1024 * 1024).map(|n| (n % 251) as u8).collect()
+}
+
+#[test]
+fn small_bodies_stay_in_memory() {
+    let dir = scratch("small");
+    let server = Server::start(|_| ok("tiny"));
+    let mut client = PeakRequests::new().spill_to_disk_over(1024, Some(dir.clone()));
+    let response = client.get(&server.url("/")).unwrap();
+    assert!(!response.is_spilled());
+    assert_eq!(response.text(), "tiny");
+    assert_eq!(files_in(&dir), 0);
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn large_bodies_go_to_disk_and_read_back_exactly() {
+    let dir = scratch("large");
+    let body = big_body();
+    let served = body.clone();
+    let server = Server::start(move |_| response_bytes("200 OK", &[], &served));
+    let mut client = PeakRequests::new()
+        .compute_digest(true)
+        .spill_to_disk_over(1024 * 1024, Some(dir.clone()));
+
+    let response = client.get(&server.url("/")).unwrap();
+    assert!(response.is_spilled());
+    assert_eq!(files_in(&dir), 1);
+    assert_eq!(response.bytes(), body);
+    assert_eq!(
+        response.sha256(),
+        Some(format!("{:x}", Sha256::digest(&body)).as_str())
+    );
+
+    let mut streamed = Vec::new();
+    response
+        .body_reader()
+        .unwrap()
+        .read_to_end(&mut streamed)
+        .unwrap();
+    assert_eq!(streamed, body);
+    assert_eq!(response.bytes_ref().len(), body.len());
+
+    drop(response);
+    assert_eq!(files_in(&dir), 0);
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn a_chunked_body_spills_once_it_passes_the_threshold() {
+    let dir = scratch("chunked");
+    let server = Server::start(|_| {
+        let mut out = String::from(
+            "HTTP/1.1 200 OK\r\nContent-Type: text/plain; charset=utf-8\r\n\
+             Transfer-Encoding: chunked\r\nConnection: close\r\n\r\n",
+        );
+        for _ in 0..64 {
+            out.push_str(&format!("400\r\n{}\r\n", "é".repeat(512)));
+        }
+        out.push_str("0\r\n\r\n");
+        out
+    });
+    let mut client = PeakRequests::new().spill_to_disk_over(16 * 1024, Some(dir.clone()));
+    let response = client.get(&server.url("/")).unwrap();
+    assert!(response.is_spilled());
+    let text = response.text();
+    assert_eq!(text.chars().count(), 64 * 512);
+    assert!(text.chars().all(|c| c == 'é'));
+    drop(text);
+    drop(response);
+    assert_eq!(files_in(&dir), 0);
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn clones_share_the_file_until_the_last_one_drops() {
+    let dir = scratch("clones");
+    let body = big_body();
+    let served = body.clone();
+    let server = Server::start(move |_| response_bytes("200 OK", &[], &served));
+    let mut client = PeakRequests::new().spill_to_disk_over(1024, Some(dir.clone()));
+
+    let response = client.get(&server.url("/")).unwrap();
+    let copy = response.clone();
+    drop(response);
+    assert_eq!(files_in(&dir), 1);
+    assert_eq!(copy.bytes().len(), body.len());
+    drop(copy);
+    assert_eq!(files_in(&dir), 0);
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn digests_are_verified_on_spilled_bodies() {
+    let dir = scratch("digest");
+    let body = big_body();
+    let header = format!("sha-512=:{}:", STANDARD.encode(Sha512::digest(&body)));
+    let server = Server::start(move |request| {
+        let mut sent = body.clone();
+        if request.path == "/tampered" {
+            sent[0] ^= 1;
+        }
+        response_bytes("200 OK", &[("Repr-Digest", &header)], &sent)
+    });
+    let mut client = PeakRequests::new()
+        .compute_digest(true)
+        .spill_to_disk_over(1024, Some(dir.clone()));
+
+    let good = client.get(&server.url("/")).unwrap();
+    assert!(good.is_spilled());
+    assert_eq!(good.digest_verified(), Some(true));
+    let bad = client.get(&server.url("/tampered")).unwrap();
+    assert_eq!(bad.digest_verified(), Some(false));
+    drop((good, bad));
+    fs::remove_dir_all(&dir).unwrap();
+}
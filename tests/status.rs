@@ -0,0 +1,32 @@
+mod common;
+
+use common::{response, Server};
+use peakrequests::{PeakError, PeakRequests};
+
+#[test]
+fn upgrade_required_keeps_the_response() {
+    let server = Server::start(|_| {
+        response(
+            "426 Upgrade Required",
+            &[("Upgrade", "HTTP/2.0, websocket"), ("Retry-After", "5")],
+            "switch please",
+        )
+    });
+
+    let error = PeakRequests::new()
+        .get(&server.url("/"))
+        .unwrap()
+        .error_for_status()
+        .unwrap_err();
+    let PeakError::UpgradeRequired { protocols, .. } = &error else {
+        panic!("expected UpgradeRequired, got {:?}", error);
+    };
+    assert_eq!(protocols, &["HTTP/2.0", "websocket"]);
+    let response = error.response().unwrap();
+    assert_eq!(response.status_code, 426);
+    assert_eq!(
+        response.headers.get("retry-after").map(String::as_str),
+        Some("5")
+    );
+    assert_eq!(response.text(), "switch please");
+}
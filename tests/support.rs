@@ -0,0 +1,76 @@
+use peakrequests::{PeakError, PeakRequests};
+
+// the capability check runs when the client is built, before anything connects
+fn first_request(mut client: PeakRequests) -> Result<peakrequests::Response, PeakError> {
+    client.get("http://127.0.0.1:1/")
+}
+
+fn assert_unsupported(client: PeakRequests, option: &str, needed_feature: &str) {
+    match first_request(client) {
+        Err(PeakError::UnsupportedFeature {
+            option: got_option,
+            needed_feature: got_feature,
+        }) => {
+            assert_eq!(got_option, option);
+            assert_eq!(got_feature, needed_feature);
+        }
+        other => panic!(
+            "expected UnsupportedFeature for {}, got {:?}",
+            option, other
+        ),
+    }
+}
+
+#[test]
+fn revocation_checks_are_refused_by_every_build() {
+    assert_unsupported(
+        PeakRequests::new().check_revocation(true),
+        "check_revocation",
+        "a tls backend with revocation checking",
+    );
+}
+
+#[cfg(not(feature = "json"))]
+#[test]
+fn captive_portal_detection_needs_json() {
+    assert_unsupported(
+        PeakRequests::new().detect_captive_portal(true),
+        "detect_captive_portal",
+        "the json feature",
+    );
+}
+
+#[cfg(not(unix))]
+#[test]
+fn unix_sockets_need_a_unix_target() {
+    assert_unsupported(
+        PeakRequests::new().unix_socket(std::path::Path::new("/tmp/peakreq.sock")),
+        "unix_socket",
+        "a unix target",
+    );
+}
+
+#[cfg(not(feature = "json"))]
+#[test]
+fn best_effort_options_build_and_warn() {
+    let client = PeakRequests::new()
+        .cookies(true)
+        .persist_session_cookies(true);
+    let warnings = client.validate();
+    assert_eq!(warnings.len(), 1);
+    assert_eq!(warnings[0].setting, "persist_session_cookies");
+    assert_eq!(
+        warnings[0].conflicts_with,
+        "building without the json feature"
+    );
+
+    let error = first_request(client).unwrap_err();
+    assert!(matches!(error, PeakError::Http(_)), "{:?}", error);
+}
+
+#[cfg(feature = "json")]
+#[test]
+fn options_the_build_supports_are_not_refused() {
+    let error = first_request(PeakRequests::new().detect_captive_portal(true)).unwrap_err();
+    assert!(matches!(error, PeakError::Http(_)), "{:?}", error);
+}
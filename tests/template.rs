@@ -0,0 +1,83 @@
+mod common;
+
+use common::{ok, Server};
+use peakrequests::{PeakError, PeakRequests, PlaceholderEncoding, RequestTemplate};
+
+fn template(url: &str) -> RequestTemplate {
+    PeakRequests::new()
+        .request("GET", url)
+        .header("X-Tenant", "{tenant}")
+        .header("Accept", "application/json")
+        .template()
+        .unwrap()
+}
+
+#[test]
+fn reserved_characters_are_encoded_per_placeholder() {
+    let template = template("https://api.example/{segment}/items?q={query}&id={id}")
+        .encoding("segment", PlaceholderEncoding::PathSegment)
+        .encoding("query", PlaceholderEncoding::Query);
+    let request = template
+        .render(&[
+            ("segment", "a/b?c#d e:f@g"),
+            ("query", "x y&z=1#frag/ok?"),
+            ("id", "50%/ü+~"),
+            ("tenant", "acme"),
+        ])
+        .unwrap();
+    assert_eq!(
+        request.url(),
+        "https://api.example/a%2Fb%3Fc%23d%20e:f@g/items?q=x+y%26z%3D1%23frag/ok?&id=50%25%2F%C3%BC%2B~"
+    );
+}
+
+#[test]
+fn header_placeholders_share_the_values() {
+    let template = template("https://api.example/{tenant}/items");
+    let request = template.render(&[("tenant", "a/b c")]).unwrap();
+
+    assert_eq!(template.placeholders(), ["tenant"]);
+    assert_eq!(request.url(), "https://api.example/a%2Fb%20c/items");
+    assert_eq!(
+        request.headers(),
+        [
+            ("x-tenant".to_string(), "a/b c".to_string()),
+            ("accept".to_string(), "application/json".to_string()),
+        ]
+    );
+}
+
+#[test]
+fn header_values_cannot_smuggle_lines() {
+    let template = template("https://api.example/items");
+    let result = template.render(&[("tenant", "acme\r\nX-Admin: 1")]);
+    assert!(matches!(result, Err(PeakError::InvalidHeader(_))));
+}
+
+#[test]
+fn invalid_fixed_headers_fail_when_the_template_is_built() {
+    let result = PeakRequests::new()
+        .request("GET", "https://api.example/")
+        .header("Bad Name", "x")
+        .template();
+    assert!(matches!(result, Err(PeakError::InvalidHeader(_))));
+}
+
+#[test]
+fn non_ascii_fixed_headers_are_kept() {
+    let server = Server::start(|_| ok(""));
+    let mut client = PeakRequests::new();
+    let template = client
+        .request("GET", &server.url("/{id}"))
+        .header("X-Name", "café")
+        .template()
+        .unwrap();
+
+    let request = template.render(&[("id", "7")]).unwrap();
+    assert_eq!(
+        request.headers(),
+        [("x-name".to_string(), "café".to_string())]
+    );
+    request.send(&mut client).unwrap();
+    assert_eq!(server.requests()[0].header("x-name"), Some("café"));
+}
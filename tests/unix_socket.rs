@@ -0,0 +1,205 @@
+#![cfg(unix)]
+
+use peakrequests::{PeakError, PeakRequests};
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Write};
+use std::os::unix::net::UnixListener;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+// a socket under the temp dir that answers one request per answer, in order, and
+// keeps the request heads it was sent
+struct Socket {
+    path: PathBuf,
+    heads: Arc<Mutex<Vec<String>>>,
+}
+
+impl Socket {
+    fn answering(answer: &[u8]) -> Socket {
+        Socket::answering_each(&[answer])
+    }
+
+    fn answering_each(answers: &[&[u8]]) -> Socket {
+        static NEXT: AtomicUsize = AtomicUsize::new(0);
+        let path = std::env::temp_dir().join(format!(
+            "peakreq-test-{}-{}.sock",
+            std::process::id(),
+            NEXT.fetch_add(1, Ordering::SeqCst)
+        ));
+        let _ = std::fs::remove_file(&path);
+        let listener = UnixListener::bind(&path).unwrap();
+        let answers: Vec<Vec<u8>> = answers.iter().map(|answer| answer.to_vec()).collect();
+        let heads = Arc::new(Mutex::new(Vec::new()));
+        let seen = Arc::clone(&heads);
+        thread::spawn(move || {
+            for answer in answers {
+                let Ok((stream, _)) = listener.accept() else {
+                    return;
+                };
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                let mut head = String::new();
+                let mut line = String::new();
+                while reader.read_line(&mut line).unwrap_or(0) > 0 && line != "\r\n" {
+                    head.push_str(&line);
+                    line.clear();
+                }
+                seen.lock().unwrap().push(head);
+                let mut stream = stream;
+                let _ = stream.write_all(&answer);
+            }
+        });
+        Socket { path, heads }
+    }
+
+    fn heads(&self) -> Vec<String> {
+        self.heads.lock().unwrap().clone()
+    }
+
+    fn get(&self, client: PeakRequests) -> Result<peakrequests::Response, PeakError> {
+        client
+            .unix_socket(&self.path)
+            .get("http://localhost/containers/json")
+    }
+}
+
+impl Drop for Socket {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
+#[test]
+fn interim_heads_are_skipped() {
+    let socket = Socket::answering(
+        b"HTTP/1.1 100 Continue\r\n\r\n\
+          HTTP/1.1 103 Early Hints\r\nLink: </style.css>; rel=preload\r\n\r\n\
+          HTTP/1.1 200 OK\r\nContent-Length: 2\r\n\r\n[]",
+    );
+
+    let resp = socket.get(PeakRequests::new()).unwrap();
+    assert_eq!(resp.status_code, 200);
+    assert_eq!(resp.text(), "[]");
+    assert!(!resp.headers.contains_key("link"));
+}
+
+#[test]
+fn announced_length_over_the_cap_is_refused() {
+    let socket = Socket::answering(b"HTTP/1.1 200 OK\r\nContent-Length: 1000000000000000\r\n\r\n");
+
+    let result = socket.get(PeakRequests::new());
+    assert!(matches!(result, Err(PeakError::BodyTooLarge { .. })));
+}
+
+#[test]
+fn chunk_sizes_cannot_overflow() {
+    let socket = Socket::answering(
+        b"HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n\
+          2\r\nab\r\nffffffffffffffff\r\n",
+    );
+
+    let result = socket.get(PeakRequests::new());
+    assert!(matches!(result, Err(PeakError::BodyTooLarge { .. })));
+}
+
+#[test]
+fn configured_cap_applies_to_every_framing() {
+    let chunked =
+        b"HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n3\r\nabc\r\n3\r\ndef\r\n0\r\n\r\n";
+    let unframed = b"HTTP/1.1 200 OK\r\n\r\nabcdef";
+
+    for answer in [&chunked[..], &unframed[..]] {
+        let client = PeakRequests::new().unix_socket_max_body(6);
+        assert_eq!(
+            Socket::answering(answer).get(client).unwrap().text(),
+            "abcdef"
+        );
+
+        let client = PeakRequests::new().unix_socket_max_body(5);
+        let result = Socket::answering(answer).get(client);
+        assert!(matches!(result, Err(PeakError::BodyTooLarge { limit: 5 })));
+    }
+}
+
+#[test]
+fn short_body_is_malformed() {
+    let socket = Socket::answering(b"HTTP/1.1 200 OK\r\nContent-Length: 10\r\n\r\nabc");
+
+    let result = socket.get(PeakRequests::new());
+    assert!(matches!(result, Err(PeakError::MalformedResponse { .. })));
+}
+
+#[test]
+fn credentials_stay_behind_on_a_hop_to_another_host() {
+    let socket = Socket::answering_each(&[
+        b"HTTP/1.1 302 Found\r\nLocation: http://elsewhere/next\r\nContent-Length: 0\r\n\r\n",
+        b"HTTP/1.1 200 OK\r\nContent-Length: 2\r\n\r\nok",
+    ]);
+    let mut headers = HashMap::new();
+    headers.insert("Authorization".to_string(), "Bearer secret".to_string());
+    headers.insert("X-Trace".to_string(), "abc".to_string());
+
+    let resp = socket.get(PeakRequests::new().headers(headers)).unwrap();
+    assert_eq!(resp.text(), "ok");
+
+    let heads = socket.heads();
+    assert_eq!(heads.len(), 2);
+    assert!(
+        heads[0].contains("authorization: Bearer secret"),
+        "{}",
+        heads[0]
+    );
+    assert!(heads[1].starts_with("GET /next "), "{}", heads[1]);
+    assert!(
+        !heads[1].to_ascii_lowercase().contains("authorization"),
+        "{}",
+        heads[1]
+    );
+    assert!(heads[1].contains("x-trace: abc"), "{}", heads[1]);
+}
+
+#[test]
+fn non_ascii_header_values_go_out_as_sent() {
+    let socket = Socket::answering(b"HTTP/1.1 200 OK\r\nContent-Length: 0\r\n\r\n");
+    let mut headers = HashMap::new();
+    headers.insert("X-Name".to_string(), "caf\u{e9}".to_string());
+
+    socket.get(PeakRequests::new().headers(headers)).unwrap();
+    assert!(
+        socket.heads()[0].contains("x-name: caf\u{e9}"),
+        "{:?}",
+        socket.heads()
+    );
+}
+
+#[test]
+fn an_endless_response_head_is_cut_off() {
+    let mut answer = b"HTTP/1.1 200 OK\r\nX-Long: ".to_vec();
+    answer.extend(std::iter::repeat_n(b'a', 64 * 1024));
+    let socket = Socket::answering(&answer);
+
+    let result = socket.get(PeakRequests::new().max_response_header_size(1024));
+    assert!(
+        matches!(result, Err(PeakError::HeadersTooLarge { size, .. }) if size == 1025),
+        "{:?}",
+        result
+    );
+}
+
+#[test]
+fn too_many_response_headers_are_refused() {
+    let mut answer = b"HTTP/1.1 200 OK\r\n".to_vec();
+    for i in 0..20 {
+        answer.extend(format!("X-{}: a\r\n", i).into_bytes());
+    }
+    answer.extend(b"Content-Length: 0\r\n\r\n");
+    let socket = Socket::answering(&answer);
+
+    let result = socket.get(PeakRequests::new().max_header_count(10));
+    assert!(
+        matches!(result, Err(PeakError::HeadersTooLarge { count: 11, .. })),
+        "{:?}",
+        result
+    );
+}
@@ -0,0 +1,118 @@
+use peakrequests::{AuthScheme, PeakRequests};
+use std::collections::HashMap;
+use std::time::Duration;
+
+// the (setting, conflicts_with) pairs validate() reports
+fn conflicts(client: &PeakRequests) -> Vec<(&'static str, &'static str)> {
+    client
+        .validate()
+        .into_iter()
+        .map(|warning| (warning.setting, warning.conflicts_with))
+        .collect()
+}
+
+#[test]
+fn a_clean_config_has_no_warnings() {
+    let client = PeakRequests::new()
+        .timeout(10)
+        .allow_redirects(true)
+        .on_redirect(|_| {})
+        .http2_adaptive_window(true);
+    assert!(client.validate().is_empty(), "{:?}", client.validate());
+}
+
+#[test]
+fn http2_tuning_under_http1_only() {
+    let client = PeakRequests::new()
+        .http1_only(true)
+        .http2_initial_stream_window_size(1 << 20);
+    assert_eq!(
+        conflicts(&client),
+        [("http2_initial_stream_window_size", "http1_only")]
+    );
+}
+
+#[test]
+fn a_fixed_window_under_the_adaptive_one() {
+    let client = PeakRequests::new()
+        .http2_adaptive_window(true)
+        .http2_initial_stream_window_size(1 << 20);
+    assert_eq!(
+        conflicts(&client),
+        [("http2_initial_stream_window_size", "http2_adaptive_window")]
+    );
+}
+
+#[test]
+fn a_redirect_hook_without_redirects() {
+    let client = PeakRequests::new()
+        .allow_redirects(false)
+        .on_redirect(|_| {});
+    assert_eq!(
+        conflicts(&client),
+        [("on_redirect", "allow_redirects(false)")]
+    );
+}
+
+#[test]
+fn a_fixed_cookie_header_next_to_the_jar() {
+    let mut headers = HashMap::new();
+    headers.insert("Cookie".to_string(), "sid=1".to_string());
+    let client = PeakRequests::new().cookies(true).headers(headers);
+    assert_eq!(conflicts(&client), [("headers(Cookie)", "cookies(true)")]);
+}
+
+#[test]
+fn auth_next_to_a_token_provider() {
+    let client = PeakRequests::new()
+        .auth(AuthScheme::Basic {
+            username: "user".to_string(),
+            password: "pass".to_string(),
+            preemptive: true,
+        })
+        .token_provider(|_| Ok("fresh".to_string()));
+    assert_eq!(conflicts(&client), [("auth", "token_provider")]);
+}
+
+#[test]
+fn keepalive_pings_without_keep_alive() {
+    let client = PeakRequests::new()
+        .disable_keep_alive(true)
+        .keepalive_ping(Duration::from_secs(30), "http://127.0.0.1:1/");
+    assert_eq!(
+        conflicts(&client),
+        [("keepalive_ping", "disable_keep_alive")]
+    );
+}
+
+#[test]
+fn a_long_get_threshold_past_the_url_limit() {
+    let client = PeakRequests::new()
+        .max_url_length(1000)
+        .convert_long_get_to_post(2000, "X-HTTP-Method-Override");
+    assert_eq!(
+        conflicts(&client),
+        [("convert_long_get_to_post", "max_url_length")]
+    );
+}
+
+#[test]
+fn every_conflict_is_reported() {
+    let client = PeakRequests::new()
+        .http1_only(true)
+        .http2_adaptive_window(true)
+        .allow_redirects(false)
+        .on_redirect(|_| {});
+    let warnings = client.validate();
+    assert_eq!(
+        conflicts(&client),
+        [
+            ("http2_adaptive_window", "http1_only"),
+            ("on_redirect", "allow_redirects(false)"),
+        ]
+    );
+    assert_eq!(
+        warnings[1].to_string(),
+        "on_redirect conflicts with allow_redirects(false): redirects aren't followed, so the hook is never called"
+    );
+}